@@ -0,0 +1,3 @@
+-- Refresh token rotation counter, used to detect reuse of rotated tokens
+ALTER TABLE auth.sessions
+    ADD COLUMN refresh_token_generation INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- Refresh tokens are now opaque and only their SHA-256 digest is stored.
+-- Sessions created before this hold the raw refresh JWT in refresh_token_hash:
+-- replace it with its digest and revoke the session, so the leaked bearer
+-- tokens can neither be read back nor used. Run this once on existing databases.
+UPDATE auth.sessions
+SET refresh_token_hash = encode(digest(refresh_token_hash, 'sha256'), 'hex'),
+    is_revoked = TRUE,
+    revoked_reason = COALESCE(revoked_reason, 'token_format_migration'),
+    revoked_at = COALESCE(revoked_at, NOW())
+WHERE refresh_token_hash LIKE 'eyJ%';
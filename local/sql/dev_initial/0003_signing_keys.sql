@@ -0,0 +1,16 @@
+-- JWT signing keys, rotated through next -> active -> retiring -> retired.
+-- Private keys are stored encrypted and wiped once a key is retired.
+CREATE TABLE auth.signing_keys (
+    kid VARCHAR(64) PRIMARY KEY,
+    algorithm VARCHAR(16) NOT NULL,
+    key_state VARCHAR(16) NOT NULL CHECK (key_state IN ('next', 'active', 'retiring', 'retired')),
+    encrypted_private_key BYTEA NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    activated_at TIMESTAMPTZ NULL,
+    retire_after TIMESTAMPTZ NULL,
+    retired_at TIMESTAMPTZ NULL
+);
+
+-- At most one key signs new tokens, and at most one waits to take over
+CREATE UNIQUE INDEX idx_signing_keys_active ON auth.signing_keys (key_state)
+    WHERE key_state IN ('next', 'active');
@@ -0,0 +1,10 @@
+-- Registered OAuth clients, e.g. resource servers calling the introspection endpoint
+CREATE TABLE auth.clients (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    client_id VARCHAR(255) NOT NULL UNIQUE,
+    client_secret_hash TEXT NULL,  -- NULL for public clients
+    name VARCHAR(255) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,20 @@
+-- Where a client may receive authorization codes, matched exactly
+ALTER TABLE auth.clients
+    ADD COLUMN redirect_uris TEXT[] NOT NULL DEFAULT '{}';
+
+-- Short-lived, single-use authorization codes; only their SHA-256 digest is stored
+CREATE TABLE auth.authorization_codes (
+    code_hash CHAR(64) PRIMARY KEY,
+    client_id VARCHAR(255) NOT NULL REFERENCES auth.clients(client_id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES auth.users(id) ON DELETE CASCADE,
+    redirect_uri TEXT NOT NULL,
+    scope TEXT NOT NULL DEFAULT '',
+    nonce TEXT NULL,
+    code_challenge VARCHAR(128) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ NULL,
+    session_id UUID NULL REFERENCES auth.sessions(id) ON DELETE SET NULL,  -- set once exchanged
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_authorization_codes_expires_at ON auth.authorization_codes(expires_at);
@@ -0,0 +1,10 @@
+-- What each client may ask for, and optional per-client token lifetimes
+ALTER TABLE auth.clients
+    ADD COLUMN grant_types TEXT[] NOT NULL DEFAULT '{authorization_code,refresh_token}',
+    ADD COLUMN scopes TEXT[] NOT NULL DEFAULT '{openid,email}',
+    ADD COLUMN access_token_lifetime INTEGER NULL CHECK (access_token_lifetime > 0),   -- seconds, NULL uses the server default
+    ADD COLUMN refresh_token_lifetime INTEGER NULL CHECK (refresh_token_lifetime > 0); -- seconds, NULL uses the server default
+
+-- The client a session was issued to, NULL for first-party logins
+ALTER TABLE auth.sessions
+    ADD COLUMN client_id VARCHAR(255) NULL REFERENCES auth.clients(client_id) ON DELETE CASCADE;
@@ -0,0 +1,23 @@
+-- Public keys of clients authenticating with private_key_jwt (RFC 7523)
+ALTER TABLE auth.clients
+    ADD COLUMN jwks JSONB NULL;
+
+-- What a client may do on its own behalf, copied into `resource_access` of its tokens
+CREATE TABLE auth.client_permissions (
+    client_id VARCHAR(255) NOT NULL REFERENCES auth.clients(client_id) ON DELETE CASCADE,
+    resource_server VARCHAR(255) NOT NULL,
+    resource VARCHAR(255) NOT NULL,
+    actions TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (client_id, resource_server, resource)
+);
+
+-- Client assertions already used, kept until they expire to stop replays
+CREATE TABLE auth.client_assertions (
+    client_id VARCHAR(255) NOT NULL REFERENCES auth.clients(client_id) ON DELETE CASCADE,
+    jti VARCHAR(255) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (client_id, jti)
+);
+
+CREATE INDEX idx_client_assertions_expires_at ON auth.client_assertions(expires_at);
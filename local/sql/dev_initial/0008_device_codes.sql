@@ -0,0 +1,18 @@
+-- Device authorization requests (RFC 8628); only the SHA-256 digest of the device code is stored
+CREATE TABLE auth.device_codes (
+    device_code_hash CHAR(64) PRIMARY KEY,
+    user_code VARCHAR(16) NOT NULL UNIQUE,  -- normalized, without separator
+    client_id VARCHAR(255) NOT NULL REFERENCES auth.clients(client_id) ON DELETE CASCADE,
+    scope TEXT NOT NULL DEFAULT '',
+    polling_interval INTEGER NOT NULL,  -- seconds, raised on every slow_down
+    last_polled_at TIMESTAMPTZ NULL,
+    user_id UUID NULL REFERENCES auth.users(id) ON DELETE CASCADE,  -- set once approved or denied
+    approved_at TIMESTAMPTZ NULL,
+    denied_at TIMESTAMPTZ NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ NULL,
+    session_id UUID NULL REFERENCES auth.sessions(id) ON DELETE SET NULL,  -- set once exchanged
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_device_codes_expires_at ON auth.device_codes(expires_at);
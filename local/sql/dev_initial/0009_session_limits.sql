@@ -0,0 +1,3 @@
+-- Per-user cap on concurrent sessions; NULL uses the server default, 0 means unlimited
+ALTER TABLE auth.users
+    ADD COLUMN max_sessions INTEGER NULL CHECK (max_sessions >= 0);
@@ -0,0 +1,7 @@
+-- How strongly the user authenticated when the session was created ('password', 'mfa')
+ALTER TABLE auth.sessions
+    ADD COLUMN acr VARCHAR(50) NOT NULL DEFAULT 'password';
+
+-- Device sessions get the acr of the session that approved them
+ALTER TABLE auth.device_codes
+    ADD COLUMN acr VARCHAR(50) NULL;
@@ -0,0 +1,25 @@
+-- Access tokens revoked before they expire, by token id (jti) or session id (sid)
+CREATE TABLE auth.revoked_tokens (
+    kind VARCHAR(3) NOT NULL CHECK (kind IN ('jti', 'sid')),
+    value VARCHAR(255) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,  -- once every matching token has expired
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (kind, value)
+);
+
+CREATE INDEX idx_revoked_tokens_expires_at ON auth.revoked_tokens(expires_at);
+
+-- Every replica keeps its denylist in sync by listening on this channel
+CREATE FUNCTION auth.notify_token_revoked() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify(
+        'token_revoked',
+        json_build_object('kind', NEW.kind, 'value', NEW.value, 'expires_at', NEW.expires_at)::TEXT
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER revoked_tokens_notify
+    AFTER INSERT OR UPDATE ON auth.revoked_tokens
+    FOR EACH ROW EXECUTE FUNCTION auth.notify_token_revoked();
@@ -0,0 +1,66 @@
+-- Schema for resource based access control
+CREATE SCHEMA iam;
+
+-- Kinds of resources a resource server protects, e.g. `channel` of the chat server
+CREATE TABLE iam.resource_types (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    resource_server VARCHAR(255) NOT NULL,  -- key of `resource_access` in tokens
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (resource_server, name)
+);
+
+-- What can be done to resources of a type, e.g. `read` or `write`
+CREATE TABLE iam.actions (
+    resource_type_id UUID NOT NULL REFERENCES iam.resource_types(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    PRIMARY KEY (resource_type_id, name)
+);
+
+-- Individual resources, named `<type>/<name>` in tokens
+CREATE TABLE iam.resources (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    resource_type_id UUID NOT NULL REFERENCES iam.resource_types(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (resource_type_id, name),
+    UNIQUE (id, resource_type_id)
+);
+
+-- Named sets of actions on one resource type, e.g. `editor` of channels
+CREATE TABLE iam.roles (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    resource_type_id UUID NOT NULL REFERENCES iam.resource_types(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (resource_type_id, name),
+    UNIQUE (id, resource_type_id)
+);
+
+CREATE TABLE iam.role_actions (
+    role_id UUID NOT NULL,
+    resource_type_id UUID NOT NULL,
+    action VARCHAR(100) NOT NULL,
+    PRIMARY KEY (role_id, action),
+    FOREIGN KEY (role_id, resource_type_id)
+        REFERENCES iam.roles(id, resource_type_id) ON DELETE CASCADE,
+    FOREIGN KEY (resource_type_id, action)
+        REFERENCES iam.actions(resource_type_id, name) ON DELETE CASCADE
+);
+
+-- Roles held by principals on resources of the role's type
+CREATE TABLE iam.role_assignments (
+    principal_type VARCHAR(20) NOT NULL CHECK (principal_type IN ('user')),
+    principal_id UUID NOT NULL,
+    role_id UUID NOT NULL,
+    resource_id UUID NOT NULL,
+    resource_type_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (principal_type, principal_id, role_id, resource_id),
+    FOREIGN KEY (role_id, resource_type_id)
+        REFERENCES iam.roles(id, resource_type_id) ON DELETE CASCADE,
+    FOREIGN KEY (resource_id, resource_type_id)
+        REFERENCES iam.resources(id, resource_type_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_role_assignments_resource_id ON iam.role_assignments(resource_id);
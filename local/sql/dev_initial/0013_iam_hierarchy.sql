@@ -0,0 +1,29 @@
+-- Resources form a tree, e.g. org -> project -> channel; grants on a parent apply to its children
+ALTER TABLE iam.resources
+    ADD COLUMN parent_id UUID NULL REFERENCES iam.resources(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_resources_parent_id ON iam.resources(parent_id);
+
+-- Turns a `*` wildcard pattern into a LIKE pattern, escaping LIKE's own wildcards
+CREATE FUNCTION iam.glob_to_like(pattern TEXT) RETURNS TEXT AS $$
+    SELECT replace(replace(replace(replace(pattern, '\', '\\'), '%', '\%'), '_', '\_'), '*', '%')
+$$ LANGUAGE SQL IMMUTABLE;
+
+-- Roles are assigned on a single resource or on every resource of the role's
+-- type whose name matches a pattern, and either allow or deny their actions
+ALTER TABLE iam.role_assignments
+    DROP CONSTRAINT role_assignments_pkey,
+    ADD COLUMN id UUID NOT NULL DEFAULT uuid_generate_v4(),
+    ALTER COLUMN resource_id DROP NOT NULL,
+    ADD COLUMN name_pattern VARCHAR(255) NULL,  -- e.g. `*` or `team-*`
+    ADD COLUMN effect VARCHAR(5) NOT NULL DEFAULT 'allow' CHECK (effect IN ('allow', 'deny')),
+    ADD CONSTRAINT role_assignments_target CHECK ((resource_id IS NULL) <> (name_pattern IS NULL));
+
+ALTER TABLE iam.role_assignments ADD PRIMARY KEY (id);
+
+CREATE UNIQUE INDEX uq_role_assignments_resource
+    ON iam.role_assignments(principal_type, principal_id, role_id, resource_id, effect)
+    WHERE resource_id IS NOT NULL;
+CREATE UNIQUE INDEX uq_role_assignments_pattern
+    ON iam.role_assignments(principal_type, principal_id, role_id, resource_type_id, name_pattern, effect)
+    WHERE name_pattern IS NOT NULL;
@@ -0,0 +1,38 @@
+-- Groups of users and other groups, which roles can be assigned to
+CREATE TABLE iam.groups (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(255) NOT NULL UNIQUE,
+    description TEXT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Direct members of a group; members of a member group belong to it too
+CREATE TABLE iam.group_members (
+    group_id UUID NOT NULL REFERENCES iam.groups(id) ON DELETE CASCADE,
+    member_type VARCHAR(20) NOT NULL CHECK (member_type IN ('user', 'group')),
+    member_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_id, member_type, member_id),
+    CHECK (member_type <> 'group' OR member_id <> group_id)
+);
+
+CREATE INDEX idx_group_members_member ON iam.group_members(member_type, member_id);
+
+ALTER TABLE iam.role_assignments
+    DROP CONSTRAINT role_assignments_principal_type_check,
+    ADD CONSTRAINT role_assignments_principal_type_check
+        CHECK (principal_type IN ('user', 'group'));
+
+-- A principal along with every group it belongs to, directly or through
+-- other groups. UNION keeps it finite should a cycle ever slip in.
+CREATE FUNCTION iam.effective_principals(kind TEXT, id UUID)
+RETURNS TABLE (principal_type TEXT, principal_id UUID) AS $$
+    WITH RECURSIVE principals(principal_type, principal_id) AS (
+        SELECT $1, $2
+        UNION
+        SELECT 'group'::TEXT, m.group_id
+        FROM iam.group_members m
+        JOIN principals p ON m.member_type = p.principal_type AND m.member_id = p.principal_id
+    )
+    SELECT principal_type, principal_id FROM principals
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,3 @@
+-- Audiences each client may exchange tokens for, besides the subject token's own
+ALTER TABLE auth.clients
+    ADD COLUMN audiences TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,11 @@
+-- When the user last authenticated, existing sessions at their creation
+ALTER TABLE auth.sessions
+    ADD COLUMN auth_time TIMESTAMPTZ NULL;
+UPDATE auth.sessions SET auth_time = created_at;
+ALTER TABLE auth.sessions
+    ALTER COLUMN auth_time SET NOT NULL,
+    ALTER COLUMN auth_time SET DEFAULT NOW();
+
+-- Device sessions get the auth_time of the session that approved them
+ALTER TABLE auth.device_codes
+    ADD COLUMN auth_time TIMESTAMPTZ NULL;
@@ -0,0 +1,16 @@
+-- The scope a user granted the client a session was created for, NULL for
+-- first-party sessions, whose tokens carry the user's access range instead
+ALTER TABLE auth.sessions
+    ADD COLUMN scope TEXT NULL;
+
+UPDATE auth.sessions s SET scope = c.scope
+FROM auth.authorization_codes c
+WHERE c.session_id = s.id;
+
+UPDATE auth.sessions s SET scope = d.scope
+FROM auth.device_codes d
+WHERE d.session_id = s.id;
+
+-- Client sessions whose grant is gone keep no scope
+UPDATE auth.sessions SET scope = ''
+WHERE client_id IS NOT NULL AND scope IS NULL;
@@ -0,0 +1,6 @@
+-- How and when the user signed in on the authorization form, and the
+-- max_age the client asked for, checked again when the code is exchanged
+ALTER TABLE auth.authorization_codes
+    ADD COLUMN acr VARCHAR(50) NOT NULL DEFAULT 'password',
+    ADD COLUMN auth_time TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN max_age INTEGER NULL;
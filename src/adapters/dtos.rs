@@ -1,7 +1,11 @@
 /* DTOs */
 
+use chrono::Duration;
+use jsonwebtoken::jwk::JwkSet;
 use uuid::Uuid;
 
+use crate::domain::models::{Acr, Client, GrantType, Resource};
+
 pub enum SignupDto {
     EmailPassord { email: String, password: String },
 }
@@ -10,7 +14,15 @@ pub struct AuthUserDto {
     pub id: Uuid,
     pub email: String,
     pub password_hash: String,
+    pub email_verified: bool,
     pub access_range: String,
+    pub max_sessions: Option<i32>, // None uses the server default
+}
+
+/// Set when a client asked for the `openid` scope and expects an ID token.
+#[derive(Debug, Default)]
+pub struct OpenIdRequest {
+    pub nonce: Option<String>,
 }
 
 pub struct DeviceInfo {
@@ -19,3 +31,98 @@ pub struct DeviceInfo {
     pub browser: String,
     pub os: String,
 }
+
+/// Tokens handed out when a session is created.
+pub struct SessionTokens {
+    pub session_id: Uuid,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub id_token: Option<String>,
+}
+
+/// A validated OAuth authorization request, see RFC 6749 section 4.1.1.
+#[derive(Debug)]
+pub struct AuthorizationRequest {
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: String,
+    pub max_age: Option<i32>, // seconds the user's sign-in may be old at most
+    pub acr: Option<Acr>,     // weakest acr the client accepts
+}
+
+/// A started device authorization, see RFC 8628 section 3.2.
+#[derive(Debug)]
+pub struct DeviceAuthorization {
+    pub device_code: String,
+    pub user_code: String, // normalized, format it with `user_code::display`
+    pub expires_in: i64,   // seconds
+    pub interval: i64,     // seconds
+}
+
+/// A token exchange request, see RFC 8693 section 2.1.
+#[derive(Debug)]
+pub struct TokenExchangeRequest {
+    pub subject_token: String,
+    pub actor_token: Option<String>,
+    pub audience: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// Registration metadata an admin sets on a client.
+#[derive(Debug)]
+pub struct ClientSettings {
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<GrantType>,
+    pub scopes: Vec<String>,
+    pub audiences: Vec<String>,
+    pub access_token_lifetime: Option<i32>,
+    pub refresh_token_lifetime: Option<i32>,
+    pub jwks: Option<JwkSet>,
+}
+
+/// One question of an authorization check: may the principal take `action`
+/// on the resource?
+#[derive(Debug, Clone, Copy)]
+pub struct ActionCheck<'a> {
+    pub resource_type: &'a str,
+    pub resource_name: &'a str,
+    pub action: &'a str,
+}
+
+/// A page of resources, continued by passing `next_cursor` back.
+#[derive(Debug)]
+pub struct ResourcePage {
+    pub resources: Vec<Resource>,
+    pub next_cursor: Option<Uuid>, // None on the last page
+}
+
+/// What adding a member to a group came to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberAddition {
+    Added,
+    AlreadyMember,
+    Cycle, // the member group already contains the group
+}
+
+/// How many sessions a user may hold, checked as a new one is created.
+/// Sessions count until they end, see `Session::ends_at`.
+#[derive(Debug, Clone)]
+pub struct SessionLimit {
+    pub max_sessions: usize,
+    /// Reason recorded on the least recently active sessions revoked to make
+    /// room. Without one, the new session is refused instead.
+    pub evicted_reason: Option<String>,
+    pub idle_timeout: Duration,
+    pub absolute_lifetime: Duration,
+}
+
+/// A client a user signs in to, with the scope they granted it.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientGrant<'a> {
+    pub client: &'a Client,
+    pub scope: &'a str,
+}
@@ -0,0 +1,118 @@
+use crate::config::database::PgPool;
+use crate::domain::models::AuthorizationCode;
+use std::sync::Arc;
+use tokio_postgres::types::ToSql;
+use uuid::Uuid;
+
+use super::Result;
+
+#[async_trait::async_trait]
+pub trait AuthorizationCodeRepository {
+    async fn create_code(&self, code: &AuthorizationCode) -> Result<()>;
+    async fn consume_code(&self, code_hash: &str) -> Result<Option<AuthorizationCode>>;
+    async fn find_code(&self, code_hash: &str) -> Result<Option<AuthorizationCode>>;
+    async fn attach_session(&self, code_hash: &str, session_id: Uuid) -> Result<()>;
+}
+
+pub struct PgAuthorizationCodeRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgAuthorizationCodeRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuthorizationCodeRepository for PgAuthorizationCodeRepository {
+    async fn create_code(&self, code: &AuthorizationCode) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let query = "
+            INSERT INTO auth.authorization_codes (
+                code_hash, client_id, user_id, redirect_uri,
+                scope, nonce, code_challenge, expires_at,
+                acr, auth_time, max_age
+            ) VALUES (
+                $1, $2, $3, $4,
+                $5, $6, $7, $8,
+                $9, $10, $11
+            )
+        ";
+        let acr = code.acr.to_string();
+        let params: Vec<&(dyn ToSql + Sync)> = vec![
+            &code.code_hash,
+            &code.client_id,
+            &code.user_id,
+            &code.redirect_uri,
+            &code.scope,
+            &code.nonce,
+            &code.code_challenge,
+            &code.expires_at,
+            &acr,
+            &code.auth_time,
+            &code.max_age,
+        ];
+
+        conn.execute(query, &params).await?;
+        Ok(())
+    }
+
+    /// Marks an unexpired code as used and returns it.
+    /// Returns `None` if the code is unknown, expired or was already used.
+    async fn consume_code(&self, code_hash: &str) -> Result<Option<AuthorizationCode>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.authorization_codes
+            SET consumed_at = NOW()
+            WHERE code_hash = $1 AND consumed_at IS NULL AND expires_at > NOW()
+            RETURNING *
+        ";
+
+        let row = conn.query_opt(query, &[&code_hash]).await?;
+        Ok(row.map(AuthorizationCode::from_row))
+    }
+
+    async fn find_code(&self, code_hash: &str) -> Result<Option<AuthorizationCode>> {
+        let conn = self.pool.get().await?;
+        let query = "SELECT * FROM auth.authorization_codes WHERE code_hash = $1";
+
+        let row = conn.query_opt(query, &[&code_hash]).await?;
+        Ok(row.map(AuthorizationCode::from_row))
+    }
+
+    /// Records the session a code was exchanged for, so a replayed code can revoke it.
+    async fn attach_session(&self, code_hash: &str, session_id: Uuid) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.authorization_codes
+            SET session_id = $1
+            WHERE code_hash = $2
+        ";
+
+        conn.execute(query, &[&session_id, &code_hash]).await?;
+        Ok(())
+    }
+}
+
+impl AuthorizationCode {
+    /// Converts a `tokio_postgres::Row` into an `AuthorizationCode`
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        Self {
+            code_hash: row.get("code_hash"),
+            client_id: row.get("client_id"),
+            user_id: row.get("user_id"),
+            redirect_uri: row.get("redirect_uri"),
+            scope: row.get("scope"),
+            nonce: row.get("nonce"),
+            code_challenge: row.get("code_challenge"),
+            acr: row.get::<_, String>("acr").parse().unwrap_or_default(),
+            auth_time: row.get("auth_time"),
+            max_age: row.get("max_age"),
+            expires_at: row.get("expires_at"),
+            consumed_at: row.get("consumed_at"),
+            session_id: row.get("session_id"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::config::database::PgPool;
+use crate::domain::models::{Authentication, DeviceCode};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio_postgres::types::ToSql;
+use uuid::Uuid;
+
+use super::Result;
+
+#[async_trait::async_trait]
+pub trait DeviceCodeRepository {
+    async fn create_device_code(&self, device_code: &DeviceCode) -> Result<bool>;
+    async fn find_pending(&self, user_code: &str) -> Result<Option<DeviceCode>>;
+    async fn decide(
+        &self,
+        user_code: &str,
+        user_id: Uuid,
+        approved: bool,
+        authentication: Authentication,
+    ) -> Result<bool>;
+    async fn record_poll(
+        &self,
+        device_code_hash: &str,
+    ) -> Result<Option<(DeviceCode, Option<DateTime<Utc>>)>>;
+    async fn slow_down(&self, device_code_hash: &str, step: i32) -> Result<()>;
+    async fn consume_device_code(&self, device_code_hash: &str) -> Result<Option<DeviceCode>>;
+    async fn attach_session(&self, device_code_hash: &str, session_id: Uuid) -> Result<()>;
+}
+
+pub struct PgDeviceCodeRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgDeviceCodeRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl DeviceCodeRepository for PgDeviceCodeRepository {
+    /// Stores a new request, dropping expired ones so their user codes can be reused.
+    /// Returns `false` if a pending request already has the same user code.
+    async fn create_device_code(&self, device_code: &DeviceCode) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        conn.execute(
+            "DELETE FROM auth.device_codes WHERE expires_at < NOW()",
+            &[],
+        )
+        .await?;
+
+        let query = "
+            INSERT INTO auth.device_codes (
+                device_code_hash, user_code, client_id, scope,
+                polling_interval, expires_at
+            ) VALUES (
+                $1, $2, $3, $4,
+                $5, $6
+            )
+            ON CONFLICT (user_code) DO NOTHING
+        ";
+        let params: Vec<&(dyn ToSql + Sync)> = vec![
+            &device_code.device_code_hash,
+            &device_code.user_code,
+            &device_code.client_id,
+            &device_code.scope,
+            &device_code.polling_interval,
+            &device_code.expires_at,
+        ];
+
+        let created = conn.execute(query, &params).await?;
+        Ok(created == 1)
+    }
+
+    /// Finds an unexpired request the user has not decided on yet.
+    async fn find_pending(&self, user_code: &str) -> Result<Option<DeviceCode>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            SELECT * FROM auth.device_codes
+            WHERE user_code = $1 AND user_id IS NULL AND expires_at > NOW()
+        ";
+
+        let row = conn.query_opt(query, &[&user_code]).await?;
+        Ok(row.map(DeviceCode::from_row))
+    }
+
+    /// Records a user's decision on a pending request, along with how the
+    /// user authenticated. Returns `false` if the request is unknown, expired or already decided.
+    async fn decide(
+        &self,
+        user_code: &str,
+        user_id: Uuid,
+        approved: bool,
+        authentication: Authentication,
+    ) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.device_codes
+            SET user_id = $1,
+                approved_at = CASE WHEN $2 THEN NOW() END,
+                denied_at = CASE WHEN $2 THEN NULL ELSE NOW() END,
+                acr = $3,
+                auth_time = $4
+            WHERE user_code = $5 AND user_id IS NULL AND expires_at > NOW()
+        ";
+
+        let acr = authentication.acr.to_string();
+        let updated = conn
+            .execute(
+                query,
+                &[
+                    &user_id,
+                    &approved,
+                    &acr,
+                    &authentication.auth_time,
+                    &user_code,
+                ],
+            )
+            .await?;
+        Ok(updated == 1)
+    }
+
+    /// Marks a request as polled now and returns it with the time of the previous poll.
+    async fn record_poll(
+        &self,
+        device_code_hash: &str,
+    ) -> Result<Option<(DeviceCode, Option<DateTime<Utc>>)>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            WITH previous AS (
+                SELECT device_code_hash, last_polled_at
+                FROM auth.device_codes
+                WHERE device_code_hash = $1
+                FOR UPDATE
+            )
+            UPDATE auth.device_codes d
+            SET last_polled_at = NOW()
+            FROM previous
+            WHERE d.device_code_hash = previous.device_code_hash
+            RETURNING d.*, previous.last_polled_at AS previous_poll
+        ";
+
+        let row = conn.query_opt(query, &[&device_code_hash]).await?;
+        Ok(row.map(|row| {
+            let previous_poll = row.get("previous_poll");
+            (DeviceCode::from_row(row), previous_poll)
+        }))
+    }
+
+    async fn slow_down(&self, device_code_hash: &str, step: i32) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.device_codes
+            SET polling_interval = polling_interval + $1
+            WHERE device_code_hash = $2
+        ";
+
+        conn.execute(query, &[&step, &device_code_hash]).await?;
+        Ok(())
+    }
+
+    /// Marks an approved, unexpired request as used and returns it.
+    /// Returns `None` if it is not approved, expired or was already used.
+    async fn consume_device_code(&self, device_code_hash: &str) -> Result<Option<DeviceCode>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.device_codes
+            SET consumed_at = NOW()
+            WHERE device_code_hash = $1
+                AND approved_at IS NOT NULL
+                AND consumed_at IS NULL
+                AND expires_at > NOW()
+            RETURNING *
+        ";
+
+        let row = conn.query_opt(query, &[&device_code_hash]).await?;
+        Ok(row.map(DeviceCode::from_row))
+    }
+
+    async fn attach_session(&self, device_code_hash: &str, session_id: Uuid) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.device_codes
+            SET session_id = $1
+            WHERE device_code_hash = $2
+        ";
+
+        conn.execute(query, &[&session_id, &device_code_hash])
+            .await?;
+        Ok(())
+    }
+}
+
+impl DeviceCode {
+    /// Converts a `tokio_postgres::Row` into a `DeviceCode`
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        Self {
+            device_code_hash: row.get("device_code_hash"),
+            user_code: row.get("user_code"),
+            client_id: row.get("client_id"),
+            scope: row.get("scope"),
+            polling_interval: row.get("polling_interval"),
+            last_polled_at: row.get("last_polled_at"),
+            user_id: row.get("user_id"),
+            approved_at: row.get("approved_at"),
+            denied_at: row.get("denied_at"),
+            acr: row
+                .get::<_, Option<String>>("acr")
+                .and_then(|acr| acr.parse().ok()),
+            auth_time: row.get("auth_time"),
+            expires_at: row.get("expires_at"),
+            consumed_at: row.get("consumed_at"),
+            session_id: row.get("session_id"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
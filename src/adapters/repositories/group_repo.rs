@@ -0,0 +1,222 @@
+use crate::adapters::dtos::MemberAddition;
+use crate::config::database::PgPool;
+use crate::domain::models::{Group, GroupMember, Principal};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::Result;
+
+#[async_trait::async_trait]
+pub trait GroupRepository {
+    async fn create_group(&self, group: &Group) -> Result<bool>;
+    async fn list_groups(&self) -> Result<Vec<Group>>;
+    async fn find_group(&self, group_id: Uuid) -> Result<Option<Group>>;
+    async fn delete_group(&self, group_id: Uuid) -> Result<bool>;
+    async fn add_member(&self, group_id: Uuid, member: Principal) -> Result<MemberAddition>;
+    async fn remove_member(&self, group_id: Uuid, member: Principal) -> Result<bool>;
+    async fn list_members(&self, group_id: Uuid) -> Result<Vec<GroupMember>>;
+    async fn list_effective_groups(&self, principal: Principal) -> Result<Vec<Group>>;
+}
+
+pub struct PgGroupRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgGroupRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl GroupRepository for PgGroupRepository {
+    /// Stores a group. Returns `false` when the name is taken.
+    async fn create_group(&self, group: &Group) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "
+            INSERT INTO iam.groups (id, name, description, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (name) DO NOTHING
+        ";
+
+        let created = conn
+            .execute(
+                query,
+                &[
+                    &group.id,
+                    &group.name,
+                    &group.description,
+                    &group.created_at,
+                ],
+            )
+            .await?;
+        Ok(created == 1)
+    }
+
+    async fn list_groups(&self) -> Result<Vec<Group>> {
+        let conn = self.pool.get().await?;
+        let query = "SELECT * FROM iam.groups ORDER BY name";
+
+        let rows = conn.query(query, &[]).await?;
+        Ok(rows.into_iter().map(Group::from_row).collect())
+    }
+
+    async fn find_group(&self, group_id: Uuid) -> Result<Option<Group>> {
+        let conn = self.pool.get().await?;
+        let query = "SELECT * FROM iam.groups WHERE id = $1";
+
+        let row = conn.query_opt(query, &[&group_id]).await?;
+        Ok(row.map(Group::from_row))
+    }
+
+    /// Deletes a group along with its memberships in other groups and the
+    /// roles assigned to it. Its members stay, they just lose the group.
+    async fn delete_group(&self, group_id: Uuid) -> Result<bool> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "
+            DELETE FROM iam.role_assignments
+            WHERE principal_type = 'group' AND principal_id = $1
+            ",
+            &[&group_id],
+        )
+        .await?;
+        tx.execute(
+            "
+            DELETE FROM iam.group_members
+            WHERE member_type = 'group' AND member_id = $1
+            ",
+            &[&group_id],
+        )
+        .await?;
+        let deleted = tx
+            .execute("DELETE FROM iam.groups WHERE id = $1", &[&group_id])
+            .await?;
+
+        tx.commit().await?;
+        Ok(deleted == 1)
+    }
+
+    /// Adds a user or a group to a group. A group is not added to one of its
+    /// own members, directly or further down, as that would close a cycle.
+    async fn add_member(&self, group_id: Uuid, member: Principal) -> Result<MemberAddition> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        if let Principal::Group(member_id) = member {
+            // Nesting is serialized, two additions could close a cycle together
+            tx.execute(
+                "SELECT pg_advisory_xact_lock(hashtext('iam.group_members'))",
+                &[],
+            )
+            .await?;
+            let cycle: bool = tx
+                .query_one(
+                    "
+                    WITH RECURSIVE contained(id) AS (
+                        SELECT $1::UUID
+                        UNION
+                        SELECT m.member_id
+                        FROM iam.group_members m
+                        JOIN contained c ON m.group_id = c.id
+                        WHERE m.member_type = 'group'
+                    )
+                    SELECT EXISTS (SELECT 1 FROM contained WHERE id = $2) AS cycle
+                    ",
+                    &[&member_id, &group_id],
+                )
+                .await?
+                .get("cycle");
+            if cycle {
+                return Ok(MemberAddition::Cycle);
+            }
+        }
+
+        let added = tx
+            .execute(
+                "
+                INSERT INTO iam.group_members (group_id, member_type, member_id)
+                VALUES ($1, $2, $3)
+                ON CONFLICT DO NOTHING
+                ",
+                &[&group_id, &member.kind(), &member.id()],
+            )
+            .await?;
+
+        tx.commit().await?;
+        Ok(if added == 1 {
+            MemberAddition::Added
+        } else {
+            MemberAddition::AlreadyMember
+        })
+    }
+
+    async fn remove_member(&self, group_id: Uuid, member: Principal) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "
+            DELETE FROM iam.group_members
+            WHERE group_id = $1 AND member_type = $2 AND member_id = $3
+        ";
+
+        let removed = conn
+            .execute(query, &[&group_id, &member.kind(), &member.id()])
+            .await?;
+        Ok(removed == 1)
+    }
+
+    /// Direct members of a group, oldest first.
+    async fn list_members(&self, group_id: Uuid) -> Result<Vec<GroupMember>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            SELECT * FROM iam.group_members
+            WHERE group_id = $1
+            ORDER BY created_at, member_type, member_id
+        ";
+
+        let rows = conn.query(query, &[&group_id]).await?;
+        Ok(rows.into_iter().filter_map(GroupMember::from_row).collect())
+    }
+
+    /// Every group a principal belongs to, directly or through other groups.
+    async fn list_effective_groups(&self, principal: Principal) -> Result<Vec<Group>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            SELECT g.*
+            FROM iam.effective_principals($1, $2) p
+            JOIN iam.groups g ON g.id = p.principal_id
+            WHERE p.principal_type = 'group'
+              AND NOT (p.principal_type = $1 AND p.principal_id = $2)
+            ORDER BY g.name
+        ";
+
+        let rows = conn
+            .query(query, &[&principal.kind(), &principal.id()])
+            .await?;
+        Ok(rows.into_iter().map(Group::from_row).collect())
+    }
+}
+
+impl Group {
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        Group {
+            id: row.get("id"),
+            name: row.get("name"),
+            description: row.get("description"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
+
+impl GroupMember {
+    // Rows of unknown member types are skipped
+    fn from_row(row: tokio_postgres::Row) -> Option<Self> {
+        let member = Principal::from_parts(row.get("member_type"), row.get("member_id")).ok()?;
+        Some(GroupMember {
+            group_id: row.get("group_id"),
+            member,
+            created_at: row.get("created_at"),
+        })
+    }
+}
@@ -0,0 +1,478 @@
+use crate::adapters::dtos::ActionCheck;
+use crate::config::database::PgPool;
+use crate::domain::models::{
+    AssignmentTarget, Effect, Grant, Permission, Principal, Resource, ResourceType, Role,
+    RoleAssignment,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::Result;
+
+#[async_trait::async_trait]
+pub trait IamRepository {
+    async fn create_resource_type(&self, resource_type: &ResourceType) -> Result<()>;
+    async fn find_resource_type(
+        &self,
+        resource_server: &str,
+        name: &str,
+    ) -> Result<Option<ResourceType>>;
+    async fn create_resource(&self, resource: &Resource) -> Result<()>;
+    async fn find_resource(&self, resource_type_id: Uuid, name: &str) -> Result<Option<Resource>>;
+    async fn create_role(&self, role: &Role) -> Result<()>;
+    async fn assign_role(&self, assignment: &RoleAssignment) -> Result<bool>;
+    async fn unassign_role(
+        &self,
+        principal: Principal,
+        role_id: Uuid,
+        resource_id: Uuid,
+    ) -> Result<bool>;
+    async fn list_permissions(&self, principal: Principal) -> Result<Vec<Permission>>;
+    async fn find_grants(
+        &self,
+        principal: Principal,
+        resource_server: &str,
+        checks: &[ActionCheck<'_>],
+    ) -> Result<Vec<Option<Grant>>>;
+    async fn list_resources_with_action(
+        &self,
+        principal: Principal,
+        resource_server: &str,
+        resource_type: &str,
+        action: &str,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<Resource>>;
+}
+
+pub struct PgIamRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgIamRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl IamRepository for PgIamRepository {
+    /// Stores a resource type along with the actions it supports.
+    async fn create_resource_type(&self, resource_type: &ResourceType) -> Result<()> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "
+            INSERT INTO iam.resource_types (id, resource_server, name, created_at)
+            VALUES ($1, $2, $3, $4)
+            ",
+            &[
+                &resource_type.id,
+                &resource_type.resource_server,
+                &resource_type.name,
+                &resource_type.created_at,
+            ],
+        )
+        .await?;
+        tx.execute(
+            "
+            INSERT INTO iam.actions (resource_type_id, name)
+            SELECT $1, UNNEST($2::VARCHAR[])
+            ",
+            &[&resource_type.id, &resource_type.actions],
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_resource_type(
+        &self,
+        resource_server: &str,
+        name: &str,
+    ) -> Result<Option<ResourceType>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            SELECT rt.*,
+                   ARRAY(
+                       SELECT a.name FROM iam.actions a
+                       WHERE a.resource_type_id = rt.id
+                       ORDER BY a.name
+                   ) AS actions
+            FROM iam.resource_types rt
+            WHERE rt.resource_server = $1 AND rt.name = $2
+        ";
+
+        let row = conn.query_opt(query, &[&resource_server, &name]).await?;
+        Ok(row.map(ResourceType::from_row))
+    }
+
+    async fn create_resource(&self, resource: &Resource) -> Result<()> {
+        let conn = self.pool.get().await?;
+        let query = "
+            INSERT INTO iam.resources (id, resource_type_id, name, parent_id, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+        ";
+
+        conn.execute(
+            query,
+            &[
+                &resource.id,
+                &resource.resource_type_id,
+                &resource.name,
+                &resource.parent_id,
+                &resource.created_at,
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn find_resource(&self, resource_type_id: Uuid, name: &str) -> Result<Option<Resource>> {
+        let conn = self.pool.get().await?;
+        let query = "SELECT * FROM iam.resources WHERE resource_type_id = $1 AND name = $2";
+
+        let row = conn.query_opt(query, &[&resource_type_id, &name]).await?;
+        Ok(row.map(Resource::from_row))
+    }
+
+    /// Stores a role along with its actions, which must all be supported by
+    /// the role's resource type.
+    async fn create_role(&self, role: &Role) -> Result<()> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "
+            INSERT INTO iam.roles (id, resource_type_id, name, created_at)
+            VALUES ($1, $2, $3, $4)
+            ",
+            &[
+                &role.id,
+                &role.resource_type_id,
+                &role.name,
+                &role.created_at,
+            ],
+        )
+        .await?;
+        tx.execute(
+            "
+            INSERT INTO iam.role_actions (role_id, resource_type_id, action)
+            SELECT $1, $2, UNNEST($3::VARCHAR[])
+            ",
+            &[&role.id, &role.resource_type_id, &role.actions],
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Gives a principal a role on a resource of the role's type, or on all
+    /// of them matching a pattern. Returns `false` when the principal already
+    /// holds it with the same effect.
+    async fn assign_role(&self, assignment: &RoleAssignment) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "
+            INSERT INTO iam.role_assignments (
+                id, principal_type, principal_id, role_id, resource_id, name_pattern,
+                effect, resource_type_id, created_at
+            )
+            SELECT $1, $2, $3, r.id, $5, $6, $7, r.resource_type_id, $8
+            FROM iam.roles r
+            WHERE r.id = $4
+            ON CONFLICT DO NOTHING
+        ";
+        let (resource_id, name_pattern) = match &assignment.target {
+            AssignmentTarget::Resource(resource_id) => (Some(*resource_id), None),
+            AssignmentTarget::Pattern(name_pattern) => (None, Some(name_pattern.as_str())),
+        };
+
+        let assigned = conn
+            .execute(
+                query,
+                &[
+                    &assignment.id,
+                    &assignment.principal.kind(),
+                    &assignment.principal.id(),
+                    &assignment.role_id,
+                    &resource_id,
+                    &name_pattern,
+                    &assignment.effect.to_string(),
+                    &assignment.created_at,
+                ],
+            )
+            .await?;
+        Ok(assigned == 1)
+    }
+
+    async fn unassign_role(
+        &self,
+        principal: Principal,
+        role_id: Uuid,
+        resource_id: Uuid,
+    ) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "
+            DELETE FROM iam.role_assignments
+            WHERE principal_type = $1 AND principal_id = $2
+              AND role_id = $3 AND resource_id = $4
+        ";
+
+        // Both an allow and a deny may be assigned
+        let removed = conn
+            .execute(
+                query,
+                &[&principal.kind(), &principal.id(), &role_id, &resource_id],
+            )
+            .await?;
+        Ok(removed > 0)
+    }
+
+    /// Every action a principal is allowed, merged across its roles and those
+    /// of its groups, one entry per resource.
+    ///
+    /// Grants on patterns and on ancestors are expanded onto the existing
+    /// resources they cover, as for `list_resources_with_action`, and actions
+    /// a deny covers are left out, so tokens agree with the authorization
+    /// endpoints.
+    async fn list_permissions(&self, principal: Principal) -> Result<Vec<Permission>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            WITH RECURSIVE grants AS (
+                SELECT a.resource_id, a.resource_type_id, a.name_pattern, a.effect, ra.action
+                FROM iam.effective_principals($1, $2) p
+                JOIN iam.role_assignments a
+                  ON a.principal_type = p.principal_type AND a.principal_id = p.principal_id
+                JOIN iam.role_actions ra ON ra.role_id = a.role_id
+            ),
+            covered AS (
+                SELECT r.id, g.action, g.effect
+                FROM grants g
+                JOIN iam.resources r
+                  ON r.id = g.resource_id
+                  OR (g.resource_id IS NULL
+                      AND r.resource_type_id = g.resource_type_id
+                      AND r.name LIKE iam.glob_to_like(g.name_pattern))
+                UNION
+                SELECT child.id, c.action, c.effect
+                FROM iam.resources child
+                JOIN covered c ON child.parent_id = c.id
+            )
+            SELECT rt.resource_server,
+                   rt.name || '/' || r.name AS resource,
+                   ARRAY_AGG(c.action ORDER BY c.action) AS actions
+            FROM covered c
+            JOIN iam.resources r ON r.id = c.id
+            JOIN iam.resource_types rt ON rt.id = r.resource_type_id
+            WHERE c.effect = 'allow'
+              AND NOT EXISTS (
+                SELECT 1 FROM covered d
+                WHERE d.id = c.id AND d.action = c.action AND d.effect = 'deny'
+              )
+            GROUP BY rt.resource_server, rt.name, r.name
+            ORDER BY rt.resource_server, resource
+        ";
+
+        let rows = conn
+            .query(query, &[&principal.kind(), &principal.id()])
+            .await?;
+        Ok(rows.into_iter().map(Permission::from_row).collect())
+    }
+
+    /// For every check, the role assignment that decides whether the
+    /// principal may take its action on its resource, in the order of
+    /// `checks`. All checks are answered by one query.
+    ///
+    /// Assignments to the principal or any group it belongs to count, on the
+    /// resource, on any of its ancestors and on patterns matching either. A
+    /// deny among them wins; otherwise the one closest to the resource is
+    /// reported, exact ones before patterns, then by role name.
+    async fn find_grants(
+        &self,
+        principal: Principal,
+        resource_server: &str,
+        checks: &[ActionCheck<'_>],
+    ) -> Result<Vec<Option<Grant>>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            WITH principals AS MATERIALIZED (
+                SELECT * FROM iam.effective_principals($1, $2)
+            )
+            SELECT g.role_id, g.role, g.resource, g.effect
+            FROM UNNEST($4::VARCHAR[], $5::VARCHAR[], $6::VARCHAR[])
+                WITH ORDINALITY AS c(resource_type, resource_name, action, position)
+            LEFT JOIN LATERAL (
+                WITH RECURSIVE lineage AS (
+                    SELECT r.id, r.parent_id, r.resource_type_id, r.name, 0 AS depth
+                    FROM iam.resources r
+                    JOIN iam.resource_types rt ON rt.id = r.resource_type_id
+                    WHERE rt.resource_server = $3
+                      AND rt.name = c.resource_type AND r.name = c.resource_name
+                    UNION ALL
+                    SELECT p.id, p.parent_id, p.resource_type_id, p.name, l.depth + 1
+                    FROM iam.resources p
+                    JOIN lineage l ON p.id = l.parent_id
+                )
+                SELECT ro.id AS role_id,
+                       ro.name AS role,
+                       rt.name || '/' || COALESCE(a.name_pattern, l.name) AS resource,
+                       a.effect
+                FROM lineage l
+                JOIN iam.resource_types rt ON rt.id = l.resource_type_id
+                JOIN iam.role_assignments a
+                  ON a.resource_id = l.id
+                  OR (a.resource_id IS NULL
+                      AND a.resource_type_id = l.resource_type_id
+                      AND l.name LIKE iam.glob_to_like(a.name_pattern))
+                JOIN principals p
+                  ON p.principal_type = a.principal_type AND p.principal_id = a.principal_id
+                JOIN iam.roles ro ON ro.id = a.role_id
+                JOIN iam.role_actions ra ON ra.role_id = a.role_id
+                WHERE ra.action = c.action
+                ORDER BY a.effect = 'deny' DESC, l.depth, a.resource_id IS NULL, ro.name
+                LIMIT 1
+            ) g ON TRUE
+            ORDER BY c.position
+        ";
+        let resource_types: Vec<&str> = checks.iter().map(|check| check.resource_type).collect();
+        let resource_names: Vec<&str> = checks.iter().map(|check| check.resource_name).collect();
+        let actions: Vec<&str> = checks.iter().map(|check| check.action).collect();
+
+        let rows = conn
+            .query(
+                query,
+                &[
+                    &principal.kind(),
+                    &principal.id(),
+                    &resource_server,
+                    &resource_types,
+                    &resource_names,
+                    &actions,
+                ],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                row.get::<_, Option<Uuid>>("role_id")
+                    .is_some()
+                    .then(|| Grant::from_row(row))
+            })
+            .collect())
+    }
+
+    /// Resources of a type on which the principal may take `action`, ordered
+    /// by id and starting after `after`, for keyset pagination. Grants and
+    /// denies are inherited, matched and expanded to groups as for
+    /// `find_grants`.
+    async fn list_resources_with_action(
+        &self,
+        principal: Principal,
+        resource_server: &str,
+        resource_type: &str,
+        action: &str,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<Resource>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            WITH RECURSIVE grants AS (
+                SELECT a.resource_id, a.resource_type_id, a.name_pattern, a.effect
+                FROM iam.effective_principals($1, $2) p
+                JOIN iam.role_assignments a
+                  ON a.principal_type = p.principal_type AND a.principal_id = p.principal_id
+                JOIN iam.role_actions ra ON ra.role_id = a.role_id
+                WHERE ra.action = $5
+            ),
+            covered AS (
+                SELECT r.id, g.effect
+                FROM grants g
+                JOIN iam.resources r
+                  ON r.id = g.resource_id
+                  OR (g.resource_id IS NULL
+                      AND r.resource_type_id = g.resource_type_id
+                      AND r.name LIKE iam.glob_to_like(g.name_pattern))
+                UNION
+                SELECT child.id, c.effect
+                FROM iam.resources child
+                JOIN covered c ON child.parent_id = c.id
+            )
+            SELECT r.*
+            FROM iam.resources r
+            JOIN iam.resource_types rt ON rt.id = r.resource_type_id
+            WHERE rt.resource_server = $3 AND rt.name = $4
+              AND ($6::UUID IS NULL OR r.id > $6)
+              AND EXISTS (SELECT 1 FROM covered c WHERE c.id = r.id AND c.effect = 'allow')
+              AND NOT EXISTS (SELECT 1 FROM covered c WHERE c.id = r.id AND c.effect = 'deny')
+            ORDER BY r.id
+            LIMIT $7
+        ";
+
+        let rows = conn
+            .query(
+                query,
+                &[
+                    &principal.kind(),
+                    &principal.id(),
+                    &resource_server,
+                    &resource_type,
+                    &action,
+                    &after,
+                    &limit,
+                ],
+            )
+            .await?;
+        Ok(rows.into_iter().map(Resource::from_row).collect())
+    }
+}
+
+impl ResourceType {
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        ResourceType {
+            id: row.get("id"),
+            resource_server: row.get("resource_server"),
+            name: row.get("name"),
+            actions: row.get("actions"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
+
+impl Resource {
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        Resource {
+            id: row.get("id"),
+            resource_type_id: row.get("resource_type_id"),
+            name: row.get("name"),
+            parent_id: row.get("parent_id"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
+
+impl Grant {
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        Grant {
+            role_id: row.get("role_id"),
+            role: row.get("role"),
+            resource: row.get("resource"),
+            effect: row
+                .get::<_, String>("effect")
+                .parse()
+                .unwrap_or(Effect::Deny),
+        }
+    }
+}
+
+impl Permission {
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        Permission {
+            resource_server: row.get("resource_server"),
+            resource: row.get("resource"),
+            actions: row.get("actions"),
+        }
+    }
+}
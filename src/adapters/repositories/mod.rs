@@ -3,10 +3,24 @@ Module for repositories
 This module contains the repository interfaces and implementations for the application.
 */
 
+mod authorization_code_repo;
+mod client_repo;
+mod device_code_repo;
 mod errors;
+mod group_repo;
+mod iam_repo;
+mod revoked_token_repo;
 mod session_repo;
+mod signing_key_repo;
 mod user_repo;
 
+pub use authorization_code_repo::{AuthorizationCodeRepository, PgAuthorizationCodeRepository};
+pub use client_repo::{ClientRepository, PgClientRepository};
+pub use device_code_repo::{DeviceCodeRepository, PgDeviceCodeRepository};
 pub use errors::{Error, Result};
+pub use group_repo::{GroupRepository, PgGroupRepository};
+pub use iam_repo::{IamRepository, PgIamRepository};
+pub use revoked_token_repo::{PgRevokedTokenRepository, RevokedTokenRepository};
 pub use session_repo::{PgSessionRepository, SessionRepository};
+pub use signing_key_repo::{PgSigningKeyRepository, SigningKeyRepository};
 pub use user_repo::{PgUserRepository, UserRepository};
@@ -0,0 +1,89 @@
+use crate::config::database::PgPool;
+use crate::domain::models::{RevokedToken, RevokedTokenKind};
+use std::sync::Arc;
+
+use super::Result;
+
+#[async_trait::async_trait]
+pub trait RevokedTokenRepository {
+    async fn revoke(&self, revoked: &[RevokedToken]) -> Result<()>;
+    async fn list_revoked(&self) -> Result<Vec<RevokedToken>>;
+    async fn purge_expired(&self) -> Result<u64>;
+}
+
+pub struct PgRevokedTokenRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgRevokedTokenRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl RevokedTokenRepository for PgRevokedTokenRepository {
+    /// Stores revocations, which notifies every replica listening on
+    /// `token_revoked`. Entries already stored keep the later expiry.
+    async fn revoke(&self, revoked: &[RevokedToken]) -> Result<()> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+        let statement = tx
+            .prepare(
+                "
+                INSERT INTO auth.revoked_tokens (kind, value, expires_at)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (kind, value) DO UPDATE
+                SET expires_at = GREATEST(auth.revoked_tokens.expires_at, EXCLUDED.expires_at)
+                ",
+            )
+            .await?;
+
+        for revoked in revoked {
+            tx.execute(
+                &statement,
+                &[
+                    &revoked.kind.to_string(),
+                    &revoked.value,
+                    &revoked.expires_at,
+                ],
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Revocations whose tokens may not have expired yet.
+    async fn list_revoked(&self) -> Result<Vec<RevokedToken>> {
+        let conn = self.pool.get().await?;
+        let query = "SELECT * FROM auth.revoked_tokens WHERE expires_at > NOW()";
+
+        let rows = conn.query(query, &[]).await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(RevokedToken::from_row)
+            .collect())
+    }
+
+    async fn purge_expired(&self) -> Result<u64> {
+        let conn = self.pool.get().await?;
+        let query = "DELETE FROM auth.revoked_tokens WHERE expires_at <= NOW()";
+
+        let purged = conn.execute(query, &[]).await?;
+        Ok(purged)
+    }
+}
+
+impl RevokedToken {
+    // Rows of unknown kinds are skipped rather than failing the whole load
+    fn from_row(row: tokio_postgres::Row) -> Option<Self> {
+        let kind: RevokedTokenKind = row.get::<_, String>("kind").parse().ok()?;
+
+        Some(RevokedToken {
+            kind,
+            value: row.get("value"),
+            expires_at: row.get("expires_at"),
+        })
+    }
+}
@@ -1,5 +1,7 @@
+use crate::adapters::dtos::SessionLimit;
 use crate::config::database::PgPool;
 use crate::domain::models::Session;
+use chrono::Utc;
 use std::sync::Arc;
 use tokio_postgres::types::ToSql;
 use uuid::Uuid;
@@ -9,9 +11,33 @@ use super::Result;
 #[async_trait::async_trait]
 pub trait SessionRepository {
     async fn create_session(&self, session: &Session) -> Result<Uuid>;
+    async fn create_limited_session(
+        &self,
+        session: &Session,
+        limit: &SessionLimit,
+    ) -> Result<Option<Vec<Uuid>>>;
     async fn get_session_by_id(&self, session_id: Uuid) -> Result<Option<Session>>;
+    async fn list_active_sessions(&self, user_id: Uuid) -> Result<Vec<Session>>;
     async fn revoke_session(&self, session_id: Uuid, reason: Option<String>) -> Result<()>;
+    async fn revoke_user_sessions(
+        &self,
+        user_id: Uuid,
+        except_session_id: Option<Uuid>,
+        reason: Option<String>,
+    ) -> Result<Vec<Uuid>>;
+    async fn revoke_user_session(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<bool>;
     async fn update_last_active(&self, session_id: Uuid) -> Result<()>;
+    async fn rotate_refresh_token(
+        &self,
+        session_id: Uuid,
+        current_generation: i32,
+        new_hash: &str,
+    ) -> Result<bool>;
 }
 
 pub struct PgSessionRepository {
@@ -28,36 +54,79 @@ impl PgSessionRepository {
 impl SessionRepository for PgSessionRepository {
     async fn create_session(&self, session: &Session) -> Result<Uuid> {
         let conn = self.pool.get().await?;
-        let query = "
-            INSERT INTO auth.sessions (
-                id, user_id, refresh_token_hash, device_identifier,
-                device_name, device_type, ip_address, user_agent,
-                expires_at, is_revoked, revoked_reason, revoked_at
-            ) VALUES (
-                $1, $2, $3, $4,
-                $5, $6, $7, $8,
-                $9, $10, $11, $12
-            ) RETURNING id;
-        ";
-        let params: Vec<&(dyn ToSql + Sync)> = vec![
-            &session.id,
-            &session.user_id,
-            &session.refresh_token_hash,
-            &session.device_identifier,
-            &session.device_name,
-            &session.device_type,
-            &session.ip_address,
-            &session.user_agent,
-            &session.expires_at,
-            &session.is_revoked,
-            &session.revoked_reason,
-            &session.revoked_at,
-        ];
-
-        let row = conn.query_one(query, &params).await?;
+        let acr = session.acr.to_string();
+
+        let row = conn
+            .query_one(CREATE_SESSION_QUERY, &session_params(session, &acr))
+            .await?;
         Ok(row.get("id"))
     }
 
+    /// Creates a session unless the user already holds `limit.max_sessions`
+    /// of them, in which case the least recently active ones are revoked to
+    /// make room, or the session is refused when no eviction reason is given.
+    /// Returns the ids of the evicted sessions, or `None` when refused.
+    ///
+    /// Sign ins of the same user are serialized, so concurrent ones can't
+    /// both take the last free place.
+    async fn create_limited_session(
+        &self,
+        session: &Session,
+        limit: &SessionLimit,
+    ) -> Result<Option<Vec<Uuid>>> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "SELECT pg_advisory_xact_lock(hashtext('auth.sessions'), hashtext($1::TEXT))",
+            &[&session.user_id.to_string()],
+        )
+        .await?;
+
+        // Most recently active first
+        let now = Utc::now();
+        let rows = tx
+            .query(
+                "
+                SELECT * FROM auth.sessions
+                WHERE user_id = $1 AND is_revoked = FALSE AND expires_at > NOW()
+                ORDER BY last_active_at DESC
+                ",
+                &[&session.user_id],
+            )
+            .await?;
+        let active: Vec<Uuid> = rows
+            .into_iter()
+            .map(Session::from_row)
+            .filter(|active| active.ends_at(limit.idle_timeout, limit.absolute_lifetime) > now)
+            .map(|active| active.id)
+            .collect();
+
+        let mut evicted = vec![];
+        if limit.max_sessions > 0 && active.len() >= limit.max_sessions {
+            let Some(reason) = &limit.evicted_reason else {
+                return Ok(None);
+            };
+            evicted = active[limit.max_sessions - 1..].to_vec();
+            tx.execute(
+                "
+                UPDATE auth.sessions
+                SET is_revoked = TRUE, revoked_reason = $1, revoked_at = NOW()
+                WHERE id = ANY($2) AND is_revoked = FALSE
+                ",
+                &[reason, &evicted],
+            )
+            .await?;
+        }
+
+        let acr = session.acr.to_string();
+        tx.execute(CREATE_SESSION_QUERY, &session_params(session, &acr))
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(evicted))
+    }
+
     async fn get_session_by_id(&self, session_id: Uuid) -> Result<Option<Session>> {
         let conn = self.pool.get().await?;
         let query = "SELECT * FROM auth.sessions WHERE id = $1";
@@ -66,18 +135,76 @@ impl SessionRepository for PgSessionRepository {
         Ok(row.map(Session::from_row))
     }
 
+    async fn list_active_sessions(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            SELECT * FROM auth.sessions
+            WHERE user_id = $1 AND is_revoked = FALSE AND expires_at > NOW()
+            ORDER BY last_active_at DESC
+        ";
+
+        let rows = conn.query(query, &[&user_id]).await?;
+        Ok(rows.into_iter().map(Session::from_row).collect())
+    }
+
+    /// Revokes a session. Sessions already revoked keep their original reason.
     async fn revoke_session(&self, session_id: Uuid, reason: Option<String>) -> Result<()> {
         let conn = self.pool.get().await?;
         let query = "
             UPDATE auth.sessions 
             SET is_revoked = TRUE, revoked_reason = $1, revoked_at = NOW()
-            WHERE id = $2
+            WHERE id = $2 AND is_revoked = FALSE
         ";
 
         conn.execute(query, &[&reason, &session_id]).await?;
         Ok(())
     }
 
+    /// Revokes every active session of a user, optionally sparing one of them.
+    /// Returns the ids of the sessions revoked.
+    async fn revoke_user_sessions(
+        &self,
+        user_id: Uuid,
+        except_session_id: Option<Uuid>,
+        reason: Option<String>,
+    ) -> Result<Vec<Uuid>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.sessions
+            SET is_revoked = TRUE, revoked_reason = $1, revoked_at = NOW()
+            WHERE user_id = $2
+              AND is_revoked = FALSE
+              AND ($3::UUID IS NULL OR id <> $3)
+            RETURNING id
+        ";
+
+        let rows = conn
+            .query(query, &[&reason, &user_id, &except_session_id])
+            .await?;
+        Ok(rows.into_iter().map(|row| row.get("id")).collect())
+    }
+
+    /// Revokes a session only if it belongs to `user_id`.
+    /// Returns `false` when no such active session exists for that user.
+    async fn revoke_user_session(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.sessions
+            SET is_revoked = TRUE, revoked_reason = $1, revoked_at = NOW()
+            WHERE id = $2 AND user_id = $3 AND is_revoked = FALSE
+        ";
+
+        let revoked = conn
+            .execute(query, &[&reason, &session_id, &user_id])
+            .await?;
+        Ok(revoked == 1)
+    }
+
     async fn update_last_active(&self, session_id: Uuid) -> Result<()> {
         let conn = self.pool.get().await?;
         let query = "
@@ -89,6 +216,69 @@ impl SessionRepository for PgSessionRepository {
         conn.execute(query, &[&session_id]).await?;
         Ok(())
     }
+
+    /// Stores the next refresh token and bumps the generation, only if the session
+    /// is still at `current_generation`. Returns `false` when another request
+    /// rotated or revoked the session first.
+    async fn rotate_refresh_token(
+        &self,
+        session_id: Uuid,
+        current_generation: i32,
+        new_hash: &str,
+    ) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.sessions
+            SET refresh_token_hash = $1,
+                refresh_token_generation = refresh_token_generation + 1,
+                last_active_at = NOW()
+            WHERE id = $2 AND refresh_token_generation = $3 AND is_revoked = FALSE
+        ";
+
+        let updated = conn
+            .execute(query, &[&new_hash, &session_id, &current_generation])
+            .await?;
+        Ok(updated == 1)
+    }
+}
+
+const CREATE_SESSION_QUERY: &str = "
+    INSERT INTO auth.sessions (
+        id, user_id, refresh_token_hash, refresh_token_generation,
+        device_identifier, device_name, device_type, ip_address,
+        user_agent, expires_at, is_revoked, revoked_reason,
+        revoked_at, client_id, acr, auth_time,
+        scope
+    ) VALUES (
+        $1, $2, $3, $4,
+        $5, $6, $7, $8,
+        $9, $10, $11, $12,
+        $13, $14, $15, $16,
+        $17
+    ) RETURNING id;
+";
+
+/// Parameters of `CREATE_SESSION_QUERY`, `acr` being the session's as text.
+fn session_params<'a>(session: &'a Session, acr: &'a String) -> Vec<&'a (dyn ToSql + Sync)> {
+    vec![
+        &session.id,
+        &session.user_id,
+        &session.refresh_token_hash,
+        &session.refresh_token_generation,
+        &session.device_identifier,
+        &session.device_name,
+        &session.device_type,
+        &session.ip_address,
+        &session.user_agent,
+        &session.expires_at,
+        &session.is_revoked,
+        &session.revoked_reason,
+        &session.revoked_at,
+        &session.client_id,
+        acr,
+        &session.auth_time,
+        &session.scope,
+    ]
 }
 
 impl Session {
@@ -98,6 +288,7 @@ impl Session {
             id: row.get("id"),
             user_id: row.get("user_id"),
             refresh_token_hash: row.get("refresh_token_hash"),
+            refresh_token_generation: row.get("refresh_token_generation"),
             device_identifier: row.get("device_identifier"),
             device_name: row.get("device_name"),
             device_type: row.get("device_type"),
@@ -109,6 +300,10 @@ impl Session {
             is_revoked: row.get("is_revoked"),
             revoked_reason: row.get("revoked_reason"),
             revoked_at: row.get("revoked_at"),
+            client_id: row.get("client_id"),
+            acr: row.get::<_, String>("acr").parse().unwrap_or_default(),
+            auth_time: row.get("auth_time"),
+            scope: row.get("scope"),
         }
     }
 }
@@ -0,0 +1,176 @@
+use crate::config::database::PgPool;
+use crate::domain::models::{KeyState, SigningKey};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tokio_postgres::types::ToSql;
+
+use super::Result;
+
+// Serialises rotations across every instance sharing the database
+const KEY_ROTATION_LOCK: i64 = 0x6761_6e64_6b65_7973;
+
+const ACTIVE_KEY_QUERY: &str =
+    "SELECT activated_at FROM auth.signing_keys WHERE key_state = 'active'";
+
+#[async_trait::async_trait]
+pub trait SigningKeyRepository {
+    async fn list_keys(&self) -> Result<Vec<SigningKey>>;
+    async fn list_usable_keys(&self) -> Result<Vec<SigningKey>>;
+    async fn is_rotation_due(&self, activated_before: DateTime<Utc>) -> Result<bool>;
+    async fn rotate_keys(
+        &self,
+        new_key: &SigningKey,
+        retire_after: DateTime<Utc>,
+        activated_before: Option<DateTime<Utc>>,
+    ) -> Result<bool>;
+    async fn retire_expired_keys(&self) -> Result<u64>;
+}
+
+pub struct PgSigningKeyRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PgSigningKeyRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl SigningKeyRepository for PgSigningKeyRepository {
+    async fn list_keys(&self) -> Result<Vec<SigningKey>> {
+        let conn = self.pool.get().await?;
+        let query = "SELECT * FROM auth.signing_keys ORDER BY created_at DESC";
+
+        let rows = conn.query(query, &[]).await?;
+        Ok(rows.into_iter().map(SigningKey::from_row).collect())
+    }
+
+    /// Keys that can still sign or verify tokens.
+    async fn list_usable_keys(&self) -> Result<Vec<SigningKey>> {
+        let conn = self.pool.get().await?;
+        let query = "
+            SELECT * FROM auth.signing_keys
+            WHERE key_state <> 'retired'
+            ORDER BY created_at DESC
+        ";
+
+        let rows = conn.query(query, &[]).await?;
+        Ok(rows.into_iter().map(SigningKey::from_row).collect())
+    }
+
+    /// Whether the active key was activated before `activated_before`, or
+    /// there is none. Lets instances skip generating a key when no rotation
+    /// is due, `rotate_keys` checks again under its lock.
+    async fn is_rotation_due(&self, activated_before: DateTime<Utc>) -> Result<bool> {
+        let conn = self.pool.get().await?;
+
+        let active = conn.query_opt(ACTIVE_KEY_QUERY, &[]).await?;
+        Ok(is_due(active, activated_before))
+    }
+
+    /// Moves every key one step along its lifecycle in a single transaction:
+    /// `active` becomes `retiring` until `retire_after`, `next` becomes `active`
+    /// and `new_key` is stored as the new `next` key.
+    ///
+    /// With `activated_before` set, nothing happens unless the active key was
+    /// activated before that instant or there is no active key at all. Returns
+    /// whether the keys were rotated.
+    async fn rotate_keys(
+        &self,
+        new_key: &SigningKey,
+        retire_after: DateTime<Utc>,
+        activated_before: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&KEY_ROTATION_LOCK])
+            .await?;
+
+        if let Some(activated_before) = activated_before {
+            let active = tx.query_opt(ACTIVE_KEY_QUERY, &[]).await?;
+            if !is_due(active, activated_before) {
+                return Ok(false);
+            }
+        }
+
+        tx.execute(
+            "
+            UPDATE auth.signing_keys
+            SET key_state = 'retiring', retire_after = $1
+            WHERE key_state = 'active'
+            ",
+            &[&retire_after],
+        )
+        .await?;
+        tx.execute(
+            "
+            UPDATE auth.signing_keys
+            SET key_state = 'active', activated_at = NOW()
+            WHERE key_state = 'next'
+            ",
+            &[],
+        )
+        .await?;
+
+        let key_state = KeyState::Next.to_string();
+        let query = "
+            INSERT INTO auth.signing_keys (
+                kid, algorithm, key_state, encrypted_private_key
+            ) VALUES ($1, $2, $3, $4)
+        ";
+        let params: Vec<&(dyn ToSql + Sync)> = vec![
+            &new_key.kid,
+            &new_key.algorithm,
+            &key_state,
+            &new_key.encrypted_private_key,
+        ];
+        tx.execute(query, &params).await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Retires keys whose last tokens have expired and wipes their private key.
+    async fn retire_expired_keys(&self) -> Result<u64> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.signing_keys
+            SET key_state = 'retired', retired_at = NOW(), encrypted_private_key = NULL
+            WHERE key_state = 'retiring' AND retire_after <= NOW()
+        ";
+
+        let retired = conn.execute(query, &[]).await?;
+        Ok(retired)
+    }
+}
+
+// Whether the `active` key row was activated before `activated_before`
+fn is_due(active: Option<tokio_postgres::Row>, activated_before: DateTime<Utc>) -> bool {
+    match active {
+        Some(row) => row
+            .get::<_, Option<DateTime<Utc>>>("activated_at")
+            .is_none_or(|activated_at| activated_at < activated_before),
+        None => true,
+    }
+}
+
+impl SigningKey {
+    /// Converts a `tokio_postgres::Row` into a `SigningKey`
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        Self {
+            kid: row.get("kid"),
+            algorithm: row.get("algorithm"),
+            key_state: row
+                .get::<_, String>("key_state")
+                .parse()
+                .expect("key_state is constrained by the schema"),
+            encrypted_private_key: row.get("encrypted_private_key"),
+            created_at: row.get("created_at"),
+            activated_at: row.get("activated_at"),
+            retire_after: row.get("retire_after"),
+            retired_at: row.get("retired_at"),
+        }
+    }
+}
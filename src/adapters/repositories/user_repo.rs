@@ -6,6 +6,7 @@ This module contains the UserRepository trait definition and its implementation
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio_postgres::types::ToSql;
+use uuid::Uuid;
 
 use super::Result;
 use crate::adapters::dtos::AuthUserDto;
@@ -37,12 +38,35 @@ impl PgUserRepository {
                 external_id,
                 email,
                 password_hash,
-                access_range
+                COALESCE(email_verified, FALSE) AS email_verified,
+                access_range,
+                max_sessions
             FROM auth.users
             WHERE email = $1
         "#
     }
 
+    fn find_auth_user_by_id_query() -> &'static str {
+        r#"
+            SELECT
+                id,
+                external_id,
+                email,
+                password_hash,
+                COALESCE(email_verified, FALSE) AS email_verified,
+                access_range,
+                max_sessions
+            FROM auth.users
+            WHERE id = $1
+        "#
+    }
+
+    fn find_by_id_query() -> &'static str {
+        r#"
+            SELECT * FROM auth.users WHERE id = $1
+        "#
+    }
+
     fn email_exists_query() -> &'static str {
         r#"
             SELECT
@@ -97,12 +121,87 @@ impl PgUserRepository {
                     id: row.get("id"),
                     email: row.get("email"),
                     password_hash: row.get("password_hash"),
+                    email_verified: row.get("email_verified"),
                     access_range: row.get("access_range"),
+                    max_sessions: row.get("max_sessions"),
                 }))
             }
             None => Ok(None),
         }
     }
+
+    pub async fn find_auth_user_by_id(&self, id: &Uuid) -> Result<Option<AuthUserDto>> {
+        let conn = self.pool.get().await?;
+        let params: &[&(dyn ToSql + Sync)] = &[id];
+        let result = conn
+            .query_opt(Self::find_auth_user_by_id_query(), params)
+            .await?;
+
+        Ok(result.map(|row| AuthUserDto {
+            id: row.get("id"),
+            email: row.get("email"),
+            password_hash: row.get("password_hash"),
+            email_verified: row.get("email_verified"),
+            access_range: row.get("access_range"),
+            max_sessions: row.get("max_sessions"),
+        }))
+    }
+
+    /// Sets how many sessions a user may hold at once, `None` for the server default.
+    /// Returns `false` if the user doesn't exist.
+    pub async fn set_max_sessions(&self, id: &Uuid, max_sessions: Option<i32>) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "UPDATE auth.users SET max_sessions = $1, updated_at = NOW() WHERE id = $2";
+
+        let updated = conn.execute(query, &[&max_sessions, id]).await?;
+        Ok(updated == 1)
+    }
+
+    /// Replaces a user's password hash. Returns `false` if the user doesn't exist.
+    pub async fn set_password_hash(&self, id: &Uuid, password_hash: &str) -> Result<bool> {
+        let conn = self.pool.get().await?;
+        let query = "
+            UPDATE auth.users
+            SET password_hash = $1, password_updated_at = NOW(), updated_at = NOW()
+            WHERE id = $2
+        ";
+
+        let updated = conn.execute(query, &[&password_hash, id]).await?;
+        Ok(updated == 1)
+    }
+
+    /// Deletes a user along with the roles and group memberships they hold,
+    /// which don't reference the users table. Sessions and codes cascade.
+    /// Returns `false` if the user doesn't exist.
+    pub async fn delete(&self, id: &Uuid) -> Result<bool> {
+        let mut conn = self.pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "DELETE FROM iam.role_assignments WHERE principal_type = 'user' AND principal_id = $1",
+            &[id],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM iam.group_members WHERE member_type = 'user' AND member_id = $1",
+            &[id],
+        )
+        .await?;
+        let deleted = tx
+            .execute("DELETE FROM auth.users WHERE id = $1", &[id])
+            .await?;
+
+        tx.commit().await?;
+        Ok(deleted == 1)
+    }
+
+    pub async fn find_by_id(&self, id: &Uuid) -> Result<Option<User>> {
+        let conn = self.pool.get().await?;
+        let params: &[&(dyn ToSql + Sync)] = &[id];
+        let result = conn.query_opt(Self::find_by_id_query(), params).await?;
+
+        Ok(result.map(User::from_row))
+    }
 }
 
 #[async_trait]
@@ -148,3 +247,48 @@ impl UserRepository for PgUserRepository {
         Ok(())
     }
 }
+
+impl User {
+    /// Converts a `tokio_postgres::Row` into a `User`
+    fn from_row(row: tokio_postgres::Row) -> Self {
+        Self {
+            id: row.get("id"),
+            external_id: row.get("external_id"),
+            username: row.get("username"),
+            email: row.get("email"),
+            password_hash: row.get("password_hash"),
+            password_updated_at: row.get("password_updated_at"),
+            password_reset_required: row
+                .get::<_, Option<bool>>("password_reset_required")
+                .unwrap_or_default(),
+            failed_login_attempts: row
+                .get::<_, Option<i32>>("failed_login_attempts")
+                .unwrap_or_default(),
+            last_failed_attempt: row.get("last_failed_attempt"),
+            account_locked_until: row.get("account_locked_until"),
+            email_verified: row
+                .get::<_, Option<bool>>("email_verified")
+                .unwrap_or_default(),
+            email_verification_token: row.get("email_verification_token"),
+            email_verification_sent_at: row.get("email_verification_sent_at"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+            requires_mfa: row
+                .get::<_, Option<bool>>("requires_mfa")
+                .unwrap_or_default(),
+            auth_provider: row
+                .get::<_, Option<String>>("auth_provider")
+                .and_then(|provider| provider.parse().ok())
+                .unwrap_or_default(),
+            user_state: row
+                .get::<_, Option<String>>("user_state")
+                .and_then(|state| state.parse().ok())
+                .unwrap_or_default(),
+            access_range: row
+                .get::<_, String>("access_range")
+                .parse()
+                .unwrap_or_default(),
+            deletion_scheduled_at: row.get("deletion_scheduled_at"),
+        }
+    }
+}
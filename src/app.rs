@@ -8,13 +8,20 @@ use tower_http::trace::TraceLayer;
 
 use crate::app_modules::AppState;
 use crate::app_modules::api::v1::routes::v1_routes;
-use crate::app_modules::health;
+use crate::app_modules::{health, well_known};
 use crate::config::database::PgPool;
 
-pub fn build_app(db: Arc<PgPool>) -> Router {
+pub async fn build_app(db: Arc<PgPool>) -> Router {
+    let state = AppState::new(db).await;
+
     Router::new()
         .route("/health", get(health::health_check))
-        .with_state(AppState::new(db.clone()))
-        .nest("/api/v1", v1_routes().with_state(AppState::new(db.clone())))
+        .route("/.well-known/jwks.json", get(well_known::jwks))
+        .route(
+            "/.well-known/openid-configuration",
+            get(well_known::openid_configuration),
+        )
+        .with_state(state.clone())
+        .nest("/api/v1", v1_routes().with_state(state))
         .layer(TraceLayer::new_for_http())
 }
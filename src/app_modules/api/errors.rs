@@ -2,7 +2,7 @@
 
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header::WWW_AUTHENTICATE},
     response::{IntoResponse, Response},
 };
 use serde::Serialize;
@@ -18,8 +18,14 @@ pub struct ErrorResponse {
 pub enum AppError {
     NotFound(String),
     Unauthorized(String),
+    Forbidden(String),
     BadRequest(String),
     Internal(String),
+    /// The caller must sign in again, recently enough or with a stronger method
+    ReauthenticationRequired {
+        max_age: Option<i64>,
+        acr: Option<String>,
+    },
 }
 
 impl IntoResponse for AppError {
@@ -27,11 +33,61 @@ impl IntoResponse for AppError {
         let (status, message) = match self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AppError::ReauthenticationRequired { max_age, acr } => {
+                return reauthentication_required(max_age, acr);
+            }
         };
 
         let body = Json(ErrorResponse { error: message });
         (status, body).into_response()
     }
 }
+
+// Step-up challenge of RFC 9470, telling the client what authentication to get
+fn reauthentication_required(max_age: Option<i64>, acr: Option<String>) -> Response {
+    let mut challenge = String::from(
+        "Bearer error=\"insufficient_user_authentication\", \
+         error_description=\"A more recent or stronger authentication is required\"",
+    );
+    if let Some(max_age) = max_age {
+        challenge.push_str(&format!(", max_age={max_age}"));
+    }
+    if let Some(acr) = acr {
+        challenge.push_str(&format!(", acr_values=\"{acr}\""));
+    }
+
+    let body = Json(ErrorResponse {
+        error: "Reauthentication required".to_string(),
+    });
+    (
+        StatusCode::UNAUTHORIZED,
+        [(WWW_AUTHENTICATE, challenge)],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reauthentication_challenges_the_client() {
+        let response = AppError::ReauthenticationRequired {
+            max_age: Some(900),
+            acr: Some("mfa".to_string()),
+        }
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            "Bearer error=\"insufficient_user_authentication\", \
+             error_description=\"A more recent or stronger authentication is required\", \
+             max_age=900, acr_values=\"mfa\""
+        );
+    }
+}
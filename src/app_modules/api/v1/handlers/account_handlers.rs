@@ -0,0 +1,47 @@
+/* V1 account handler module */
+
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use validator::Validate;
+
+use crate::app_modules::api::v1::schemas::ChangePasswordRequest;
+use crate::app_modules::api::{AppError, ResponseResult};
+use crate::app_modules::{AppState, auth::AuthenticatedUser};
+
+/// Changes the caller's password and signs out their other sessions.
+/// Takes a recent sign-in.
+pub async fn change_password(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    user.require_recent_authentication()?;
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    state
+        .user_service
+        .change_password(&user.user_id, &payload.new_password)
+        .await?;
+    state
+        .auth_service
+        .logout_all(user.user_id, Some(user.session_id))
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Deletes the caller's account. Takes a recent sign-in.
+pub async fn delete_account(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> ResponseResult<impl IntoResponse> {
+    user.require_recent_authentication()?;
+    state.user_service.delete_user(&user.user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
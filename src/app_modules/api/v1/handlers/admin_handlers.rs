@@ -0,0 +1,275 @@
+/* V1 admin handler module */
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::app_modules::api::ResponseResult;
+use crate::app_modules::api::v1::schemas::{
+    ClientPermissionRequest, ClientPermissionResponse, ClientRequest, ClientResponse,
+    GroupMemberPath, GroupMemberRequest, GroupMemberResponse, GroupRequest, GroupResponse,
+    RegisteredClientResponse, SessionLimitRequest, SigningKeyResponse,
+};
+use crate::app_modules::{AppState, auth::AdminUser};
+
+pub async fn list_signing_keys(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> ResponseResult<impl IntoResponse> {
+    let keys: Vec<SigningKeyResponse> = state
+        .key_service
+        .list_keys()
+        .await?
+        .into_iter()
+        .map(SigningKeyResponse::from)
+        .collect();
+
+    Ok(Json(keys))
+}
+
+pub async fn rotate_signing_keys(
+    State(state): State<AppState>,
+    admin: AdminUser,
+) -> ResponseResult<impl IntoResponse> {
+    admin.0.require_recent_authentication()?;
+    state.key_service.rotate().await?;
+
+    list_signing_keys(State(state), admin).await
+}
+
+pub async fn list_clients(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> ResponseResult<impl IntoResponse> {
+    let clients: Vec<ClientResponse> = state
+        .client_service
+        .list_clients()
+        .await?
+        .into_iter()
+        .map(ClientResponse::from)
+        .collect();
+
+    Ok(Json(clients))
+}
+
+pub async fn register_client(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Json(payload): Json<ClientRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    let public = payload.public;
+    let (client, client_secret) = state
+        .client_service
+        .register_client(payload.into(), public)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(RegisteredClientResponse {
+            client: client.into(),
+            client_secret,
+        }),
+    ))
+}
+
+pub async fn get_client(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(client_id): Path<String>,
+) -> ResponseResult<impl IntoResponse> {
+    let client = state.client_service.get_client(&client_id).await?;
+
+    Ok(Json(ClientResponse::from(client)))
+}
+
+pub async fn update_client(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(client_id): Path<String>,
+    Json(payload): Json<ClientRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    let is_active = payload.is_active;
+    let client = state
+        .client_service
+        .update_client(&client_id, payload.into(), is_active)
+        .await?;
+
+    Ok(Json(ClientResponse::from(client)))
+}
+
+pub async fn delete_client(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    Path(client_id): Path<String>,
+) -> ResponseResult<impl IntoResponse> {
+    admin.0.require_recent_authentication()?;
+    state.client_service.delete_client(&client_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_client_permissions(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(client_id): Path<String>,
+) -> ResponseResult<impl IntoResponse> {
+    // Unknown clients are reported instead of listing nothing
+    state.client_service.get_client(&client_id).await?;
+    let permissions: Vec<ClientPermissionResponse> = state
+        .client_service
+        .list_permissions(&client_id)
+        .await?
+        .into_iter()
+        .map(ClientPermissionResponse::from)
+        .collect();
+
+    Ok(Json(permissions))
+}
+
+pub async fn replace_client_permissions(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(client_id): Path<String>,
+    Json(payload): Json<Vec<ClientPermissionRequest>>,
+) -> ResponseResult<impl IntoResponse> {
+    let permissions: Vec<ClientPermissionResponse> = state
+        .client_service
+        .replace_permissions(&client_id, payload.into_iter().map(Into::into).collect())
+        .await?
+        .into_iter()
+        .map(ClientPermissionResponse::from)
+        .collect();
+
+    Ok(Json(permissions))
+}
+
+pub async fn set_user_session_limit(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<SessionLimitRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    state
+        .user_service
+        .set_session_limit(&user_id, payload.max_sessions)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_groups(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> ResponseResult<impl IntoResponse> {
+    let groups: Vec<GroupResponse> = state
+        .group_service
+        .list_groups()
+        .await?
+        .into_iter()
+        .map(GroupResponse::from)
+        .collect();
+
+    Ok(Json(groups))
+}
+
+pub async fn create_group(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Json(payload): Json<GroupRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    let group = state
+        .group_service
+        .create_group(payload.name, payload.description)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(GroupResponse::from(group))))
+}
+
+pub async fn get_group(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(group_id): Path<Uuid>,
+) -> ResponseResult<impl IntoResponse> {
+    let group = state.group_service.get_group(group_id).await?;
+
+    Ok(Json(GroupResponse::from(group)))
+}
+
+pub async fn delete_group(
+    State(state): State<AppState>,
+    admin: AdminUser,
+    Path(group_id): Path<Uuid>,
+) -> ResponseResult<impl IntoResponse> {
+    admin.0.require_recent_authentication()?;
+    state.group_service.delete_group(group_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_group_members(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(group_id): Path<Uuid>,
+) -> ResponseResult<impl IntoResponse> {
+    let members: Vec<GroupMemberResponse> = state
+        .group_service
+        .list_members(group_id)
+        .await?
+        .into_iter()
+        .map(GroupMemberResponse::from)
+        .collect();
+
+    Ok(Json(members))
+}
+
+/// Adds a user or a group to a group. Adding an existing member is a no-op.
+pub async fn add_group_member(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(group_id): Path<Uuid>,
+    Json(payload): Json<GroupMemberRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    let added = state
+        .group_service
+        .add_member(group_id, payload.into())
+        .await?;
+
+    Ok(if added {
+        StatusCode::CREATED
+    } else {
+        StatusCode::NO_CONTENT
+    })
+}
+
+pub async fn remove_group_member(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(path): Path<GroupMemberPath>,
+) -> ResponseResult<impl IntoResponse> {
+    state
+        .group_service
+        .remove_member(path.group_id, path.member_type.principal(path.member_id))
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists the groups a user belongs to, directly or through other groups.
+pub async fn list_user_groups(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(user_id): Path<Uuid>,
+) -> ResponseResult<impl IntoResponse> {
+    let groups: Vec<GroupResponse> = state
+        .group_service
+        .list_user_groups(user_id)
+        .await?
+        .into_iter()
+        .map(GroupResponse::from)
+        .collect();
+
+    Ok(Json(groups))
+}
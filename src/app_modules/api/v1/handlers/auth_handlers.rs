@@ -4,7 +4,7 @@ use std::net::SocketAddr;
 
 use axum::{
     extract::{ConnectInfo, Json, State},
-    http::HeaderMap,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 
@@ -12,8 +12,14 @@ use tracing::{debug, error};
 
 use crate::adapters::dtos::SignupDto;
 use crate::app_modules::api::ResponseResult;
-use crate::app_modules::api::v1::schemas::{AuthLocal, AuthResponse, UserResponse};
-use crate::app_modules::{AppState, auth::AuthMethod};
+use crate::app_modules::api::v1::schemas::{
+    AuthLocal, AuthResponse, LogoutAllRequest, LogoutAllResponse, RefreshTokenRequest, UserResponse,
+};
+use crate::app_modules::{
+    AppState,
+    auth::{AuthMethod, AuthenticatedUser},
+};
+use crate::domain::models::{Acr, Authentication};
 use crate::utils::user_agent::get_device_info;
 
 use crate::app_modules::api::AppError;
@@ -38,6 +44,7 @@ pub async fn local_login(
         }
     };
 
+    let openid = payload.openid_request();
     let auth_user = strategy
         .authenticate(&SignupDto::EmailPassord {
             email: payload.email,
@@ -49,18 +56,68 @@ pub async fn local_login(
     let ip = addr.ip();
     let device_info = get_device_info(headers);
 
+    let tokens = state
+        .auth_service
+        .make_session(
+            auth_user,
+            ip,
+            device_info,
+            openid,
+            None,
+            Authentication::now(Acr::Password),
+        )
+        .await?;
+
+    Ok(Json(AuthResponse {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        token_type: "Bearer".to_string(),
+        id_token: tokens.id_token,
+    }))
+}
+
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    Json(payload): Json<RefreshTokenRequest>,
+) -> ResponseResult<impl IntoResponse> {
     let (access_token, refresh_token) = state
         .auth_service
-        .make_session(auth_user, ip, device_info)
+        .refresh_session(&payload.refresh_token, None)
         .await?;
 
     Ok(Json(AuthResponse {
         access_token,
         refresh_token,
         token_type: "Bearer".to_string(),
+        id_token: None,
     }))
 }
 
+pub async fn logout(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> ResponseResult<impl IntoResponse> {
+    state.auth_service.logout(user.session_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn logout_all(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    payload: Option<Json<LogoutAllRequest>>,
+) -> ResponseResult<impl IntoResponse> {
+    let Json(payload) = payload.unwrap_or_default();
+    let keep_session_id = payload.keep_current.then_some(user.session_id);
+
+    let revoked_sessions = state
+        .auth_service
+        .logout_all(user.user_id, keep_session_id)
+        .await?;
+
+    Ok(Json(LogoutAllResponse { revoked_sessions }))
+}
+
 pub async fn local_signup(
     State(state): State<AppState>,
     Json(payload): Json<AuthLocal>,
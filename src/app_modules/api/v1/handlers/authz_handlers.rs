@@ -0,0 +1,89 @@
+/* V1 authorization handler module */
+
+use axum::{
+    extract::{Json, Query, State},
+    response::IntoResponse,
+};
+
+use crate::app_modules::api::ResponseResult;
+use crate::app_modules::api::v1::schemas::{
+    AuthzBatchCheckRequest, AuthzBatchCheckResponse, AuthzBatchCheckResult, AuthzCheckRequest,
+    AuthzCheckResponse, PermittedResourcesQuery, PermittedResourcesResponse,
+};
+use crate::app_modules::{AppState, auth::AuthenticatedCaller};
+
+/// Decides whether a principal may take an action on a resource.
+pub async fn check(
+    State(state): State<AppState>,
+    caller: AuthenticatedCaller,
+    Json(payload): Json<AuthzCheckRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    let principal = caller.principal(payload.principal_id)?;
+
+    let decision = state
+        .authorization_service
+        .check(
+            principal,
+            &payload.resource_server,
+            &payload.resource,
+            &payload.action,
+        )
+        .await?;
+
+    Ok(Json(AuthzCheckResponse::from(decision)))
+}
+
+/// Decides many `(resource, action)` pairs of one principal at once, for
+/// filtering lists.
+pub async fn check_batch(
+    State(state): State<AppState>,
+    caller: AuthenticatedCaller,
+    Json(payload): Json<AuthzBatchCheckRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    let principal = caller.principal(payload.principal_id)?;
+    let checks: Vec<(&str, &str)> = payload
+        .checks
+        .iter()
+        .map(|check| (check.resource.as_str(), check.action.as_str()))
+        .collect();
+
+    let decisions = state
+        .authorization_service
+        .check_many(principal, &payload.resource_server, &checks)
+        .await?;
+
+    let results = payload
+        .checks
+        .into_iter()
+        .zip(decisions)
+        .map(|(check, decision)| AuthzBatchCheckResult {
+            resource: check.resource,
+            action: check.action,
+            result: decision.into(),
+        })
+        .collect();
+    Ok(Json(AuthzBatchCheckResponse { results }))
+}
+
+/// Lists the resources of a type on which a principal may take an action.
+pub async fn list_permitted_resources(
+    State(state): State<AppState>,
+    caller: AuthenticatedCaller,
+    Query(query): Query<PermittedResourcesQuery>,
+) -> ResponseResult<impl IntoResponse> {
+    let principal = caller.principal(query.principal_id)?;
+
+    let page = state
+        .authorization_service
+        .list_resources(
+            principal,
+            &query.resource_server,
+            &query.resource_type,
+            &query.action,
+            query.after,
+            query.limit,
+        )
+        .await?;
+
+    Ok(Json(PermittedResourcesResponse::from(page)))
+}
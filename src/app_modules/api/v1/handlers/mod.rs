@@ -1,3 +1,9 @@
 /* V1 handlers */
 
+pub mod account_handlers;
+pub mod admin_handlers;
 pub mod auth_handlers;
+pub mod authz_handlers;
+pub mod oauth_handlers;
+pub mod session_handlers;
+pub mod userinfo_handlers;
@@ -0,0 +1,443 @@
+/* V1 OAuth handler module */
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use axum::{
+    Form,
+    extract::{ConnectInfo, Json, Query, State},
+    http::{HeaderMap, StatusCode, header::CACHE_CONTROL},
+    response::{IntoResponse, Response},
+};
+use tracing::error;
+
+use crate::adapters::dtos::{SignupDto, TokenExchangeRequest};
+use crate::app_modules::AppState;
+use crate::app_modules::api::ResponseResult;
+use crate::app_modules::api::v1::schemas::{
+    AuthorizeLoginRequest, DeviceAuthorizationRequest, DeviceAuthorizationResponse,
+    DeviceVerificationQuery, DeviceVerificationRequest, DeviceVerificationResponse,
+    IntrospectionRequest, IntrospectionResponse, RevocationRequest, TokenRequest, TokenResponse,
+};
+use crate::app_modules::auth::{self, AuthMethod, AuthenticatedUser};
+use crate::app_modules::oauth::authorize::{
+    AuthorizeParams, AuthorizeRejection, LOGIN_FORM_ACR, code_redirect, login_page,
+};
+use crate::app_modules::oauth::{OAuthError, authenticate_client};
+use crate::config::get_config;
+use crate::domain::models::{Authentication, Client, GrantType, TokenType};
+use crate::utils::user_agent::get_device_info;
+
+// Token types of RFC 8693 section 3; our access tokens are JWTs, so both apply
+const ACCESS_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:access_token";
+const JWT_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:jwt";
+
+/// Authorization endpoint (RFC 6749 section 3.1). Shows the sign-in form for a
+/// valid request, or sends the error back to the client.
+pub async fn authorize(
+    State(state): State<AppState>,
+    Query(params): Query<AuthorizeParams>,
+) -> Result<Response, AuthorizeRejection> {
+    let client = find_client(&state, &params).await?;
+    let (client, _) = params.validate(client)?;
+
+    Ok(login_page(&params, &client, None).into_response())
+}
+
+/// Signs the user in from the authorization form and redirects back to the
+/// client with a single-use authorization code.
+pub async fn authorize_login(
+    State(state): State<AppState>,
+    Form(payload): Form<AuthorizeLoginRequest>,
+) -> Result<Response, AuthorizeRejection> {
+    let params = payload.params;
+    let client = find_client(&state, &params).await?;
+    let (client, request) = params.validate(client)?;
+
+    let redirect_error = |error| AuthorizeRejection::Redirect {
+        redirect_uri: request.redirect_uri.clone(),
+        state: request.state.clone(),
+        error,
+    };
+
+    let strategy = state
+        .auth_service
+        .strategies
+        .get(&AuthMethod::EmailPassword)
+        .ok_or_else(|| {
+            error!("AuthMethod not found");
+            redirect_error(OAuthError::ServerError)
+        })?;
+
+    let user = match strategy
+        .authenticate(&SignupDto::EmailPassord {
+            email: payload.email,
+            password: payload.password,
+        })
+        .await
+    {
+        Ok(user) => user,
+        Err(auth::Error::InvalidCredentials | auth::Error::UserNotFound) => {
+            let page = login_page(&params, &client, Some("Wrong email or password"));
+            return Ok((StatusCode::UNAUTHORIZED, page).into_response());
+        }
+        Err(e) => {
+            error!("Failed to authenticate user: {e}");
+            return Err(redirect_error(OAuthError::ServerError));
+        }
+    };
+
+    let code = state
+        .oauth_service
+        .issue_authorization_code(&request, user.id, Authentication::now(LOGIN_FORM_ACR))
+        .await
+        .map_err(|e| redirect_error(e.into()))?;
+
+    Ok(code_redirect(&request, &code).into_response())
+}
+
+/// Device authorization endpoint (RFC 8628 section 3.1), for devices that
+/// can't receive a redirect, like CLIs and TVs.
+pub async fn device_authorization(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(payload): Form<DeviceAuthorizationRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let client = authenticate_client(&state, &headers, &payload.client).await?;
+    // Without a page to enter user codes on, the grant is disabled
+    let Some(verification_uri) = get_config().device_verification_uri.clone() else {
+        return Err(OAuthError::UnauthorizedClient);
+    };
+    if !client.allows_grant_type(GrantType::DeviceCode) {
+        return Err(OAuthError::UnauthorizedClient);
+    }
+    let scope = payload.scope.unwrap_or_default();
+    if !client.allows_scopes(&scope) {
+        return Err(OAuthError::InvalidScope);
+    }
+
+    let authorization = state
+        .oauth_service
+        .start_device_authorization(&client, &scope)
+        .await?;
+
+    Ok((
+        [(CACHE_CONTROL, "no-store")],
+        Json(DeviceAuthorizationResponse::new(
+            authorization,
+            verification_uri,
+        )),
+    ))
+}
+
+/// Shows a signed in user which client a device user code belongs to.
+pub async fn device_verification(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    Query(query): Query<DeviceVerificationQuery>,
+) -> ResponseResult<impl IntoResponse> {
+    let (request, client) = state
+        .oauth_service
+        .find_device_authorization(&query.user_code)
+        .await?;
+
+    Ok(Json(DeviceVerificationResponse::new(request, client)))
+}
+
+/// Lets a signed in user approve or deny a device user code.
+pub async fn verify_device(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(payload): Json<DeviceVerificationRequest>,
+) -> ResponseResult<impl IntoResponse> {
+    state
+        .oauth_service
+        .decide_device_authorization(
+            &payload.user_code,
+            user.user_id,
+            payload.approve,
+            user.claims
+                .authentication()
+                .ok_or(auth::Error::InvalidToken)?,
+        )
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Token endpoint (RFC 6749 section 3.2) for the `authorization_code`,
+/// `refresh_token`, `client_credentials`, device code and token exchange grants.
+pub async fn token(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(payload): Form<TokenRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let client = authenticate_client(&state, &headers, &payload.client).await?;
+
+    let grant_type =
+        GrantType::from_str(&payload.grant_type).map_err(|_| OAuthError::UnsupportedGrantType)?;
+    if !client.allows_grant_type(grant_type) {
+        return Err(OAuthError::UnauthorizedClient);
+    }
+
+    let response = match grant_type {
+        GrantType::AuthorizationCode => {
+            authorization_code_grant(&state, &client, addr, headers, payload).await?
+        }
+        GrantType::DeviceCode => {
+            let device_code = required(payload.device_code, "device_code")?;
+            let (tokens, scope) = state
+                .oauth_service
+                .exchange_device_code(&client, &device_code, addr.ip(), get_device_info(headers))
+                .await?;
+
+            token_response(
+                &state,
+                &client,
+                tokens.access_token,
+                Some(tokens.refresh_token),
+                tokens.id_token,
+                Some(scope).filter(|scope| !scope.is_empty()),
+            )
+        }
+        GrantType::TokenExchange => {
+            // Only clients that can keep a secret, like gateways, may swap tokens
+            if client.is_public() {
+                return Err(OAuthError::UnauthorizedClient);
+            }
+            token_exchange_grant(&state, &client, payload).await?
+        }
+        GrantType::RefreshToken => {
+            let refresh_token = required(payload.refresh_token, "refresh_token")?;
+            let (access_token, refresh_token) = state
+                .auth_service
+                .refresh_session(&refresh_token, Some(&client))
+                .await?;
+
+            token_response(
+                &state,
+                &client,
+                access_token,
+                Some(refresh_token),
+                None,
+                None,
+            )
+        }
+        GrantType::ClientCredentials => {
+            // Only clients that can keep a secret may act on their own behalf
+            if client.is_public() {
+                return Err(OAuthError::UnauthorizedClient);
+            }
+            let scope = payload.scope.unwrap_or_default();
+            if !client.allows_scopes(&scope) {
+                return Err(OAuthError::InvalidScope);
+            }
+
+            let access_token = state
+                .oauth_service
+                .client_credentials(&client, &scope)
+                .await?;
+            let scope = Some(scope).filter(|scope| !scope.is_empty());
+
+            token_response(&state, &client, access_token, None, None, scope)
+        }
+    };
+
+    // Token responses must never be cached (RFC 6749 section 5.1)
+    Ok(([(CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+/// Token introspection (RFC 7662) for resource servers that can't verify tokens locally.
+pub async fn introspect(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(payload): Form<IntrospectionRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let client = authenticate_client(&state, &headers, &payload.client).await?;
+    // Anyone can name a public client, so it can't vouch for the caller
+    if client.is_public() {
+        return Err(OAuthError::InvalidClient);
+    }
+
+    // Unknown hints are ignored, as the RFC requires
+    let hint = payload
+        .token_type_hint
+        .as_deref()
+        .and_then(token_type_from_hint);
+
+    let introspection = state
+        .auth_service
+        .introspect(&payload.token, hint, &client.client_id)
+        .await?;
+
+    Ok(Json(IntrospectionResponse::from(introspection)))
+}
+
+/// Token revocation (RFC 7009). Answers 200 whether or not the token was valid.
+pub async fn revoke(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(payload): Form<RevocationRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let client = authenticate_client(&state, &headers, &payload.client).await?;
+
+    let hint = payload
+        .token_type_hint
+        .as_deref()
+        .and_then(token_type_from_hint);
+
+    state
+        .auth_service
+        .revoke_token(&payload.token, hint, &client.client_id)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+async fn authorization_code_grant(
+    state: &AppState,
+    client: &Client,
+    addr: SocketAddr,
+    headers: HeaderMap,
+    payload: TokenRequest,
+) -> Result<TokenResponse, OAuthError> {
+    let code = required(payload.code, "code")?;
+    let redirect_uri = required(payload.redirect_uri, "redirect_uri")?;
+    let code_verifier = required(payload.code_verifier, "code_verifier")?;
+
+    let (tokens, scope) = state
+        .oauth_service
+        .exchange_authorization_code(
+            client,
+            &code,
+            &redirect_uri,
+            &code_verifier,
+            addr.ip(),
+            get_device_info(headers),
+        )
+        .await?;
+
+    Ok(token_response(
+        state,
+        client,
+        tokens.access_token,
+        Some(tokens.refresh_token),
+        tokens.id_token,
+        Some(scope).filter(|scope| !scope.is_empty()),
+    ))
+}
+
+async fn token_exchange_grant(
+    state: &AppState,
+    client: &Client,
+    payload: TokenRequest,
+) -> Result<TokenResponse, OAuthError> {
+    let subject_token = required(payload.subject_token, "subject_token")?;
+    let subject_token_type = required(payload.subject_token_type, "subject_token_type")?;
+    if !is_access_token_type(&subject_token_type)
+        || payload
+            .actor_token_type
+            .as_deref()
+            .is_some_and(|token_type| !is_access_token_type(token_type))
+    {
+        return Err(OAuthError::InvalidRequest(
+            "Only access tokens can be exchanged".to_string(),
+        ));
+    }
+    if payload.actor_token.is_some() != payload.actor_token_type.is_some() {
+        return Err(OAuthError::InvalidRequest(
+            "actor_token and actor_token_type go together".to_string(),
+        ));
+    }
+    if payload
+        .requested_token_type
+        .as_deref()
+        .is_some_and(|token_type| token_type != ACCESS_TOKEN_TYPE)
+    {
+        return Err(OAuthError::InvalidRequest(
+            "Only access tokens can be requested".to_string(),
+        ));
+    }
+
+    let (access_token, expires_in, scope) = state
+        .oauth_service
+        .exchange_token(
+            client,
+            TokenExchangeRequest {
+                subject_token,
+                actor_token: payload.actor_token,
+                audience: payload.audience,
+                scope: payload.scope,
+            },
+        )
+        .await?;
+
+    Ok(TokenResponse {
+        issued_token_type: Some(ACCESS_TOKEN_TYPE.to_string()),
+        expires_in,
+        ..token_response(
+            state,
+            client,
+            access_token,
+            None,
+            None,
+            Some(scope).filter(|scope| !scope.is_empty()),
+        )
+    })
+}
+
+fn is_access_token_type(token_type: &str) -> bool {
+    token_type == ACCESS_TOKEN_TYPE || token_type == JWT_TOKEN_TYPE
+}
+
+fn token_response(
+    state: &AppState,
+    client: &Client,
+    access_token: String,
+    refresh_token: Option<String>,
+    id_token: Option<String>,
+    scope: Option<String>,
+) -> TokenResponse {
+    TokenResponse {
+        access_token,
+        issued_token_type: None,
+        token_type: "Bearer".to_string(),
+        expires_in: state
+            .auth_service
+            .access_token_lifetime(Some(client))
+            .num_seconds(),
+        refresh_token,
+        id_token,
+        scope,
+    }
+}
+
+fn required(value: Option<String>, name: &str) -> Result<String, OAuthError> {
+    value
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| OAuthError::InvalidRequest(format!("{name} is required")))
+}
+
+async fn find_client(
+    state: &AppState,
+    params: &AuthorizeParams,
+) -> Result<Option<Client>, AuthorizeRejection> {
+    let Some(client_id) = params.client_id.as_deref() else {
+        return Ok(None);
+    };
+
+    state
+        .client_service
+        .find_client(client_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to load client: {e}");
+            AuthorizeRejection::Invalid("Something went wrong, please try again".into())
+        })
+}
+
+// Maps `access_token`/`refresh_token` hints to a token type
+fn token_type_from_hint(hint: &str) -> Option<TokenType> {
+    TokenType::from_str(hint.strip_suffix("_token")?).ok()
+}
@@ -0,0 +1,39 @@
+/* V1 session handler module */
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::app_modules::api::ResponseResult;
+use crate::app_modules::api::v1::schemas::SessionResponse;
+use crate::app_modules::{AppState, auth::AuthenticatedUser};
+
+pub async fn list_my_sessions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> ResponseResult<impl IntoResponse> {
+    let sessions = state.auth_service.list_sessions(user.user_id).await?;
+
+    let sessions: Vec<SessionResponse> = sessions
+        .into_iter()
+        .map(|session| SessionResponse::new(session, user.session_id))
+        .collect();
+
+    Ok(Json(sessions))
+}
+
+pub async fn revoke_my_session(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(session_id): Path<Uuid>,
+) -> ResponseResult<impl IntoResponse> {
+    state
+        .auth_service
+        .revoke_user_session(user.user_id, session_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
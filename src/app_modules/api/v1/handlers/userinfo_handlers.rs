@@ -0,0 +1,25 @@
+/* V1 userinfo handler module */
+
+use axum::{
+    extract::{Json, State},
+    response::IntoResponse,
+};
+
+use crate::app_modules::api::v1::schemas::UserInfoResponse;
+use crate::app_modules::api::{AppError, ResponseResult};
+use crate::app_modules::{AppState, auth::OpenIdUser};
+
+/// OpenID Connect UserInfo endpoint, describing the owner of the access token
+/// with the claims its scope grants.
+pub async fn userinfo(
+    State(state): State<AppState>,
+    OpenIdUser(owner): OpenIdUser,
+) -> ResponseResult<impl IntoResponse> {
+    let user = state
+        .user_service
+        .find_user_by_id(&owner.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    Ok(Json(UserInfoResponse::new(user, &owner.claims.scope)))
+}
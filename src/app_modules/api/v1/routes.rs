@@ -1,12 +1,97 @@
 /* Api V1 routes module */
 
-use axum::{Router, routing::post};
+use axum::{
+    Router,
+    routing::{delete, get, post, put},
+};
 
 use crate::app_modules::AppState;
-use crate::app_modules::api::v1::handlers::auth_handlers;
+use crate::app_modules::api::v1::handlers::{
+    account_handlers, admin_handlers, auth_handlers, authz_handlers, oauth_handlers,
+    session_handlers, userinfo_handlers,
+};
 
 pub fn v1_routes() -> Router<AppState> {
     Router::new()
         .route("/auth/signup", post(auth_handlers::local_signup))
         .route("/auth/login", post(auth_handlers::local_login))
+        .route("/auth/refresh", post(auth_handlers::refresh_token))
+        .route("/auth/logout", post(auth_handlers::logout))
+        .route("/auth/logout-all", post(auth_handlers::logout_all))
+        .route(
+            "/oauth/authorize",
+            get(oauth_handlers::authorize).post(oauth_handlers::authorize_login),
+        )
+        .route(
+            "/oauth/device_authorization",
+            post(oauth_handlers::device_authorization),
+        )
+        .route(
+            "/oauth/device/verify",
+            get(oauth_handlers::device_verification).post(oauth_handlers::verify_device),
+        )
+        .route("/oauth/token", post(oauth_handlers::token))
+        .route("/oauth/introspect", post(oauth_handlers::introspect))
+        .route("/oauth/revoke", post(oauth_handlers::revoke))
+        .route(
+            "/userinfo",
+            get(userinfo_handlers::userinfo).post(userinfo_handlers::userinfo),
+        )
+        .route("/authz/check", post(authz_handlers::check))
+        .route("/authz/check/batch", post(authz_handlers::check_batch))
+        .route(
+            "/authz/resources",
+            get(authz_handlers::list_permitted_resources),
+        )
+        .route("/me", delete(account_handlers::delete_account))
+        .route("/me/password", put(account_handlers::change_password))
+        .route("/me/sessions", get(session_handlers::list_my_sessions))
+        .route(
+            "/me/sessions/{id}",
+            delete(session_handlers::revoke_my_session),
+        )
+        .route(
+            "/admin/clients",
+            get(admin_handlers::list_clients).post(admin_handlers::register_client),
+        )
+        .route(
+            "/admin/clients/{client_id}",
+            get(admin_handlers::get_client)
+                .put(admin_handlers::update_client)
+                .delete(admin_handlers::delete_client),
+        )
+        .route(
+            "/admin/clients/{client_id}/permissions",
+            get(admin_handlers::list_client_permissions)
+                .put(admin_handlers::replace_client_permissions),
+        )
+        .route(
+            "/admin/users/{user_id}/session-limit",
+            put(admin_handlers::set_user_session_limit),
+        )
+        .route(
+            "/admin/users/{user_id}/groups",
+            get(admin_handlers::list_user_groups),
+        )
+        .route(
+            "/admin/groups",
+            get(admin_handlers::list_groups).post(admin_handlers::create_group),
+        )
+        .route(
+            "/admin/groups/{group_id}",
+            get(admin_handlers::get_group).delete(admin_handlers::delete_group),
+        )
+        .route(
+            "/admin/groups/{group_id}/members",
+            get(admin_handlers::list_group_members).post(admin_handlers::add_group_member),
+        )
+        .route(
+            "/admin/groups/{group_id}/members/{member_type}/{member_id}",
+            delete(admin_handlers::remove_group_member),
+        )
+        .route("/admin/keys", get(admin_handlers::list_signing_keys))
+        .route(
+            "/admin/keys/rotate",
+            post(admin_handlers::rotate_signing_keys),
+        )
 }
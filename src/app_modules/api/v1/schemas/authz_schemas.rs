@@ -0,0 +1,184 @@
+/* V1 authorization schemas module */
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::adapters::dtos::ResourcePage;
+use crate::domain::models::{Decision, Effect, Grant, Resource};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthzCheckRequest {
+    // Only services with the authz scope may ask about someone else
+    pub principal_id: Option<Uuid>,
+    pub resource_server: String,
+    pub resource: String, // `<type>/<name>`
+    pub action: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrantResponse {
+    pub role_id: Uuid,
+    pub role: String,
+    pub resource: String,
+    pub effect: Effect,
+}
+
+impl From<Grant> for GrantResponse {
+    fn from(grant: Grant) -> Self {
+        Self {
+            role_id: grant.role_id,
+            role: grant.role,
+            resource: grant.resource,
+            effect: grant.effect,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthzCheckResponse {
+    pub decision: &'static str, // "allow" or "deny"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grant: Option<GrantResponse>,
+}
+
+impl From<Decision> for AuthzCheckResponse {
+    fn from(decision: Decision) -> Self {
+        Self {
+            decision: if decision.allowed { "allow" } else { "deny" },
+            grant: decision.grant.map(GrantResponse::from),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthzBatchCheckRequest {
+    pub principal_id: Option<Uuid>,
+    pub resource_server: String,
+    pub checks: Vec<AuthzBatchCheckItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthzBatchCheckItem {
+    pub resource: String,
+    pub action: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthzBatchCheckResult {
+    pub resource: String,
+    pub action: String,
+    #[serde(flatten)]
+    pub result: AuthzCheckResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthzBatchCheckResponse {
+    pub results: Vec<AuthzBatchCheckResult>, // in the order of the checks
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermittedResourcesQuery {
+    pub principal_id: Option<Uuid>,
+    pub resource_server: String,
+    pub resource_type: String,
+    pub action: String,
+    pub after: Option<Uuid>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PermittedResourceResponse {
+    pub id: Uuid,
+    pub name: String,
+}
+
+impl From<Resource> for PermittedResourceResponse {
+    fn from(resource: Resource) -> Self {
+        Self {
+            id: resource.id,
+            name: resource.name,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermittedResourcesResponse {
+    pub resources: Vec<PermittedResourceResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<Uuid>,
+}
+
+impl From<ResourcePage> for PermittedResourcesResponse {
+    fn from(page: ResourcePage) -> Self {
+        Self {
+            resources: page
+                .resources
+                .into_iter()
+                .map(PermittedResourceResponse::from)
+                .collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_allowed_decision_reports_its_grant() {
+        let role_id = Uuid::parse_str("c21ff270-2b35-48fb-adcf-2b1756003c98").unwrap();
+        let decision = Decision::allow(Grant {
+            role_id,
+            role: "editor".to_string(),
+            resource: "channel/general".to_string(),
+            effect: Effect::Allow,
+        });
+
+        let actual = serde_json::to_value(AuthzCheckResponse::from(decision)).unwrap();
+
+        let expected = json!({
+            "decision": "allow",
+            "grant": {
+                "roleId": "c21ff270-2b35-48fb-adcf-2b1756003c98",
+                "role": "editor",
+                "resource": "channel/general",
+                "effect": "allow",
+            },
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_denied_decision_has_no_grant() {
+        let actual = serde_json::to_value(AuthzCheckResponse::from(Decision::deny())).unwrap();
+
+        assert_eq!(actual, json!({ "decision": "deny" }));
+    }
+
+    #[test]
+    fn test_batch_results_name_their_check() {
+        let result = AuthzBatchCheckResult {
+            resource: "channel/general".to_string(),
+            action: "write".to_string(),
+            result: Decision::deny().into(),
+        };
+
+        let actual = serde_json::to_value(result).unwrap();
+
+        let expected = json!({
+            "resource": "channel/general",
+            "action": "write",
+            "decision": "deny",
+        });
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,185 @@
+/* V1 client registry schemas module */
+
+use jsonwebtoken::jwk::JwkSet;
+use serde::{Deserialize, Serialize};
+
+use crate::adapters::dtos::ClientSettings;
+use crate::domain::models::{Client, ClientPermission, GrantType};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientRequest {
+    pub name: String,
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+    #[serde(default = "GrantType::defaults")]
+    pub grant_types: Vec<GrantType>,
+    #[serde(default = "Client::default_scopes")]
+    pub scopes: Vec<String>,
+    // Other clients it may exchange tokens for
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    pub access_token_lifetime: Option<i32>,
+    pub refresh_token_lifetime: Option<i32>,
+    // Public keys of clients using private_key_jwt, these get no secret
+    pub jwks: Option<JwkSet>,
+    // Only read on registration, public clients get no secret
+    #[serde(default)]
+    pub public: bool,
+    // Only read on update
+    #[serde(default = "active_by_default")]
+    pub is_active: bool,
+}
+
+fn active_by_default() -> bool {
+    true
+}
+
+impl From<ClientRequest> for ClientSettings {
+    fn from(request: ClientRequest) -> Self {
+        Self {
+            name: request.name,
+            redirect_uris: request.redirect_uris,
+            grant_types: request.grant_types,
+            scopes: request.scopes,
+            audiences: request.audiences,
+            access_token_lifetime: request.access_token_lifetime,
+            refresh_token_lifetime: request.refresh_token_lifetime,
+            jwks: request.jwks,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientResponse {
+    pub client_id: String,
+    pub name: String,
+    pub public: bool,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<GrantType>,
+    pub scopes: Vec<String>,
+    pub audiences: Vec<String>,
+    pub access_token_lifetime: Option<i32>,
+    pub refresh_token_lifetime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwks: Option<JwkSet>,
+    pub is_active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<Client> for ClientResponse {
+    fn from(client: Client) -> Self {
+        Self {
+            public: client.is_public(),
+            client_id: client.client_id,
+            name: client.name,
+            redirect_uris: client.redirect_uris,
+            grant_types: client.grant_types,
+            scopes: client.scopes,
+            audiences: client.audiences,
+            access_token_lifetime: client.access_token_lifetime,
+            refresh_token_lifetime: client.refresh_token_lifetime,
+            jwks: client.jwks,
+            is_active: client.is_active,
+            created_at: client.created_at.to_rfc3339(),
+            updated_at: client.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Returned once on registration, the only time the secret is shown.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredClientResponse {
+    #[serde(flatten)]
+    pub client: ClientResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientPermissionRequest {
+    pub resource_server: String,
+    pub resource: String,
+    pub actions: Vec<String>,
+}
+
+impl From<ClientPermissionRequest> for ClientPermission {
+    fn from(request: ClientPermissionRequest) -> Self {
+        Self {
+            resource_server: request.resource_server,
+            resource: request.resource,
+            actions: request.actions,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientPermissionResponse {
+    pub resource_server: String,
+    pub resource: String,
+    pub actions: Vec<String>,
+}
+
+impl From<ClientPermission> for ClientPermissionResponse {
+    fn from(permission: ClientPermission) -> Self {
+        Self {
+            resource_server: permission.resource_server,
+            resource: permission.resource,
+            actions: permission.actions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_client_request_defaults() {
+        let request: ClientRequest = serde_json::from_value(json!({
+            "name": "SPA",
+            "redirectUris": ["https://spa.example.com/callback"],
+        }))
+        .unwrap();
+
+        assert_eq!(request.grant_types, GrantType::defaults());
+        assert_eq!(request.scopes, Client::default_scopes());
+        assert!(request.audiences.is_empty());
+        assert!(!request.public);
+        assert!(request.is_active);
+    }
+
+    #[test]
+    fn test_unknown_grant_type_is_rejected() {
+        let request = serde_json::from_value::<ClientRequest>(json!({
+            "name": "SPA",
+            "grantTypes": ["password"],
+        }));
+
+        assert!(request.is_err());
+    }
+
+    #[test]
+    fn test_registered_client_shows_secret_once() {
+        let client = Client::new("abc".to_string(), "API".to_string(), None, vec![]);
+        let response = RegisteredClientResponse {
+            client: client.into(),
+            client_secret: Some("secret".to_string()),
+        };
+
+        let actual = serde_json::to_value(response).unwrap();
+
+        assert_eq!(actual["clientId"], "abc");
+        assert_eq!(actual["clientSecret"], "secret");
+        assert_eq!(
+            actual["grantTypes"],
+            json!(["authorization_code", "refresh_token"])
+        );
+    }
+}
@@ -0,0 +1,137 @@
+/* V1 group schemas module */
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::{Group, GroupMember, Principal};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupRequest {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: String,
+}
+
+impl From<Group> for GroupResponse {
+    fn from(group: Group) -> Self {
+        Self {
+            id: group.id,
+            name: group.name,
+            description: group.description,
+            created_at: group.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberType {
+    User,
+    Group,
+}
+
+impl MemberType {
+    pub fn principal(self, member_id: Uuid) -> Principal {
+        match self {
+            MemberType::User => Principal::User(member_id),
+            MemberType::Group => Principal::Group(member_id),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMemberRequest {
+    pub member_type: MemberType,
+    pub member_id: Uuid,
+}
+
+impl From<GroupMemberRequest> for Principal {
+    fn from(request: GroupMemberRequest) -> Self {
+        request.member_type.principal(request.member_id)
+    }
+}
+
+/// Path of `/admin/groups/{group_id}/members/{member_type}/{member_id}`.
+#[derive(Debug, Deserialize)]
+pub struct GroupMemberPath {
+    pub group_id: Uuid,
+    pub member_type: MemberType,
+    pub member_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupMemberResponse {
+    pub member_type: MemberType,
+    pub member_id: Uuid,
+    pub added_at: String,
+}
+
+impl From<GroupMember> for GroupMemberResponse {
+    fn from(member: GroupMember) -> Self {
+        let member_type = match member.member {
+            Principal::User(_) => MemberType::User,
+            Principal::Group(_) => MemberType::Group,
+        };
+        Self {
+            member_type,
+            member_id: member.member.id(),
+            added_at: member.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    #[test]
+    fn test_member_request_names_a_principal() {
+        let member_id = Uuid::new_v4();
+        let request: GroupMemberRequest = serde_json::from_value(json!({
+            "memberType": "group",
+            "memberId": member_id,
+        }))
+        .unwrap();
+
+        assert_eq!(Principal::from(request), Principal::Group(member_id));
+    }
+
+    #[test]
+    fn test_unknown_member_type_is_rejected() {
+        let request = serde_json::from_value::<GroupMemberRequest>(json!({
+            "memberType": "client",
+            "memberId": Uuid::new_v4(),
+        }));
+
+        assert!(request.is_err());
+    }
+
+    #[test]
+    fn test_member_response_shape() {
+        let user_id = Uuid::new_v4();
+        let member = GroupMember {
+            group_id: Uuid::new_v4(),
+            member: Principal::User(user_id),
+            created_at: Utc::now(),
+        };
+
+        let actual = serde_json::to_value(GroupMemberResponse::from(member)).unwrap();
+
+        assert_eq!(actual["memberType"], "user");
+        assert_eq!(actual["memberId"], json!(user_id));
+        assert!(actual["addedAt"].is_string());
+    }
+}
@@ -0,0 +1,31 @@
+/* V1 signing key schemas module */
+
+use serde::Serialize;
+
+use crate::domain::models::SigningKey;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningKeyResponse {
+    pub kid: String,
+    pub algorithm: String,
+    pub state: String,
+    pub created_at: String,
+    pub activated_at: Option<String>,
+    pub retire_after: Option<String>,
+    pub retired_at: Option<String>,
+}
+
+impl From<SigningKey> for SigningKeyResponse {
+    fn from(key: SigningKey) -> Self {
+        Self {
+            kid: key.kid,
+            algorithm: key.algorithm,
+            state: key.key_state.to_string(),
+            created_at: key.created_at.to_rfc3339(),
+            activated_at: key.activated_at.map(|at| at.to_rfc3339()),
+            retire_after: key.retire_after.map(|at| at.to_rfc3339()),
+            retired_at: key.retired_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
@@ -1,8 +1,37 @@
 /* V1 Schemas module  */
 
+mod authz_schemas;
+mod client_schemas;
+mod group_schemas;
+mod key_schemas;
+mod oauth_schemas;
+mod session_schemas;
 mod user_schemas;
+mod userinfo_schemas;
 
 // re-exports
+pub use authz_schemas::{
+    AuthzBatchCheckRequest, AuthzBatchCheckResponse, AuthzBatchCheckResult, AuthzCheckRequest,
+    AuthzCheckResponse, PermittedResourcesQuery, PermittedResourcesResponse,
+};
+pub use client_schemas::{
+    ClientPermissionRequest, ClientPermissionResponse, ClientRequest, ClientResponse,
+    RegisteredClientResponse,
+};
+pub use group_schemas::{
+    GroupMemberPath, GroupMemberRequest, GroupMemberResponse, GroupRequest, GroupResponse,
+};
+pub use key_schemas::SigningKeyResponse;
+pub use oauth_schemas::{
+    AuthorizeLoginRequest, DeviceAuthorizationRequest, DeviceAuthorizationResponse,
+    DeviceVerificationQuery, DeviceVerificationRequest, DeviceVerificationResponse,
+    IntrospectionRequest, IntrospectionResponse, RevocationRequest, TokenRequest, TokenResponse,
+};
+pub use session_schemas::{LogoutAllRequest, LogoutAllResponse, SessionResponse};
 pub use user_schemas::AuthLocal;
 pub use user_schemas::AuthResponse;
+pub use user_schemas::ChangePasswordRequest;
+pub use user_schemas::RefreshTokenRequest;
+pub use user_schemas::SessionLimitRequest;
 pub use user_schemas::UserResponse;
+pub use userinfo_schemas::UserInfoResponse;
@@ -0,0 +1,300 @@
+/* V1 OAuth schemas module */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::adapters::dtos::DeviceAuthorization;
+use crate::app_modules::oauth::ClientAuthentication;
+use crate::app_modules::oauth::authorize::AuthorizeParams;
+use crate::domain::models::{Acr, Actor, Client, DeviceCode, TokenIntrospection};
+use crate::utils::user_code;
+
+// OAuth endpoints use the snake_case form and JSON fields of the RFCs
+
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeLoginRequest {
+    #[serde(flatten)]
+    pub params: AuthorizeParams,
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    pub device_code: Option<String>,
+    pub subject_token: Option<String>,
+    pub subject_token_type: Option<String>,
+    pub actor_token: Option<String>,
+    pub actor_token_type: Option<String>,
+    pub requested_token_type: Option<String>,
+    pub audience: Option<String>,
+    pub scope: Option<String>,
+    #[serde(flatten)]
+    pub client: ClientAuthentication,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issued_token_type: Option<String>,
+    pub token_type: String,
+    pub expires_in: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeviceAuthorizationRequest {
+    pub scope: Option<String>,
+    #[serde(flatten)]
+    pub client: ClientAuthentication,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeviceAuthorizationResponse {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: String,
+    pub expires_in: i64,
+    pub interval: i64,
+}
+
+impl DeviceAuthorizationResponse {
+    /// Points users to `verification_uri` to enter the user code.
+    pub fn new(authorization: DeviceAuthorization, verification_uri: String) -> Self {
+        let user_code = user_code::display(&authorization.user_code);
+
+        Self {
+            device_code: authorization.device_code,
+            verification_uri_complete: format!("{verification_uri}?user_code={user_code}"),
+            user_code,
+            verification_uri,
+            expires_in: authorization.expires_in,
+            interval: authorization.interval,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeviceVerificationQuery {
+    pub user_code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeviceVerificationRequest {
+    pub user_code: String,
+    pub approve: bool,
+}
+
+/// What a user is asked to approve for a device.
+#[derive(Debug, Serialize)]
+pub struct DeviceVerificationResponse {
+    pub client_id: String,
+    pub client_name: String,
+    pub scope: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl DeviceVerificationResponse {
+    pub fn new(request: DeviceCode, client: Client) -> Self {
+        Self {
+            client_id: client.client_id,
+            client_name: client.name,
+            scope: request.scope,
+            expires_at: request.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IntrospectionRequest {
+    pub token: String,
+    pub token_type_hint: Option<String>,
+    #[serde(flatten)]
+    pub client: ClientAuthentication,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevocationRequest {
+    pub token: String,
+    pub token_type_hint: Option<String>,
+    #[serde(flatten)]
+    pub client: ClientAuthentication,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acr: Option<Acr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+}
+
+impl From<Option<TokenIntrospection>> for IntrospectionResponse {
+    fn from(introspection: Option<TokenIntrospection>) -> Self {
+        // Inactive tokens reveal nothing but `active: false`
+        let Some(token) = introspection else {
+            return Self::default();
+        };
+
+        Self {
+            active: true,
+            sub: Some(token.sub),
+            scope: Some(token.scope),
+            sid: token.sid,
+            iss: Some(token.iss),
+            aud: Some(token.aud),
+            client_id: Some(token.client_id),
+            exp: Some(token.exp),
+            iat: Some(token.iat),
+            jti: token.jti,
+            act: token.act,
+            auth_time: token.auth_time,
+            acr: token.acr,
+            token_type: Some(token.token_type.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::TokenType;
+    use serde_json::json;
+
+    #[test]
+    fn test_authorize_login_request_keeps_params() {
+        let body = "response_type=code&client_id=spa&state=xyz&email=a%40b.com&password=secret";
+
+        let request: AuthorizeLoginRequest = serde_urlencoded::from_str(body).unwrap();
+
+        assert_eq!(request.params.client_id.as_deref(), Some("spa"));
+        assert_eq!(request.params.state.as_deref(), Some("xyz"));
+        assert_eq!(request.email, "a@b.com");
+    }
+
+    #[test]
+    fn test_token_request_reads_client_assertion() {
+        let body = "grant_type=client_credentials&scope=reports\
+            &client_assertion_type=urn%3Aietf%3Aparams%3Aoauth%3Aclient-assertion-type%3Ajwt-bearer\
+            &client_assertion=a.b.c";
+
+        let request: TokenRequest = serde_urlencoded::from_str(body).unwrap();
+
+        assert_eq!(request.grant_type, "client_credentials");
+        assert_eq!(request.scope.as_deref(), Some("reports"));
+        assert_eq!(request.client.client_assertion.as_deref(), Some("a.b.c"));
+        assert!(request.client.client_id.is_none());
+    }
+
+    #[test]
+    fn test_token_request_reads_device_code() {
+        let body = "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Adevice_code\
+            &device_code=abc&client_id=cli";
+
+        let request: TokenRequest = serde_urlencoded::from_str(body).unwrap();
+
+        assert_eq!(
+            request.grant_type,
+            "urn:ietf:params:oauth:grant-type:device_code"
+        );
+        assert_eq!(request.device_code.as_deref(), Some("abc"));
+        assert_eq!(request.client.client_id.as_deref(), Some("cli"));
+    }
+
+    #[test]
+    fn test_token_request_reads_token_exchange() {
+        let body = "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Atoken-exchange\
+            &subject_token=s.t.x&subject_token_type=urn%3Aietf%3Aparams%3Aoauth%3Atoken-type%3Aaccess_token\
+            &actor_token=a.t.x&actor_token_type=urn%3Aietf%3Aparams%3Aoauth%3Atoken-type%3Ajwt\
+            &audience=billing&scope=user";
+
+        let request: TokenRequest = serde_urlencoded::from_str(body).unwrap();
+
+        assert_eq!(request.subject_token.as_deref(), Some("s.t.x"));
+        assert_eq!(request.actor_token.as_deref(), Some("a.t.x"));
+        assert_eq!(request.audience.as_deref(), Some("billing"));
+        assert_eq!(request.scope.as_deref(), Some("user"));
+    }
+
+    #[test]
+    fn test_inactive_token_only_reports_active() {
+        let actual = serde_json::to_value(IntrospectionResponse::from(None)).unwrap();
+
+        assert_eq!(actual, json!({ "active": false }));
+    }
+
+    #[test]
+    fn test_active_token_reports_claims() {
+        let sid = Uuid::parse_str("c21ff270-2b35-48fb-adcf-2b1756003c98").unwrap();
+        let introspection = TokenIntrospection {
+            sub: "user-id".to_string(),
+            scope: "user".to_string(),
+            sid: Some(sid),
+            iss: "localhost".to_string(),
+            aud: "spa".to_string(),
+            client_id: "spa".to_string(),
+            exp: 1_700_000_900,
+            iat: 1_700_000_000,
+            jti: None,
+            act: None,
+            auth_time: Some(1_700_000_000),
+            acr: Some(Acr::Mfa),
+            token_type: TokenType::Refresh,
+        };
+
+        let actual =
+            serde_json::to_value(IntrospectionResponse::from(Some(introspection))).unwrap();
+
+        let expected = json!({
+            "active": true,
+            "sub": "user-id",
+            "scope": "user",
+            "sid": "c21ff270-2b35-48fb-adcf-2b1756003c98",
+            "iss": "localhost",
+            "aud": "spa",
+            "client_id": "spa",
+            "exp": 1_700_000_900,
+            "iat": 1_700_000_000,
+            "auth_time": 1_700_000_000,
+            "acr": "mfa",
+            "token_type": "refresh",
+        });
+        assert_eq!(actual, expected);
+    }
+}
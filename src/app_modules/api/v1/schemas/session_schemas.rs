@@ -0,0 +1,98 @@
+/* V1 session schemas module */
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::models::Session;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoutAllRequest {
+    #[serde(default)]
+    pub keep_current: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoutAllResponse {
+    pub revoked_sessions: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub device_name: Option<String>,
+    pub device_type: Option<String>,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+    pub last_active_at: String,
+    pub current: bool,
+}
+
+impl SessionResponse {
+    pub fn new(session: Session, current_session_id: Uuid) -> Self {
+        Self {
+            current: session.id == current_session_id,
+            id: session.id,
+            device_name: session.device_name,
+            device_type: session.device_type,
+            ip_address: session.ip_address.to_string(),
+            user_agent: session.user_agent,
+            created_at: session.created_at.to_rfc3339(),
+            last_active_at: session.last_active_at.to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::Acr;
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+
+    #[test]
+    fn test_session_response_schema() {
+        let session_id = Uuid::parse_str("c21ff270-2b35-48fb-adcf-2b1756003c98").unwrap();
+        let created_at: DateTime<Utc> = "2025-05-04T09:57:13.479118+00:00".parse().unwrap();
+
+        let session = Session {
+            id: session_id,
+            user_id: Uuid::new_v4(),
+            refresh_token_hash: "hash".to_string(),
+            refresh_token_generation: 2,
+            device_identifier: None,
+            device_name: Some("iPhone".to_string()),
+            device_type: Some("Mobile".to_string()),
+            ip_address: "10.0.0.1".parse().unwrap(),
+            user_agent: Some("iOS".to_string()),
+            expires_at: created_at,
+            created_at,
+            last_active_at: created_at,
+            is_revoked: false,
+            revoked_reason: None,
+            revoked_at: None,
+            client_id: None,
+            acr: Acr::Password,
+            auth_time: created_at,
+            scope: None,
+        };
+
+        let actual = serde_json::to_value(SessionResponse::new(session, session_id)).unwrap();
+
+        let expected = json!({
+            "id": "c21ff270-2b35-48fb-adcf-2b1756003c98",
+            "deviceName": "iPhone",
+            "deviceType": "Mobile",
+            "ipAddress": "10.0.0.1",
+            "userAgent": "iOS",
+            "createdAt": "2025-05-04T09:57:13.479118+00:00",
+            "lastActiveAt": "2025-05-04T09:57:13.479118+00:00",
+            "current": true,
+        });
+
+        assert_eq!(actual, expected);
+    }
+}
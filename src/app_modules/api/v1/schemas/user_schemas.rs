@@ -1,6 +1,8 @@
 /* V1 use schemas module */
 
+use crate::adapters::dtos::OpenIdRequest;
 use crate::domain::models::User;
+use crate::domain::services::has_scope;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
@@ -12,6 +14,25 @@ pub struct AuthLocal {
     pub email: String,
     #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
     pub password: String,
+    // Space separated, `openid` requests an ID token on login
+    pub scope: Option<String>,
+    pub nonce: Option<String>,
+}
+
+impl AuthLocal {
+    pub fn openid_request(&self) -> Option<OpenIdRequest> {
+        let scope = self.scope.as_deref()?;
+
+        has_scope(scope, "openid").then(|| OpenIdRequest {
+            nonce: self.nonce.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -20,6 +41,22 @@ pub struct AuthResponse {
     pub access_token: String,
     pub refresh_token: String,
     pub token_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+/// A user's own session limit; `null` falls back to the server default.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLimitRequest {
+    pub max_sessions: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub new_password: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -97,10 +134,34 @@ mod tests {
         let auth_local = AuthLocal {
             email: "test@mail.com".to_string(),
             password: "123456789".to_string(),
+            scope: None,
+            nonce: None,
         };
 
         let result = auth_local.validate();
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_openid_scope_requests_id_token() {
+        let auth_local: AuthLocal = serde_json::from_value(json!({
+            "email": "test@mail.com",
+            "password": "123456789",
+            "scope": "email openid",
+            "nonce": "abc",
+        }))
+        .unwrap();
+
+        let openid = auth_local.openid_request().unwrap();
+        assert_eq!(openid.nonce.as_deref(), Some("abc"));
+
+        let without_openid: AuthLocal = serde_json::from_value(json!({
+            "email": "test@mail.com",
+            "password": "123456789",
+            "scope": "email",
+        }))
+        .unwrap();
+        assert!(without_openid.openid_request().is_none());
+    }
 }
@@ -0,0 +1,73 @@
+/* V1 userinfo schemas module */
+
+use serde::Serialize;
+
+use crate::domain::models::User;
+use crate::domain::services::has_scope;
+
+// Claim names follow OpenID Connect Core, section 5.1
+#[derive(Debug, Serialize)]
+pub struct UserInfoResponse {
+    pub sub: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_username: Option<String>,
+    pub updated_at: i64,
+}
+
+impl UserInfoResponse {
+    /// The claims of `user` the token's `scope` grants, email ones only
+    /// with the `email` scope.
+    pub fn new(user: User, scope: &str) -> Self {
+        let email = has_scope(scope, "email");
+        Self {
+            sub: user.id.to_string(),
+            email_verified: email.then_some(user.email_verified),
+            email: email.then_some(user.email),
+            preferred_username: user.username,
+            updated_at: user.updated_at.timestamp(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn user() -> User {
+        let mut user = User::new("user@mail.com".to_string());
+        user.id = Uuid::parse_str("c21ff270-2b35-48fb-adcf-2b1756003c98").unwrap();
+        user.email_verified = true;
+        user.updated_at = "2025-05-04T09:57:13+00:00".parse().unwrap();
+        user
+    }
+
+    #[test]
+    fn test_userinfo_schema() {
+        let actual = serde_json::to_value(UserInfoResponse::new(user(), "openid email")).unwrap();
+
+        let expected = json!({
+            "sub": "c21ff270-2b35-48fb-adcf-2b1756003c98",
+            "email": "user@mail.com",
+            "email_verified": true,
+            "updated_at": 1_746_352_633,
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_email_claims_need_the_email_scope() {
+        let actual = serde_json::to_value(UserInfoResponse::new(user(), "openid")).unwrap();
+
+        let expected = json!({
+            "sub": "c21ff270-2b35-48fb-adcf-2b1756003c98",
+            "updated_at": 1_746_352_633,
+        });
+        assert_eq!(actual, expected);
+    }
+}
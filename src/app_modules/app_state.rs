@@ -4,22 +4,37 @@ use std::sync::Arc;
 
 use crate::config::database::PgPool;
 use crate::domain::services::AuthService;
+use crate::domain::services::AuthorizationService;
+use crate::domain::services::ClientService;
 use crate::domain::services::EmailService;
+use crate::domain::services::GroupService;
+use crate::domain::services::KeyService;
+use crate::domain::services::OAuthService;
 use crate::domain::services::UserService;
 
 use crate::app_modules::auth::configure_auth_strategies;
 use crate::utils::PasswordUtil;
+use crate::utils::jwt_keys::JwtKeySet;
 
 // Configuration struct to hold application state
 #[derive(Clone)]
 pub struct AppState {
     pub user_service: Arc<UserService>,
     pub auth_service: Arc<AuthService>,
+    pub authorization_service: Arc<AuthorizationService>,
+    pub client_service: Arc<ClientService>,
+    pub group_service: Arc<GroupService>,
+    pub oauth_service: Arc<OAuthService>,
+    pub key_service: Arc<KeyService>,
+    pub jwt_keys: Arc<JwtKeySet>,
 }
 
 impl AppState {
-    pub fn new(db_pool: Arc<PgPool>) -> AppState {
+    pub async fn new(db_pool: Arc<PgPool>) -> AppState {
         let user_service = Arc::new(UserService::new(db_pool.clone()));
+        let client_service = Arc::new(ClientService::new(db_pool.clone()));
+        let authorization_service = Arc::new(AuthorizationService::new(db_pool.clone()));
+        let group_service = Arc::new(GroupService::new(db_pool.clone()));
 
         let email_service = Arc::new(EmailService::new());
 
@@ -29,11 +44,31 @@ impl AppState {
             Arc::new(PasswordUtil::new()),
         );
 
-        let auth_service = Arc::new(AuthService::new(auth_strategies, db_pool));
+        let key_service = Arc::new(
+            KeyService::new(db_pool.clone())
+                .await
+                .expect("Failed to load JWT signing keys"),
+        );
+        key_service.spawn_rotation_task();
+        let jwt_keys = key_service.jwt_keys();
+
+        let auth_service = Arc::new(AuthService::new(
+            auth_strategies,
+            db_pool.clone(),
+            Arc::clone(&jwt_keys),
+        ));
+        auth_service.spawn_denylist_listener();
+        let oauth_service = Arc::new(OAuthService::new(db_pool, Arc::clone(&auth_service)));
 
         AppState {
             user_service,
             auth_service,
+            authorization_service,
+            client_service,
+            group_service,
+            oauth_service,
+            key_service,
+            jwt_keys,
         }
     }
 }
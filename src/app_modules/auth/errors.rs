@@ -32,6 +32,45 @@ pub enum Error {
     #[error("Token expired")]
     TokenExpired,
 
+    #[error("Refresh token reused")]
+    RefreshTokenReused,
+
+    #[error("Session not found")]
+    SessionNotFound,
+
+    #[error("Invalid authorization grant")]
+    InvalidGrant,
+
+    #[error("Authorization pending")]
+    AuthorizationPending,
+
+    #[error("Polling too fast")]
+    SlowDown,
+
+    #[error("Device code expired")]
+    DeviceCodeExpired,
+
+    #[error("Access denied")]
+    AccessDenied,
+
+    #[error("Insufficient permissions")]
+    InsufficientPermissions,
+
+    #[error("Reauthentication required")]
+    ReauthenticationRequired {
+        max_age: Option<i64>, // seconds
+        acr: Option<crate::domain::models::Acr>,
+    },
+
+    #[error("Too many active sessions")]
+    SessionLimitReached,
+
+    #[error("Invalid scope")]
+    InvalidScope,
+
+    #[error("Invalid target")]
+    InvalidTarget,
+
     #[error("User service error: {0}")]
     UserServiceError(#[from] crate::domain::services::errors::Error),
 }
@@ -39,14 +78,39 @@ pub enum Error {
 impl From<Error> for AppError {
     fn from(error: Error) -> Self {
         match error {
-            Error::InvalidToken => AppError::BadRequest("Invalid token".to_string()),
-            Error::MissingToken => AppError::BadRequest("Missing token".to_string()),
-            Error::TokenExpired => AppError::BadRequest("Token expired".to_string()),
+            Error::InvalidToken => AppError::Unauthorized("Invalid token".to_string()),
+            Error::MissingToken => AppError::Unauthorized("Missing token".to_string()),
+            Error::TokenExpired => AppError::Unauthorized("Token expired".to_string()),
+            Error::RefreshTokenReused => {
+                AppError::Unauthorized("Refresh token reused, session revoked".to_string())
+            }
             Error::InvalidCredentials => {
                 AppError::BadRequest("Wrong username or password".to_string())
             }
             Error::InvalidEmail => AppError::BadRequest("Invalid email".to_string()),
             Error::UserNotFound => AppError::NotFound("User not found".to_string()),
+            Error::SessionNotFound => AppError::NotFound("Session not found".to_string()),
+            Error::InvalidGrant => AppError::BadRequest("Invalid authorization grant".to_string()),
+            Error::AuthorizationPending => {
+                AppError::BadRequest("Authorization pending".to_string())
+            }
+            Error::SlowDown => AppError::BadRequest("Polling too fast".to_string()),
+            Error::DeviceCodeExpired => AppError::BadRequest("Device code expired".to_string()),
+            Error::AccessDenied => AppError::Forbidden("Access denied".to_string()),
+            Error::InsufficientPermissions => {
+                AppError::Forbidden("Insufficient permissions".to_string())
+            }
+            Error::ReauthenticationRequired { max_age, acr } => {
+                AppError::ReauthenticationRequired {
+                    max_age,
+                    acr: acr.map(|acr| acr.to_string()),
+                }
+            }
+            Error::SessionLimitReached => {
+                AppError::Forbidden("Too many active sessions".to_string())
+            }
+            Error::InvalidScope => AppError::BadRequest("Invalid scope".to_string()),
+            Error::InvalidTarget => AppError::BadRequest("Invalid target".to_string()),
             Error::UserAlreadyExists => AppError::BadRequest("User already exists".to_string()),
             Error::InternalError => AppError::Internal("Internal server error".to_string()),
             Error::UserServiceError(err) => {
@@ -0,0 +1,325 @@
+/* Auth request extractors */
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::app_modules::AppState;
+use crate::app_modules::api::AppError;
+use crate::config::get_config;
+use crate::domain::models::{AccessRange, Acr, JwtClaims, Principal};
+use crate::domain::services::has_scope;
+
+use super::Error;
+
+/// The caller of a protected endpoint, resolved from a bearer access token
+/// issued to our own app.
+///
+/// Extraction fails with `MissingToken`, `InvalidToken` or `TokenExpired` when the
+/// token is absent, fails verification or has expired, and with
+/// `InsufficientPermissions` for tokens issued to a client.
+#[derive(Debug)]
+pub struct AuthenticatedUser {
+    pub user_id: Uuid,
+    pub session_id: Uuid,
+    pub claims: JwtClaims,
+}
+
+impl FromRequestParts<AppState> for AuthenticatedUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts).ok_or(Error::MissingToken)?;
+        let claims = state.auth_service.verify_access_token(token).await?;
+        // Tokens of clients only reach the endpoints their scope opens
+        if !state.auth_service.is_first_party(&claims) {
+            return Err(Error::InsufficientPermissions.into());
+        }
+
+        Ok(AuthenticatedUser::from_claims(claims)?)
+    }
+}
+
+impl AuthenticatedUser {
+    fn from_claims(claims: JwtClaims) -> Result<Self, Error> {
+        // Tokens without a session were issued to a client, not a user
+        let session_id = claims.sid.ok_or(Error::InvalidToken)?;
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| Error::InvalidToken)?;
+
+        Ok(AuthenticatedUser {
+            user_id,
+            session_id,
+            claims,
+        })
+    }
+
+    /// Rejects callers who did not sign in within `max_age` or with at least
+    /// `acr`, for sensitive operations. Fails with `ReauthenticationRequired`,
+    /// which tells the client to send the user through login again.
+    pub fn require_authentication(
+        &self,
+        max_age: Option<Duration>,
+        acr: Option<Acr>,
+    ) -> Result<(), Error> {
+        if self.claims.meets_authentication(max_age, acr, Utc::now()) {
+            return Ok(());
+        }
+        Err(Error::ReauthenticationRequired {
+            max_age: max_age.map(|max_age| max_age.num_seconds()),
+            acr,
+        })
+    }
+
+    /// Rejects callers who did not sign in within the configured
+    /// `reauthentication_max_age`.
+    pub fn require_recent_authentication(&self) -> Result<(), Error> {
+        self.require_authentication(Some(get_config().reauthentication_max_age()), None)
+    }
+}
+
+/// An authenticated caller with global access, required by admin endpoints.
+///
+/// Extraction fails with `InsufficientPermissions` for user-scoped tokens.
+#[derive(Debug)]
+pub struct AdminUser(pub AuthenticatedUser);
+
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user = AuthenticatedUser::from_request_parts(parts, state).await?;
+
+        if user.claims.scope != AccessRange::Global.to_string() {
+            return Err(Error::InsufficientPermissions.into());
+        }
+        Ok(AdminUser(user))
+    }
+}
+
+/// The owner of an access token carrying the `openid` scope, for the OpenID
+/// Connect endpoints clients call on their user's behalf.
+///
+/// Extraction fails like for `AuthenticatedUser`, except that tokens issued
+/// to a client are accepted, and with `InsufficientPermissions` for tokens
+/// without the `openid` scope.
+#[derive(Debug)]
+pub struct OpenIdUser(pub AuthenticatedUser);
+
+impl FromRequestParts<AppState> for OpenIdUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts).ok_or(Error::MissingToken)?;
+        let claims = state.auth_service.verify_access_token(token).await?;
+        if !has_scope(&claims.scope, "openid") {
+            return Err(Error::InsufficientPermissions.into());
+        }
+
+        Ok(OpenIdUser(AuthenticatedUser::from_claims(claims)?))
+    }
+}
+
+/// Scope a service's token needs to ask the authorization endpoints about
+/// any principal. Admins grant it by registering it on the client.
+pub const AUTHZ_SCOPE: &str = "authz";
+
+/// The caller of an endpoint open to users and services alike, resolved from
+/// a bearer access token. Tokens without a session were issued to a client
+/// acting on its own behalf, through the client credentials grant.
+///
+/// Extraction fails like for `AuthenticatedUser`, except that tokens a client
+/// got for its user are accepted when they carry the `AUTHZ_SCOPE`.
+#[derive(Debug)]
+pub enum AuthenticatedCaller {
+    User(AuthenticatedUser),
+    Client {
+        client_id: String,
+        claims: JwtClaims,
+    },
+}
+
+impl FromRequestParts<AppState> for AuthenticatedCaller {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts).ok_or(Error::MissingToken)?;
+        let claims = state.auth_service.verify_access_token(token).await?;
+
+        if claims.sid.is_none() {
+            return Ok(AuthenticatedCaller::Client {
+                client_id: claims.sub.clone(),
+                claims,
+            });
+        }
+        if !state.auth_service.is_first_party(&claims) && !has_scope(&claims.scope, AUTHZ_SCOPE) {
+            return Err(Error::InsufficientPermissions.into());
+        }
+
+        Ok(AuthenticatedCaller::User(AuthenticatedUser::from_claims(
+            claims,
+        )?))
+    }
+}
+
+impl AuthenticatedCaller {
+    /// Resolves who a request asks about. Users are always asked about
+    /// themselves. Services name the principal they ask about, which takes
+    /// the `AUTHZ_SCOPE`.
+    pub fn principal(self, principal_id: Option<Uuid>) -> Result<Principal, AppError> {
+        match (self, principal_id) {
+            (AuthenticatedCaller::User(user), None) => Ok(Principal::User(user.user_id)),
+            (AuthenticatedCaller::User(user), Some(principal_id)) => {
+                if principal_id != user.user_id {
+                    return Err(AppError::Forbidden(
+                        "Users can only check their own permissions".to_string(),
+                    ));
+                }
+                Ok(Principal::User(principal_id))
+            }
+            (AuthenticatedCaller::Client { claims, .. }, _)
+                if !has_scope(&claims.scope, AUTHZ_SCOPE) =>
+            {
+                Err(Error::InsufficientPermissions.into())
+            }
+            (AuthenticatedCaller::Client { .. }, Some(principal_id)) => {
+                Ok(Principal::User(principal_id))
+            }
+            (AuthenticatedCaller::Client { .. }, None) => Err(AppError::BadRequest(
+                "principalId is required for service tokens".to_string(),
+            )),
+        }
+    }
+}
+
+/// Reads the token from an `Authorization: Bearer <token>` header.
+fn bearer_token(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .strip_prefix("Bearer ")
+                .or_else(|| value.strip_prefix("bearer "))
+        })
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    fn parts_with_auth(value: Option<&str>) -> Parts {
+        let mut builder = Request::builder();
+        if let Some(value) = value {
+            builder = builder.header(AUTHORIZATION, value);
+        }
+        builder.body(()).unwrap().into_parts().0
+    }
+
+    fn caller(sid: Option<Uuid>, sub: &str, scope: &str) -> AuthenticatedCaller {
+        let now = Utc::now().timestamp();
+        let claims = JwtClaims {
+            sub: sub.to_string(),
+            scope: scope.to_string(),
+            sid,
+            iss: "gandalf".to_string(),
+            aud: "gandalf".to_string(),
+            azp: "gandalf".to_string(),
+            exp: now + 60,
+            iat: now,
+            jti: Uuid::new_v4().to_string(),
+            nbf: now,
+            auth_time: now,
+            resource_access: Default::default(),
+            token_type: "access".to_string(),
+            act: None,
+            acr: None,
+        };
+        match sid {
+            Some(session_id) => AuthenticatedCaller::User(AuthenticatedUser {
+                user_id: Uuid::parse_str(sub).unwrap(),
+                session_id,
+                claims,
+            }),
+            None => AuthenticatedCaller::Client {
+                client_id: sub.to_string(),
+                claims,
+            },
+        }
+    }
+
+    #[test]
+    fn test_users_ask_about_themselves() {
+        let user_id = Uuid::new_v4();
+        let user = || caller(Some(Uuid::new_v4()), &user_id.to_string(), "user");
+
+        assert!(matches!(
+            user().principal(None),
+            Ok(Principal::User(id)) if id == user_id
+        ));
+        assert!(matches!(
+            user().principal(Some(user_id)),
+            Ok(Principal::User(id)) if id == user_id
+        ));
+        assert!(matches!(
+            user().principal(Some(Uuid::new_v4())),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_only_authz_clients_ask_about_principals() {
+        let principal_id = Uuid::new_v4();
+
+        let trusted = caller(None, "service", "openid authz");
+        assert!(matches!(
+            trusted.principal(Some(principal_id)),
+            Ok(Principal::User(id)) if id == principal_id
+        ));
+        let untrusted = caller(None, "service", "openid");
+        assert!(matches!(
+            untrusted.principal(Some(principal_id)),
+            Err(AppError::Forbidden(_))
+        ));
+        let unnamed = caller(None, "service", "authz");
+        assert!(matches!(
+            unnamed.principal(None),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_bearer_token_is_extracted() {
+        let parts = parts_with_auth(Some("Bearer abc.def.ghi"));
+
+        assert_eq!(bearer_token(&parts), Some("abc.def.ghi"));
+    }
+
+    #[test]
+    fn test_missing_or_non_bearer_header_is_ignored() {
+        assert_eq!(bearer_token(&parts_with_auth(None)), None);
+        assert_eq!(
+            bearer_token(&parts_with_auth(Some("Basic Zm9vOmJhcg=="))),
+            None
+        );
+        assert_eq!(bearer_token(&parts_with_auth(Some("Bearer "))), None);
+    }
+}
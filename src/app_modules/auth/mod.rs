@@ -3,8 +3,10 @@
 mod auth_config;
 
 pub mod errors;
+pub mod extractors;
 pub mod strategies;
 
 pub use auth_config::{AuthMethod, configure_auth_strategies};
 pub use errors::{Error, Result};
+pub use extractors::{AdminUser, AuthenticatedCaller, AuthenticatedUser, OpenIdUser};
 pub use strategies::AuthStrategy;
@@ -8,5 +8,7 @@ pub mod api;
 pub mod auth;
 pub mod health;
 pub mod middleware;
+pub mod oauth;
+pub mod well_known;
 
 pub use app_state::AppState;
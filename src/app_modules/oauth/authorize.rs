@@ -0,0 +1,407 @@
+/* OAuth authorization endpoint helpers */
+
+use axum::{
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+
+use crate::adapters::dtos::AuthorizationRequest;
+use crate::domain::models::{Acr, Client, GrantType};
+use crate::utils::pkce;
+
+use super::OAuthError;
+
+/// How strongly the authorization form signs users in, it only asks for
+/// their password.
+pub const LOGIN_FORM_ACR: Acr = Acr::Password;
+
+/// Query of `GET /oauth/authorize`, echoed back by the login form.
+/// Everything is optional so missing values can be reported properly.
+#[derive(Debug, Default, Deserialize)]
+pub struct AuthorizeParams {
+    pub response_type: Option<String>,
+    pub client_id: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    pub max_age: Option<String>,
+    pub acr_values: Option<String>,
+}
+
+/// Why an authorization request was refused.
+#[derive(Debug)]
+pub enum AuthorizeRejection {
+    /// The client or redirect URI can't be trusted, so the error is shown
+    /// to the user instead of redirecting.
+    Invalid(String),
+    /// Reported back to the client's redirect URI.
+    Redirect {
+        redirect_uri: String,
+        state: Option<String>,
+        error: OAuthError,
+    },
+}
+
+impl IntoResponse for AuthorizeRejection {
+    fn into_response(self) -> Response {
+        match self {
+            AuthorizeRejection::Invalid(message) => (
+                StatusCode::BAD_REQUEST,
+                Html(format!(
+                    "<!doctype html><title>Invalid request</title><p>{}</p>",
+                    escape_html(&message)
+                )),
+            )
+                .into_response(),
+            AuthorizeRejection::Redirect {
+                redirect_uri,
+                state,
+                error,
+            } => {
+                let mut params = vec![("error", error.code().to_string())];
+                if let Some(description) = error.description() {
+                    params.push(("error_description", description));
+                }
+                if let Some(state) = state {
+                    params.push(("state", state));
+                }
+                Redirect::to(&redirect_url(&redirect_uri, &params)).into_response()
+            }
+        }
+    }
+}
+
+impl AuthorizeParams {
+    /// Checks the request against the client it names (RFC 6749 section 4.1.1)
+    /// and requires PKCE with S256. Returns the client along with the request.
+    ///
+    /// `max_age` and `acr_values` follow OpenID Connect Core section 3.1.2.1.
+    /// Requests only the form can't meet, asking for a stronger acr than a
+    /// password, are refused.
+    pub fn validate(
+        &self,
+        client: Option<Client>,
+    ) -> Result<(Client, AuthorizationRequest), AuthorizeRejection> {
+        let client = client.ok_or_else(|| AuthorizeRejection::Invalid("Unknown client".into()))?;
+        let redirect_uri = self
+            .redirect_uri
+            .as_deref()
+            .filter(|uri| client.allows_redirect_uri(uri))
+            .ok_or_else(|| AuthorizeRejection::Invalid("Redirect URI is not registered".into()))?;
+
+        let reject = |error| AuthorizeRejection::Redirect {
+            redirect_uri: redirect_uri.to_string(),
+            state: self.state.clone(),
+            error,
+        };
+
+        if self.response_type.as_deref() != Some("code") {
+            return Err(reject(OAuthError::UnsupportedResponseType));
+        }
+        if !client.allows_grant_type(GrantType::AuthorizationCode) {
+            return Err(reject(OAuthError::UnauthorizedClient));
+        }
+        let scope = self.scope.clone().unwrap_or_default();
+        if !client.allows_scopes(&scope) {
+            return Err(reject(OAuthError::InvalidScope));
+        }
+        let code_challenge = self
+            .code_challenge
+            .as_deref()
+            .filter(|challenge| pkce::is_valid_challenge(challenge));
+        let Some(code_challenge) = code_challenge else {
+            return Err(reject(OAuthError::InvalidRequest(
+                "code_challenge is required".into(),
+            )));
+        };
+        if self.code_challenge_method.as_deref() != Some(pkce::S256) {
+            return Err(reject(OAuthError::InvalidRequest(
+                "code_challenge_method must be S256".into(),
+            )));
+        }
+
+        let max_age = match self.max_age.as_deref() {
+            Some(max_age) => Some(
+                max_age
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|max_age| *max_age >= 0)
+                    .ok_or_else(|| {
+                        reject(OAuthError::InvalidRequest(
+                            "max_age must be a number of seconds".into(),
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
+        // Any of the listed values will do, so the weakest one we know is required
+        let acr = self.acr_values.as_deref().and_then(|values| {
+            values
+                .split_whitespace()
+                .filter_map(|value| value.parse::<Acr>().ok())
+                .min()
+        });
+        if acr.is_some_and(|acr| acr > LOGIN_FORM_ACR) {
+            return Err(reject(OAuthError::UnmetAuthenticationRequirements));
+        }
+
+        let request = AuthorizationRequest {
+            client_id: client.client_id.clone(),
+            redirect_uri: redirect_uri.to_string(),
+            scope,
+            state: self.state.clone(),
+            nonce: self.nonce.clone(),
+            code_challenge: code_challenge.to_string(),
+            max_age,
+            acr,
+        };
+        Ok((client, request))
+    }
+}
+
+/// Sends the user back to the client with a fresh authorization code.
+pub fn code_redirect(request: &AuthorizationRequest, code: &str) -> Redirect {
+    let mut params = vec![("code", code.to_string())];
+    if let Some(state) = &request.state {
+        params.push(("state", state.clone()));
+    }
+
+    Redirect::to(&redirect_url(&request.redirect_uri, &params))
+}
+
+/// The sign-in form shown by `GET /oauth/authorize`. It posts the original
+/// request back along with the user's credentials.
+pub fn login_page(params: &AuthorizeParams, client: &Client, error: Option<&str>) -> Html<String> {
+    let hidden = [
+        ("response_type", &params.response_type),
+        ("client_id", &params.client_id),
+        ("redirect_uri", &params.redirect_uri),
+        ("scope", &params.scope),
+        ("state", &params.state),
+        ("nonce", &params.nonce),
+        ("code_challenge", &params.code_challenge),
+        ("code_challenge_method", &params.code_challenge_method),
+        ("max_age", &params.max_age),
+        ("acr_values", &params.acr_values),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| {
+        value.as_deref().map(|value| {
+            format!(
+                r#"<input type="hidden" name="{name}" value="{}">"#,
+                escape_html(value)
+            )
+        })
+    })
+    .collect::<String>();
+
+    let error = error
+        .map(|error| format!(r#"<p role="alert">{}</p>"#, escape_html(error)))
+        .unwrap_or_default();
+
+    Html(format!(
+        r#"<!doctype html>
+<html>
+<head><meta charset="utf-8"><title>Sign in to {client}</title></head>
+<body>
+<h1>Sign in to {client}</h1>
+{error}
+<form method="post" action="">
+{hidden}
+<label>Email <input type="email" name="email" required autofocus></label>
+<label>Password <input type="password" name="password" required></label>
+<button type="submit">Sign in</button>
+</form>
+</body>
+</html>"#,
+        client = escape_html(&client.name),
+    ))
+}
+
+// Appends query parameters to a redirect URI that may already have a query
+fn redirect_url(redirect_uri: &str, params: &[(&str, String)]) -> String {
+    let query = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish();
+    let separator = if redirect_uri.contains('?') { '&' } else { '?' };
+
+    format!("{redirect_uri}{separator}{query}")
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALLBACK: &str = "https://spa.example.com/callback";
+    const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    fn client() -> Client {
+        Client::new(
+            "spa".to_string(),
+            "SPA".to_string(),
+            None,
+            vec![CALLBACK.to_string()],
+        )
+    }
+
+    fn params() -> AuthorizeParams {
+        AuthorizeParams {
+            response_type: Some("code".to_string()),
+            client_id: Some("spa".to_string()),
+            redirect_uri: Some(CALLBACK.to_string()),
+            scope: Some("openid".to_string()),
+            state: Some("xyz".to_string()),
+            nonce: Some("n-0S6".to_string()),
+            code_challenge: Some(CHALLENGE.to_string()),
+            code_challenge_method: Some("S256".to_string()),
+            max_age: None,
+            acr_values: None,
+        }
+    }
+
+    fn redirected_error(rejection: AuthorizeRejection) -> &'static str {
+        match rejection {
+            AuthorizeRejection::Redirect { error, state, .. } => {
+                assert_eq!(state.as_deref(), Some("xyz"));
+                error.code()
+            }
+            AuthorizeRejection::Invalid(message) => panic!("not redirected: {message}"),
+        }
+    }
+
+    #[test]
+    fn test_valid_request() {
+        let (_, request) = params().validate(Some(client())).unwrap();
+
+        assert_eq!(request.redirect_uri, CALLBACK);
+        assert_eq!(request.code_challenge, CHALLENGE);
+        assert_eq!(request.nonce.as_deref(), Some("n-0S6"));
+    }
+
+    #[test]
+    fn test_unknown_client_or_redirect_uri_is_not_redirected() {
+        assert!(matches!(
+            params().validate(None),
+            Err(AuthorizeRejection::Invalid(_))
+        ));
+
+        let mut params = params();
+        params.redirect_uri = Some("https://attacker.example.com/callback".to_string());
+        assert!(matches!(
+            params.validate(Some(client())),
+            Err(AuthorizeRejection::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_pkce_s256_is_required() {
+        let mut without_challenge = params();
+        without_challenge.code_challenge = None;
+        let rejection = without_challenge.validate(Some(client())).unwrap_err();
+        assert_eq!(redirected_error(rejection), "invalid_request");
+
+        let mut plain = params();
+        plain.code_challenge_method = Some("plain".to_string());
+        let rejection = plain.validate(Some(client())).unwrap_err();
+        assert_eq!(redirected_error(rejection), "invalid_request");
+    }
+
+    #[test]
+    fn test_max_age_is_a_number_of_seconds() {
+        let mut params = params();
+        params.max_age = Some("300".to_string());
+        let (_, request) = params.validate(Some(client())).unwrap();
+        assert_eq!(request.max_age, Some(300));
+
+        params.max_age = Some("-1".to_string());
+        let rejection = params.validate(Some(client())).unwrap_err();
+        assert_eq!(redirected_error(rejection), "invalid_request");
+    }
+
+    #[test]
+    fn test_acr_values_the_form_cant_meet_are_refused() {
+        let mut params = params();
+        params.acr_values = Some("mfa password".to_string());
+        let (_, request) = params.validate(Some(client())).unwrap();
+        assert_eq!(request.acr, Some(Acr::Password));
+
+        // Unknown values are preferences we can ignore
+        params.acr_values = Some("urn:example:loa:2".to_string());
+        let (_, request) = params.validate(Some(client())).unwrap();
+        assert_eq!(request.acr, None);
+
+        params.acr_values = Some("mfa".to_string());
+        let rejection = params.validate(Some(client())).unwrap_err();
+        assert_eq!(
+            redirected_error(rejection),
+            "unmet_authentication_requirements"
+        );
+    }
+
+    #[test]
+    fn test_only_code_response_type_is_supported() {
+        let mut implicit = params();
+        implicit.response_type = Some("token".to_string());
+
+        let rejection = implicit.validate(Some(client())).unwrap_err();
+        assert_eq!(redirected_error(rejection), "unsupported_response_type");
+    }
+
+    #[test]
+    fn test_client_must_be_registered_for_the_grant_and_scopes() {
+        let mut client = client();
+        let rejection = params_with_scope("openid admin")
+            .validate(Some(client.clone()))
+            .unwrap_err();
+        assert_eq!(redirected_error(rejection), "invalid_scope");
+
+        client.grant_types = vec![GrantType::RefreshToken];
+        let rejection = params().validate(Some(client.clone())).unwrap_err();
+        assert_eq!(redirected_error(rejection), "unauthorized_client");
+    }
+
+    fn params_with_scope(scope: &str) -> AuthorizeParams {
+        AuthorizeParams {
+            scope: Some(scope.to_string()),
+            ..params()
+        }
+    }
+
+    #[test]
+    fn test_redirect_url_keeps_existing_query() {
+        let params = [("code", "a b".to_string()), ("state", "x&y".to_string())];
+
+        assert_eq!(
+            redirect_url(CALLBACK, &params),
+            "https://spa.example.com/callback?code=a+b&state=x%26y"
+        );
+        assert_eq!(
+            redirect_url("https://spa.example.com/cb?tenant=1", &params[..1]),
+            "https://spa.example.com/cb?tenant=1&code=a+b"
+        );
+    }
+
+    #[test]
+    fn test_login_page_escapes_request_values() {
+        let mut params = params();
+        params.state = Some(r#""><script>"#.to_string());
+
+        let Html(page) = login_page(&params, &client(), None);
+
+        assert!(page.contains(r#"value="&quot;&gt;&lt;script&gt;""#));
+        assert!(!page.contains("<script>"));
+    }
+}
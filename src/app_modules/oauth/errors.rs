@@ -0,0 +1,195 @@
+/* OAuth errors module */
+
+use axum::{
+    Json,
+    http::{StatusCode, header::WWW_AUTHENTICATE},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use tracing::error;
+
+use crate::app_modules::auth;
+use crate::domain::services::errors::Error as ServiceError;
+
+/// Error body defined by RFC 6749 section 5.2
+#[derive(Debug, Serialize)]
+pub struct OAuthErrorResponse {
+    pub error: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_description: Option<String>,
+}
+
+/// Errors returned by the OAuth endpoints, in the shape OAuth clients expect.
+#[derive(Debug, thiserror::Error)]
+pub enum OAuthError {
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Client authentication failed")]
+    InvalidClient,
+
+    #[error("Invalid authorization grant")]
+    InvalidGrant,
+
+    #[error("Client is not allowed to use this grant")]
+    UnauthorizedClient,
+
+    #[error("Invalid scope")]
+    InvalidScope,
+
+    #[error("Invalid target")]
+    InvalidTarget,
+
+    #[error("Unsupported grant type")]
+    UnsupportedGrantType,
+
+    #[error("Unsupported response type")]
+    UnsupportedResponseType,
+
+    #[error("Access denied")]
+    AccessDenied,
+
+    #[error("Authentication requirements can't be met")]
+    UnmetAuthenticationRequirements,
+
+    #[error("Authorization pending")]
+    AuthorizationPending,
+
+    #[error("Polling too fast")]
+    SlowDown,
+
+    #[error("Device code expired")]
+    ExpiredToken,
+
+    #[error("Internal server error")]
+    ServerError,
+}
+
+impl OAuthError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            OAuthError::InvalidRequest(_) => "invalid_request",
+            OAuthError::InvalidClient => "invalid_client",
+            OAuthError::InvalidGrant => "invalid_grant",
+            OAuthError::UnauthorizedClient => "unauthorized_client",
+            OAuthError::InvalidScope => "invalid_scope",
+            OAuthError::InvalidTarget => "invalid_target",
+            OAuthError::UnsupportedGrantType => "unsupported_grant_type",
+            OAuthError::UnsupportedResponseType => "unsupported_response_type",
+            OAuthError::AccessDenied => "access_denied",
+            OAuthError::UnmetAuthenticationRequirements => "unmet_authentication_requirements",
+            OAuthError::AuthorizationPending => "authorization_pending",
+            OAuthError::SlowDown => "slow_down",
+            OAuthError::ExpiredToken => "expired_token",
+            OAuthError::ServerError => "server_error",
+        }
+    }
+
+    /// The description sent along the error code, if any.
+    pub fn description(&self) -> Option<String> {
+        match self {
+            OAuthError::InvalidRequest(description) => Some(description.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for OAuthError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            OAuthError::InvalidClient => StatusCode::UNAUTHORIZED,
+            OAuthError::ServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        let body = Json(OAuthErrorResponse {
+            error: self.code(),
+            error_description: self.description(),
+        });
+
+        // Clients that tried HTTP Basic must be challenged again
+        if let OAuthError::InvalidClient = self {
+            return (
+                status,
+                [(WWW_AUTHENTICATE, "Basic realm=\"gandalf\"")],
+                body,
+            )
+                .into_response();
+        }
+        (status, body).into_response()
+    }
+}
+
+impl From<ServiceError> for OAuthError {
+    fn from(error: ServiceError) -> Self {
+        match error {
+            ServiceError::InvalidClient => OAuthError::InvalidClient,
+            err => {
+                error!("{}", err);
+                OAuthError::ServerError
+            }
+        }
+    }
+}
+
+impl From<auth::Error> for OAuthError {
+    fn from(error: auth::Error) -> Self {
+        match error {
+            auth::Error::InternalError => OAuthError::ServerError,
+            auth::Error::InvalidGrant
+            | auth::Error::InvalidToken
+            | auth::Error::TokenExpired
+            | auth::Error::RefreshTokenReused => OAuthError::InvalidGrant,
+            auth::Error::AuthorizationPending => OAuthError::AuthorizationPending,
+            auth::Error::SlowDown => OAuthError::SlowDown,
+            auth::Error::DeviceCodeExpired => OAuthError::ExpiredToken,
+            auth::Error::AccessDenied | auth::Error::SessionLimitReached => {
+                OAuthError::AccessDenied
+            }
+            auth::Error::InvalidScope => OAuthError::InvalidScope,
+            auth::Error::InvalidTarget => OAuthError::InvalidTarget,
+            auth::Error::ReauthenticationRequired { .. } => {
+                OAuthError::UnmetAuthenticationRequirements
+            }
+            auth::Error::UserServiceError(err) => err.into(),
+            err => OAuthError::InvalidRequest(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+    use serde_json::{Value, json};
+
+    async fn body_of(response: Response) -> Value {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_invalid_client_challenges_basic_auth() {
+        let response = OAuthError::InvalidClient.into_response();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            "Basic realm=\"gandalf\""
+        );
+        assert_eq!(
+            body_of(response).await,
+            json!({ "error": "invalid_client" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_has_description() {
+        let response = OAuthError::InvalidRequest("token is required".to_string()).into_response();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body_of(response).await,
+            json!({ "error": "invalid_request", "error_description": "token is required" })
+        );
+    }
+}
@@ -0,0 +1,205 @@
+/* OAuth request extractors */
+
+use axum::http::{HeaderMap, header::AUTHORIZATION};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::Deserialize;
+
+use crate::app_modules::AppState;
+use crate::config::get_config;
+use crate::domain::models::Client;
+use crate::utils::client_assertion;
+
+use super::OAuthError;
+
+/// Client credentials sent in the body of a form-encoded OAuth request.
+#[derive(Debug, Default, Deserialize)]
+pub struct ClientAuthentication {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub client_assertion_type: Option<String>,
+    pub client_assertion: Option<String>,
+}
+
+/// Resolves the client of a form-encoded OAuth request.
+///
+/// Clients authenticate with HTTP Basic credentials, a JWT client assertion
+/// (`private_key_jwt`) or `client_id`/`client_secret` form fields
+/// (`client_secret_post`), and only one of them. Public clients send only
+/// their `client_id`.
+pub async fn authenticate_client(
+    state: &AppState,
+    headers: &HeaderMap,
+    credentials: &ClientAuthentication,
+) -> Result<Client, OAuthError> {
+    let basic = basic_credentials(headers);
+    check_single_method(basic.is_some(), credentials)?;
+
+    if let Some((client_id, client_secret)) = basic {
+        return Ok(state
+            .client_service
+            .authenticate(&client_id, Some(&client_secret))
+            .await?);
+    }
+
+    if let Some(assertion) = credentials.client_assertion.as_deref() {
+        if credentials.client_assertion_type.as_deref() != Some(client_assertion::JWT_BEARER) {
+            return Err(OAuthError::InvalidClient);
+        }
+        let client = state
+            .client_service
+            .authenticate_assertion(
+                assertion,
+                &assertion_audiences(),
+                get_config().jwt_leeway.into(),
+            )
+            .await?;
+
+        // The assertion speaks for the client it names
+        if credentials
+            .client_id
+            .as_deref()
+            .is_some_and(|client_id| client_id != client.client_id)
+        {
+            return Err(OAuthError::InvalidClient);
+        }
+        return Ok(client);
+    }
+
+    let client_id = credentials
+        .client_id
+        .as_deref()
+        .ok_or(OAuthError::InvalidClient)?;
+    Ok(state
+        .client_service
+        .authenticate(client_id, credentials.client_secret.as_deref())
+        .await?)
+}
+
+// Clients must not use more than one authentication method (RFC 6749 section 2.3)
+fn check_single_method(basic: bool, credentials: &ClientAuthentication) -> Result<(), OAuthError> {
+    let methods = [
+        basic,
+        credentials.client_assertion.is_some(),
+        credentials.client_secret.is_some(),
+    ];
+    if methods.into_iter().filter(|used| *used).count() > 1 {
+        return Err(OAuthError::InvalidRequest(
+            "Only one client authentication method may be used".into(),
+        ));
+    }
+    Ok(())
+}
+
+// Assertions must be addressed to us, as the issuer or the token endpoint
+fn assertion_audiences() -> Vec<String> {
+    let config = get_config();
+
+    vec![
+        config.jwt_issuer.clone(),
+        format!("{}/api/v1/oauth/token", config.public_url),
+    ]
+}
+
+/// Reads the credentials from an `Authorization: Basic <base64(id:secret)>` header.
+///
+/// Client ids and secrets issued by gandalf are URL safe, so the form encoding
+/// RFC 6749 applies before base64 leaves them unchanged.
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .strip_prefix("Basic ")
+                .or_else(|| value.strip_prefix("basic "))
+        })?;
+
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (client_id, client_secret) = decoded.split_once(':')?;
+    if client_id.is_empty() {
+        return None;
+    }
+
+    Some((client_id.to_string(), client_secret.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{Request, request::Parts};
+
+    fn parts_with_auth(value: &str) -> Parts {
+        Request::builder()
+            .header(AUTHORIZATION, value)
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    }
+
+    fn parts_with_auth_headers(value: &str) -> HeaderMap {
+        parts_with_auth(value).headers
+    }
+
+    #[test]
+    fn test_basic_credentials_are_extracted() {
+        let encoded = STANDARD.encode("client:s3cr3t:with-colon");
+        let parts = parts_with_auth(&format!("Basic {encoded}"));
+
+        assert_eq!(
+            basic_credentials(&parts.headers),
+            Some(("client".to_string(), "s3cr3t:with-colon".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_non_basic_credentials_are_ignored() {
+        assert_eq!(
+            basic_credentials(&parts_with_auth_headers("Bearer abc")),
+            None
+        );
+        assert_eq!(
+            basic_credentials(&parts_with_auth_headers("Basic not-base64!")),
+            None
+        );
+
+        let no_id = STANDARD.encode(":secret");
+        assert_eq!(
+            basic_credentials(&parts_with_auth_headers(&format!("Basic {no_id}"))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_only_one_authentication_method_is_accepted() {
+        let post = ClientAuthentication {
+            client_id: Some("client".to_string()),
+            client_secret: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let assertion = ClientAuthentication {
+            client_assertion_type: Some(client_assertion::JWT_BEARER.to_string()),
+            client_assertion: Some("a.b.c".to_string()),
+            ..Default::default()
+        };
+        let public = ClientAuthentication {
+            client_id: Some("client".to_string()),
+            ..Default::default()
+        };
+
+        assert!(check_single_method(false, &post).is_ok());
+        assert!(check_single_method(false, &assertion).is_ok());
+        assert!(check_single_method(true, &public).is_ok());
+
+        let mixed = ClientAuthentication {
+            client_secret: Some("secret".to_string()),
+            ..assertion
+        };
+        for (basic, credentials) in [(true, &post), (false, &mixed)] {
+            assert!(matches!(
+                check_single_method(basic, credentials),
+                Err(OAuthError::InvalidRequest(_))
+            ));
+        }
+    }
+}
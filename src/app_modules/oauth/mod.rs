@@ -0,0 +1,8 @@
+/* OAuth 2.0 module */
+
+pub mod authorize;
+pub mod errors;
+pub mod extractors;
+
+pub use errors::OAuthError;
+pub use extractors::{ClientAuthentication, authenticate_client};
@@ -0,0 +1,106 @@
+/* Module for /.well-known discovery endpoints */
+
+use axum::{Json, extract::State};
+use serde::Serialize;
+
+use crate::app_modules::AppState;
+use crate::app_modules::oauth::authorize::LOGIN_FORM_ACR;
+use crate::config::get_config;
+use crate::utils::jwt_keys::JwkSet;
+use crate::utils::{client_assertion, pkce};
+
+/// OpenID Provider metadata (OpenID Connect Discovery 1.0, section 3).
+#[derive(Debug, Serialize)]
+pub struct OpenIdConfiguration {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_authorization_endpoint: Option<String>,
+    pub jwks_uri: String,
+    pub userinfo_endpoint: String,
+    pub introspection_endpoint: String,
+    pub revocation_endpoint: String,
+    pub response_types_supported: Vec<&'static str>,
+    pub grant_types_supported: Vec<&'static str>,
+    pub code_challenge_methods_supported: Vec<&'static str>,
+    pub subject_types_supported: Vec<&'static str>,
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    pub scopes_supported: Vec<&'static str>,
+    pub claims_supported: Vec<&'static str>,
+    pub acr_values_supported: Vec<String>,
+    pub token_endpoint_auth_methods_supported: Vec<&'static str>,
+    pub token_endpoint_auth_signing_alg_values_supported: Vec<String>,
+}
+
+/// Publishes the public signing keys so other services can verify our tokens.
+pub async fn jwks(State(state): State<AppState>) -> Json<JwkSet> {
+    Json(state.jwt_keys.jwks())
+}
+
+/// Lets OpenID Connect clients discover our endpoints and capabilities.
+pub async fn openid_configuration(State(state): State<AppState>) -> Json<OpenIdConfiguration> {
+    let config = get_config();
+    let api_url = format!("{}/api/v1", config.public_url);
+
+    Json(OpenIdConfiguration {
+        issuer: config.jwt_issuer.clone(),
+        authorization_endpoint: format!("{api_url}/oauth/authorize"),
+        token_endpoint: format!("{api_url}/oauth/token"),
+        device_authorization_endpoint: config
+            .device_verification_uri
+            .as_ref()
+            .map(|_| format!("{api_url}/oauth/device_authorization")),
+        jwks_uri: format!("{}/.well-known/jwks.json", config.public_url),
+        userinfo_endpoint: format!("{api_url}/userinfo"),
+        introspection_endpoint: format!("{api_url}/oauth/introspect"),
+        revocation_endpoint: format!("{api_url}/oauth/revoke"),
+        response_types_supported: vec!["code"],
+        grant_types_supported: [
+            Some("authorization_code"),
+            Some("refresh_token"),
+            Some("client_credentials"),
+            config
+                .device_verification_uri
+                .as_ref()
+                .map(|_| "urn:ietf:params:oauth:grant-type:device_code"),
+            Some("urn:ietf:params:oauth:grant-type:token-exchange"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect(),
+        code_challenge_methods_supported: vec![pkce::S256],
+        subject_types_supported: vec!["public"],
+        id_token_signing_alg_values_supported: vec![format!(
+            "{:?}",
+            state.jwt_keys.signing_key().algorithm
+        )],
+        scopes_supported: vec!["openid", "email"],
+        claims_supported: vec![
+            "iss",
+            "sub",
+            "aud",
+            "exp",
+            "iat",
+            "auth_time",
+            "acr",
+            "nonce",
+            "sid",
+            "email",
+            "email_verified",
+            "preferred_username",
+        ],
+        // Nothing signs users in with a second factor yet
+        acr_values_supported: vec![LOGIN_FORM_ACR.to_string()],
+        token_endpoint_auth_methods_supported: vec![
+            "client_secret_basic",
+            "client_secret_post",
+            "private_key_jwt",
+            "none",
+        ],
+        token_endpoint_auth_signing_alg_values_supported: client_assertion::ALGORITHMS
+            .iter()
+            .map(|algorithm| format!("{algorithm:?}"))
+            .collect(),
+    })
+}
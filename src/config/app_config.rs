@@ -3,41 +3,97 @@ This module contains config settings for authentication.
 */
 
 use super::defaults;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Duration;
+use hkdf::Hkdf;
+use sha2::Sha256;
 use std::env;
+use std::fmt;
 use std::sync::OnceLock;
+use tracing::warn;
+
+// HKDF info of the refresh token secret derived from JWT_SECRET
+const REFRESH_TOKEN_SECRET_LABEL: &str = "gandalf refresh token secret";
 
 #[derive(Clone, Debug)]
 pub struct AppConfig {
     pub app_name: String,
     pub app_host: String,
     pub app_port: u16,
+    pub public_url: String, // where clients reach gandalf, without trailing slash
     pub app_env: String,
     pub jwt_secret: String,
-    pub jwt_expiration: u8,               // minutes
+    pub refresh_token_secret: String,
+    pub jwt_expiration: u8,    // minutes
+    pub jwt_algorithm: String, // HS256, RS256, ES256 or EdDSA
+    pub jwt_private_key_path: Option<String>,
+    pub jwt_issuer: String,
+    pub jwt_audience: String,
+    pub jwt_leeway: u16, // seconds
+    pub verify_session_on_access: bool,
+    pub key_rotation_interval: u16, // days
+    pub key_encryption_key: Option<String>,
     pub refresh_token_expiration: u8,     // hours
     pub access_token_expiration: u8,      // minutes
-    pub password_reset_expiration: u8,    // minutes
-    pub verification_code_expiration: u8, // minutes
+    pub max_access_token_expiration: u16, // minutes, caps client lifetimes
+    pub session_idle_timeout: u16,        // minutes
+    pub session_absolute_lifetime: u16,   // hours
+    pub reauthentication_max_age: u16,    // minutes
+    pub max_sessions_per_user: u16,       // 0 is unlimited, users may override it
+    pub session_limit_policy: SessionLimitPolicy,
+    pub authorization_code_expiration: u16,      // seconds
+    pub device_code_expiration: u16,             // seconds
+    pub device_code_interval: u8,                // seconds
+    pub device_verification_uri: Option<String>, // user code entry page, unset disables the grant
+    pub password_reset_expiration: u8,           // minutes
+    pub verification_code_expiration: u8,        // minutes
     pub max_failed_login_attempts: u8,
     pub account_lockout_duration: u8, // minutes
 }
 
 impl AppConfig {
     fn from_env() -> Self {
+        let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
+        let app_host = get_env_or_default("APP_HOST", defaults::APP_HOST.to_string());
+        let app_port = get_env_or_default("APP_PORT", defaults::APP_PORT);
+        let public_url = get_env_or_default("PUBLIC_URL", format!("http://{app_host}:{app_port}"))
+            .trim_end_matches('/')
+            .to_string();
+        let jwt_issuer = get_env_or_default("JWT_ISSUER", public_url.clone());
+
         Self {
             // Server settings
             app_name: get_env_or_default("APP_NAME", defaults::APP_NAME.to_string()),
-            app_host: get_env_or_default("APP_HOST", defaults::APP_HOST.to_string()),
-            app_port: get_env_or_default("APP_PORT", defaults::APP_PORT),
+            app_host,
+            app_port,
+            public_url,
 
             // Environment settings
             app_env: get_env_or_default("APP_ENV", defaults::APP_ENV.to_string()),
 
             // JWT settings
-            jwt_secret: env::var("JWT_SECRET").expect("JWT_SECRET must be set"),
+            refresh_token_secret: env::var("REFRESH_TOKEN_SECRET").unwrap_or_else(|_| {
+                warn!("REFRESH_TOKEN_SECRET is not set, deriving it from JWT_SECRET");
+                derive_secret(&jwt_secret, REFRESH_TOKEN_SECRET_LABEL)
+            }),
+            jwt_secret,
             jwt_expiration: get_env_or_default("JWT_EXPIRATION", defaults::JWT_EXPIRATION),
+            jwt_algorithm: get_env_or_default("JWT_ALGORITHM", defaults::JWT_ALGORITHM.to_string()),
+            jwt_private_key_path: env::var("JWT_PRIVATE_KEY_PATH").ok(),
+            jwt_issuer,
+            jwt_audience: get_env_or_default("JWT_AUDIENCE", defaults::JWT_AUDIENCE.to_string()),
+            jwt_leeway: get_env_or_default("JWT_LEEWAY", defaults::JWT_LEEWAY),
+            verify_session_on_access: get_env_or_default(
+                "VERIFY_SESSION_ON_ACCESS",
+                defaults::VERIFY_SESSION_ON_ACCESS,
+            ),
 
             // Authentication settings
+            key_rotation_interval: get_env_or_default(
+                "KEY_ROTATION_INTERVAL",
+                defaults::KEY_ROTATION_INTERVAL,
+            ),
+            key_encryption_key: env::var("KEY_ENCRYPTION_KEY").ok(),
             refresh_token_expiration: get_env_or_default(
                 "REFRESH_TOKEN_EXPIRATION",
                 defaults::REFRESH_TOKEN_EXPIRATION,
@@ -46,6 +102,43 @@ impl AppConfig {
                 "ACCESS_TOKEN_EXPIRATION",
                 defaults::ACCESS_TOKEN_EXPIRATION,
             ),
+            max_access_token_expiration: get_env_or_default(
+                "MAX_ACCESS_TOKEN_EXPIRATION",
+                defaults::MAX_ACCESS_TOKEN_EXPIRATION,
+            ),
+            session_idle_timeout: get_env_or_default(
+                "SESSION_IDLE_TIMEOUT",
+                defaults::SESSION_IDLE_TIMEOUT,
+            ),
+            session_absolute_lifetime: get_env_or_default(
+                "SESSION_ABSOLUTE_LIFETIME",
+                defaults::SESSION_ABSOLUTE_LIFETIME,
+            ),
+            reauthentication_max_age: get_env_or_default(
+                "REAUTHENTICATION_MAX_AGE",
+                defaults::REAUTHENTICATION_MAX_AGE,
+            ),
+            max_sessions_per_user: get_env_or_default(
+                "MAX_SESSIONS_PER_USER",
+                defaults::MAX_SESSIONS_PER_USER,
+            ),
+            session_limit_policy: get_env_or_default(
+                "SESSION_LIMIT_POLICY",
+                defaults::SESSION_LIMIT_POLICY.parse().unwrap(),
+            ),
+            authorization_code_expiration: get_env_or_default(
+                "AUTHORIZATION_CODE_EXPIRATION",
+                defaults::AUTHORIZATION_CODE_EXPIRATION,
+            ),
+            device_code_expiration: get_env_or_default(
+                "DEVICE_CODE_EXPIRATION",
+                defaults::DEVICE_CODE_EXPIRATION,
+            ),
+            device_code_interval: get_env_or_default(
+                "DEVICE_CODE_INTERVAL",
+                defaults::DEVICE_CODE_INTERVAL,
+            ),
+            device_verification_uri: env::var("DEVICE_VERIFICATION_URI").ok(),
             password_reset_expiration: get_env_or_default(
                 "PASSWORD_RESET_EXPIRATION",
                 defaults::PASSWORD_RESET_EXPIRATION,
@@ -66,6 +159,69 @@ impl AppConfig {
     }
 }
 
+impl AppConfig {
+    /// Default lifetime of access tokens, clients may override it.
+    pub fn access_token_lifetime(&self) -> Duration {
+        Duration::minutes(self.access_token_expiration.into())
+    }
+
+    /// Longest lifetime of any access token, clients can't override it.
+    pub fn max_access_token_lifetime(&self) -> Duration {
+        Duration::minutes(self.max_access_token_expiration.into()).max(self.access_token_lifetime())
+    }
+
+    /// Default lifetime of a session and its refresh tokens, clients may override it.
+    pub fn refresh_token_lifetime(&self) -> Duration {
+        Duration::hours(self.refresh_token_expiration.into())
+    }
+
+    /// Sessions not used for this long end, whatever their expiry.
+    pub fn session_idle_timeout(&self) -> Duration {
+        Duration::minutes(self.session_idle_timeout.into())
+    }
+
+    /// Sensitive operations need a sign in at most this old.
+    pub fn reauthentication_max_age(&self) -> Duration {
+        Duration::minutes(self.reauthentication_max_age.into())
+    }
+
+    /// Sessions end this long after sign in, whatever their expiry or use.
+    pub fn session_absolute_lifetime(&self) -> Duration {
+        Duration::hours(self.session_absolute_lifetime.into())
+    }
+}
+
+/// What happens to a login that would exceed a user's session limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionLimitPolicy {
+    /// The login fails until the user signs out somewhere.
+    Reject,
+    /// The least recently active sessions are revoked to make room.
+    EvictOldest,
+}
+
+impl std::str::FromStr for SessionLimitPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(SessionLimitPolicy::Reject),
+            "evict_oldest" => Ok(SessionLimitPolicy::EvictOldest),
+            _ => Err(format!("Invalid session limit policy: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for SessionLimitPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let policy_str = match self {
+            SessionLimitPolicy::Reject => "reject",
+            SessionLimitPolicy::EvictOldest => "evict_oldest",
+        };
+        write!(f, "{}", policy_str)
+    }
+}
+
 fn get_env_or_default<T>(key: &str, default: T) -> T
 where
     T: std::str::FromStr + ToString,
@@ -77,6 +233,16 @@ where
         .unwrap_or_else(|_| panic!("{key} must be a valid {}", std::any::type_name::<T>()))
 }
 
+// Derives a secret for another purpose with HKDF-SHA256, so no two
+// purposes ever share a key
+fn derive_secret(secret: &str, label: &str) -> String {
+    let mut derived = [0u8; 32];
+    Hkdf::<Sha256>::new(None, secret.as_bytes())
+        .expand(label.as_bytes(), &mut derived)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    URL_SAFE_NO_PAD.encode(derived)
+}
+
 // Global singleton config
 static CONFIG_INSTANCE: OnceLock<AppConfig> = OnceLock::new();
 
@@ -112,12 +278,59 @@ mod tests {
         assert_eq!(config.app_port, 3000);
         assert_eq!(config.app_env, "development");
         assert_eq!(config.jwt_secret, "supersecret");
+        assert_eq!(
+            config.refresh_token_secret,
+            derive_secret("supersecret", REFRESH_TOKEN_SECRET_LABEL)
+        );
+        assert_ne!(config.refresh_token_secret, config.jwt_secret);
         assert_eq!(config.jwt_expiration, 15);
+        assert_eq!(config.jwt_algorithm, "HS256");
+        assert_eq!(config.jwt_private_key_path, None);
+        assert_eq!(config.public_url, "http://localhost:3000");
+        assert_eq!(config.jwt_issuer, "http://localhost:3000");
+        assert_eq!(config.jwt_audience, "app.teta");
+        assert_eq!(config.jwt_leeway, 30);
+        assert!(config.verify_session_on_access);
+        assert_eq!(config.key_rotation_interval, 30);
+        assert_eq!(config.key_encryption_key, None);
         assert_eq!(config.refresh_token_expiration, 30);
         assert_eq!(config.access_token_expiration, 15);
+        assert_eq!(config.max_access_token_expiration, 60);
+        assert_eq!(config.session_idle_timeout, 1440);
+        assert_eq!(config.session_absolute_lifetime, 720);
+        assert_eq!(config.reauthentication_max_age, 15);
+        assert_eq!(config.max_sessions_per_user, 0);
+        assert_eq!(config.session_limit_policy, SessionLimitPolicy::EvictOldest);
+        assert_eq!(config.authorization_code_expiration, 60);
+        assert_eq!(config.device_code_expiration, 600);
+        assert_eq!(config.device_code_interval, 5);
+        assert_eq!(config.device_verification_uri, None);
         assert_eq!(config.password_reset_expiration, 24);
         assert_eq!(config.verification_code_expiration, 24);
         assert_eq!(config.max_failed_login_attempts, 5);
         assert_eq!(config.account_lockout_duration, 30);
     }
+
+    #[test]
+    #[serial]
+    fn test_lifetimes_use_documented_units() {
+        set_env("JWT_SECRET", "supersecret");
+        let mut config = AppConfig::from_env();
+        config.access_token_expiration = 15;
+        config.refresh_token_expiration = 30;
+        config.session_idle_timeout = 90;
+        config.session_absolute_lifetime = 48;
+
+        assert_eq!(config.access_token_lifetime(), Duration::minutes(15));
+        config.max_access_token_expiration = 60;
+        assert_eq!(config.max_access_token_lifetime(), Duration::hours(1));
+        // The default lifetime is never capped
+        config.max_access_token_expiration = 5;
+        assert_eq!(config.max_access_token_lifetime(), Duration::minutes(15));
+        assert_eq!(config.refresh_token_lifetime(), Duration::hours(30));
+        assert_eq!(config.session_idle_timeout(), Duration::minutes(90));
+        assert_eq!(config.session_absolute_lifetime(), Duration::days(2));
+        config.reauthentication_max_age = 15;
+        assert_eq!(config.reauthentication_max_age(), Duration::minutes(15));
+    }
 }
@@ -12,8 +12,22 @@ pub const APP_PORT: u16 = 3000;
 
 // Auth defaults
 pub const JWT_EXPIRATION: u8 = 60; // in minutes
-pub const REFRESH_TOKEN_EXPIRATION: u8 = 30;
+pub const JWT_ALGORITHM: &str = "HS256";
+pub const JWT_AUDIENCE: &str = "app.teta";
+pub const JWT_LEEWAY: u16 = 30; // in seconds
+pub const VERIFY_SESSION_ON_ACCESS: bool = true;
+pub const KEY_ROTATION_INTERVAL: u16 = 30; // in days
+pub const REFRESH_TOKEN_EXPIRATION: u8 = 30; // in hours
 pub const ACCESS_TOKEN_EXPIRATION: u8 = 15; // in minutes
+pub const MAX_ACCESS_TOKEN_EXPIRATION: u16 = 60; // in minutes
+pub const SESSION_IDLE_TIMEOUT: u16 = 1440; // in minutes
+pub const SESSION_ABSOLUTE_LIFETIME: u16 = 720; // in hours
+pub const REAUTHENTICATION_MAX_AGE: u16 = 15; // in minutes
+pub const MAX_SESSIONS_PER_USER: u16 = 0; // 0 is unlimited
+pub const SESSION_LIMIT_POLICY: &str = "evict_oldest";
+pub const AUTHORIZATION_CODE_EXPIRATION: u16 = 60; // in seconds
+pub const DEVICE_CODE_EXPIRATION: u16 = 600; // in seconds
+pub const DEVICE_CODE_INTERVAL: u8 = 5; // in seconds
 pub const PASSWORD_RESET_EXPIRATION: u8 = 24; // in hours
 pub const VERIFICATION_CODE_EXPIRATION: u8 = 24; // in hours
 pub const MAX_FAILED_LOGIN_ATTEMPTS: u8 = 5;
@@ -5,72 +5,170 @@ This module holds the model for supported auth providers
 use std::fmt;
 use std::net::IpAddr;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use jsonwebtoken::{Algorithm, Validation, decode, encode};
+
+use crate::utils::jwt_keys::JwtKey;
 use std::collections::HashMap;
 
-type ResourceAccess = HashMap<String, HashMap<String, Vec<String>>>;
+/// Permissions per resource server, then per resource.
+pub type ResourceAccess = HashMap<String, HashMap<String, Vec<String>>>;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JwtClaims {
-    pub sub: String, // User ID
+    pub sub: String, // User ID, or client ID for client credentials
     pub scope: String,
-    pub sid: Uuid,                       // Session ID
-    pub iss: String,                     // Issuer (auth server)
-    pub aud: String,                     // Audience (client app)
-    pub exp: i64,                        // Expiry time
-    pub iat: i64,                        // Issued at
-    pub jti: String,                     // Unique JWT ID (prevents replay attacks)
-    pub nbf: i64,                        // Not before (optional)
-    pub auth_time: i64,                  // Last authentication time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>, // Session ID, None without a user
+    pub iss: String,    // Issuer (auth server)
+    pub aud: String,    // Audience (client app)
+    pub azp: String,    // Authorized party (requesting client)
+    pub exp: i64,       // Expiry time
+    pub iat: i64,       // Issued at
+    pub jti: String,    // Unique JWT ID (prevents replay attacks)
+    pub nbf: i64,       // Not before (optional)
+    pub auth_time: i64, // Last authentication time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acr: Option<Acr>, // How the user authenticated, None without a user
     pub resource_access: ResourceAccess, // Resource access permissions
-    pub token_type: String,              // "access" or "refresh"
+    pub token_type: String, // "access" or "refresh"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>, // Who acts for `sub`, on delegated tokens
+}
+
+/// The party a delegated token was issued to act for the subject (RFC 8693
+/// section 4.1). Actors delegating again nest the previous actor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Actor {
+    pub sub: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Box<Actor>>,
 }
 
 impl JwtClaims {
-    pub fn to_jwt(&self, secret: &str) -> String {
-        let token = encode(
-            &Header::new(Algorithm::HS256),
-            &self,
-            &EncodingKey::from_secret(secret.as_bytes()),
-        )
-        .expect("Jwt Generation encoding should not fail");
+    pub fn to_jwt(&self, key: &JwtKey) -> String {
+        encode(&key.header(), &self, key.encoding_key())
+            .expect("Jwt Generation encoding should not fail")
+    }
+
+    /// Validation rules for access tokens: signature, `exp`/`nbf` with `leeway`
+    /// seconds of clock skew, the expected `iss` and, when given, `aud`.
+    pub fn validation(
+        issuer: &str,
+        audience: Option<&str>,
+        leeway: u64,
+        algorithm: Algorithm,
+    ) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        validation.leeway = leeway;
+        validation.validate_nbf = true;
+        validation.set_issuer(&[issuer]);
+        match audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud", "sub"]);
+        validation
+    }
+
+    /// Whether the token is meant for our own endpoints: addressed to the
+    /// first-party `audience`, or to the client it was issued to, which calls
+    /// us on behalf of its user or itself. Tokens exchanged for another
+    /// audience are meant for that one.
+    pub fn is_for_issuer(&self, audience: &str) -> bool {
+        self.aud == audience || self.aud == self.azp
+    }
 
-        token
+    /// Whether the token was issued to our own app under the first-party
+    /// `audience`, rather than to a client acting for its user or itself.
+    pub fn is_first_party(&self, audience: &str) -> bool {
+        self.aud == audience && self.azp == audience
+    }
+
+    /// Whether the user authenticated within `max_age` before `now`, with
+    /// `acr` or a stronger method. Tokens without a user never qualify.
+    pub fn meets_authentication(
+        &self,
+        max_age: Option<Duration>,
+        acr: Option<Acr>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.authentication()
+            .is_some_and(|authentication| authentication.meets(max_age, acr, now))
+    }
+
+    /// How the user authenticated, for tokens issued to a user.
+    pub fn authentication(&self) -> Option<Authentication> {
+        Some(Authentication {
+            acr: self.acr?,
+            auth_time: DateTime::from_timestamp(self.auth_time, 0)?,
+        })
+    }
+
+    /// Decodes an access token, checking it against `validation`.
+    pub fn from_jwt(
+        token: &str,
+        key: &JwtKey,
+        validation: &Validation,
+    ) -> jsonwebtoken::errors::Result<Self> {
+        let data = decode::<Self>(token, key.decoding_key(), validation)?;
+
+        Ok(data.claims)
     }
 }
 
-// Refresh token claims structure
+/// Claims of an OpenID Connect ID token.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct RefreshTokenClaims {
-    pub sub: String,        // user_id
-    pub session_id: String, // links to session
-    pub exp: i64,           // expiration
-    pub iat: i64,           // issued at
-    pub token_type: String, // "refresh"
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: String,
+    pub exp: i64,
+    pub iat: i64,
+    pub auth_time: i64,
+    pub acr: Acr,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    pub sid: Uuid,
+    pub email: String,
+    pub email_verified: bool,
 }
-impl RefreshTokenClaims {
-    pub fn to_jwt(&self, secret: &str) -> String {
-        let token = encode(
-            &Header::new(Algorithm::HS256),
-            &self,
-            &EncodingKey::from_secret(secret.as_bytes()),
-        )
-        .expect("Jwt Generation encoding should not fail");
 
-        token
+impl IdTokenClaims {
+    pub fn to_jwt(&self, key: &JwtKey) -> String {
+        encode(&key.header(), &self, key.encoding_key())
+            .expect("Jwt Generation encoding should not fail")
     }
 }
 
+/// What an active token says about itself, as reported by introspection (RFC 7662).
+#[derive(Debug)]
+pub struct TokenIntrospection {
+    pub sub: String,
+    pub scope: String,
+    pub sid: Option<Uuid>,
+    pub iss: String,
+    pub aud: String,
+    pub client_id: String,
+    pub exp: i64,
+    pub iat: i64,
+    pub jti: Option<String>,
+    pub act: Option<Actor>,
+    pub auth_time: Option<i64>, // None for tokens issued to a client
+    pub acr: Option<Acr>,
+    pub token_type: TokenType,
+}
+
 // Session structure for storing user sessions
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Session {
     pub id: Uuid,
     pub user_id: Uuid,
     pub refresh_token_hash: String,
+    pub refresh_token_generation: i32,
     pub device_identifier: Option<String>,
     pub device_name: Option<String>,
     pub device_type: Option<String>,
@@ -82,10 +180,92 @@ pub struct Session {
     pub is_revoked: bool,
     pub revoked_reason: Option<String>,
     pub revoked_at: Option<DateTime<Utc>>,
+    pub client_id: Option<String>, // None for first-party logins
+    pub acr: Acr,
+    pub auth_time: DateTime<Utc>,
+    pub scope: Option<String>, // granted to the client, None for first-party logins
+}
+
+impl Session {
+    /// When the session ends: at `expires_at`, after `idle_timeout` without
+    /// use, or `absolute_lifetime` after sign in, whichever comes first.
+    pub fn ends_at(&self, idle_timeout: Duration, absolute_lifetime: Duration) -> DateTime<Utc> {
+        self.expires_at
+            .min(self.last_active_at + idle_timeout)
+            .min(self.created_at + absolute_lifetime)
+    }
+
+    /// The scope its tokens carry: what the user granted the client, or the
+    /// user's access range for first-party sessions. Clients never get the
+    /// access range, which would open our own admin endpoints to them.
+    pub fn token_scope(&self, access_range: &str) -> String {
+        match self.client_id {
+            Some(_) => self.scope.clone().unwrap_or_default(),
+            None => access_range.to_string(),
+        }
+    }
+}
+
+/// How and when a user last proved who they are. Sessions record it and
+/// their tokens carry it, refreshing doesn't change it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Authentication {
+    pub acr: Acr,
+    pub auth_time: DateTime<Utc>,
+}
+
+impl Authentication {
+    /// A user authenticating right now with `acr`.
+    pub fn now(acr: Acr) -> Self {
+        Self {
+            acr,
+            auth_time: Utc::now(),
+        }
+    }
+
+    /// Whether it happened within `max_age` before `now`, with `acr` or a
+    /// stronger method.
+    pub fn meets(&self, max_age: Option<Duration>, acr: Option<Acr>, now: DateTime<Utc>) -> bool {
+        let recent_enough = max_age.is_none_or(|max_age| now - self.auth_time <= max_age);
+        let strong_enough = acr.is_none_or(|acr| self.acr >= acr);
+        recent_enough && strong_enough
+    }
+}
+
+/// Authentication context class (`acr`): how strongly a user proved who they
+/// are. Later variants are stronger, so levels can be compared.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Acr {
+    #[default]
+    Password,
+    Mfa,
+}
+
+impl std::str::FromStr for Acr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "password" => Ok(Acr::Password),
+            "mfa" => Ok(Acr::Mfa),
+            _ => Err(format!("Invalid acr: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Acr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let acr_str = match self {
+            Acr::Password => "password",
+            Acr::Mfa => "mfa",
+        };
+        write!(f, "{}", acr_str)
+    }
 }
 
 // Tokentype enum
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenType {
     Access,
     Refresh,
@@ -112,6 +292,33 @@ impl fmt::Display for TokenType {
     }
 }
 
+// Reasons recorded in `auth.sessions.revoked_reason`
+#[derive(Debug)]
+pub enum RevocationReason {
+    RefreshTokenReuse,
+    Logout,
+    LogoutAll,
+    UserRevoked,
+    TokenRevoked,
+    AuthorizationCodeReuse,
+    Evicted,
+}
+
+impl fmt::Display for RevocationReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason_str = match self {
+            RevocationReason::RefreshTokenReuse => "refresh_token_reuse",
+            RevocationReason::Logout => "logout",
+            RevocationReason::LogoutAll => "logout_all",
+            RevocationReason::UserRevoked => "user_revoked",
+            RevocationReason::TokenRevoked => "token_revoked",
+            RevocationReason::AuthorizationCodeReuse => "authorization_code_reuse",
+            RevocationReason::Evicted => "evicted",
+        };
+        write!(f, "{}", reason_str)
+    }
+}
+
 // AccessRange enum
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub enum AccessRange {
@@ -194,3 +401,304 @@ impl fmt::Display for AuthProvider {
         write!(f, "{}", provider_str)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::errors::ErrorKind;
+
+    fn access_claims(iat: i64, exp: i64) -> JwtClaims {
+        JwtClaims {
+            sub: Uuid::new_v4().to_string(),
+            scope: "user".to_string(),
+            sid: Some(Uuid::new_v4()),
+            iss: "gandalf".to_string(),
+            aud: "app".to_string(),
+            azp: "app".to_string(),
+            exp,
+            iat,
+            jti: Uuid::new_v4().to_string(),
+            nbf: iat,
+            auth_time: iat,
+            resource_access: HashMap::new(),
+            token_type: TokenType::Access.to_string(),
+            act: None,
+            acr: Some(Acr::Password),
+        }
+    }
+
+    fn session(created_at: DateTime<Utc>, last_active_at: DateTime<Utc>) -> Session {
+        Session {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            refresh_token_hash: String::new(),
+            refresh_token_generation: 0,
+            device_identifier: None,
+            device_name: None,
+            device_type: None,
+            ip_address: IpAddr::from([127, 0, 0, 1]),
+            user_agent: None,
+            expires_at: created_at + Duration::days(30),
+            created_at,
+            last_active_at,
+            is_revoked: false,
+            revoked_reason: None,
+            revoked_at: None,
+            client_id: None,
+            acr: Acr::Password,
+            auth_time: created_at,
+            scope: None,
+        }
+    }
+
+    #[test]
+    fn test_idle_session_ends_after_idle_timeout() {
+        let created_at = Utc::now() - Duration::days(2);
+        let last_active_at = created_at + Duration::hours(1);
+
+        let ends_at =
+            session(created_at, last_active_at).ends_at(Duration::minutes(30), Duration::days(7));
+
+        assert_eq!(ends_at, last_active_at + Duration::minutes(30));
+    }
+
+    #[test]
+    fn test_active_session_ends_at_absolute_lifetime_or_expiry() {
+        let created_at = Utc::now() - Duration::days(2);
+        let active = session(created_at, Utc::now());
+
+        assert_eq!(
+            active.ends_at(Duration::minutes(30), Duration::hours(36)),
+            created_at + Duration::hours(36)
+        );
+        assert_eq!(
+            active.ends_at(Duration::days(60), Duration::days(60)),
+            active.expires_at
+        );
+    }
+
+    #[test]
+    fn test_client_sessions_never_carry_the_access_range() {
+        let mut session = session(Utc::now(), Utc::now());
+        assert_eq!(session.token_scope("global"), "global");
+
+        session.client_id = Some("app".to_string());
+        assert_eq!(session.token_scope("global"), "");
+        session.scope = Some("openid email".to_string());
+        assert_eq!(session.token_scope("global"), "openid email");
+    }
+
+    #[test]
+    fn test_old_authentication_needs_reauthentication() {
+        let now = Utc::now();
+        let claims = access_claims(now.timestamp() - 600, now.timestamp() + 60);
+
+        assert!(claims.meets_authentication(None, None, now));
+        assert!(claims.meets_authentication(Some(Duration::minutes(15)), None, now));
+        assert!(!claims.meets_authentication(Some(Duration::minutes(5)), None, now));
+    }
+
+    #[test]
+    fn test_weaker_authentication_needs_reauthentication() {
+        let now = Utc::now();
+        let mut claims = access_claims(now.timestamp(), now.timestamp() + 60);
+
+        assert!(claims.meets_authentication(None, Some(Acr::Password), now));
+        assert!(!claims.meets_authentication(None, Some(Acr::Mfa), now));
+
+        claims.acr = Some(Acr::Mfa);
+        assert!(claims.meets_authentication(None, Some(Acr::Password), now));
+
+        // Client tokens have no user to authenticate again
+        claims.acr = None;
+        assert!(!claims.meets_authentication(None, None, now));
+    }
+
+    #[test]
+    fn test_authentication_is_read_from_user_tokens() {
+        let iat = Utc::now().timestamp() - 600;
+        let mut claims = access_claims(iat, iat + 3600);
+
+        let authentication = claims.authentication().unwrap();
+        assert_eq!(authentication.acr, Acr::Password);
+        assert_eq!(authentication.auth_time.timestamp(), iat);
+
+        claims.acr = None;
+        assert_eq!(claims.authentication(), None);
+    }
+
+    #[test]
+    fn test_id_token_omits_missing_nonce() {
+        let key = JwtKey::hmac("secret");
+        let mut claims = IdTokenClaims {
+            iss: "gandalf".to_string(),
+            sub: Uuid::new_v4().to_string(),
+            aud: "app".to_string(),
+            exp: 0,
+            iat: 0,
+            auth_time: 0,
+            acr: Acr::Password,
+            nonce: None,
+            sid: Uuid::new_v4(),
+            email: "user@mail.com".to_string(),
+            email_verified: true,
+        };
+
+        let mut validation = validation("gandalf", Some("app"), 0);
+        validation.validate_exp = false;
+        validation.validate_nbf = false;
+        validation.required_spec_claims.clear();
+
+        let decode_claims = |claims: &IdTokenClaims| {
+            decode::<serde_json::Value>(&claims.to_jwt(&key), key.decoding_key(), &validation)
+                .unwrap()
+                .claims
+        };
+
+        assert!(decode_claims(&claims).get("nonce").is_none());
+
+        claims.nonce = Some("n-0S6_WzA2Mj".to_string());
+        assert_eq!(decode_claims(&claims)["nonce"], "n-0S6_WzA2Mj");
+    }
+
+    fn validation(issuer: &str, audience: Option<&str>, leeway: u64) -> Validation {
+        JwtClaims::validation(issuer, audience, leeway, Algorithm::HS256)
+    }
+
+    fn decode_err(claims: &JwtClaims, validation: &Validation) -> ErrorKind {
+        let key = JwtKey::hmac("secret");
+        let token = claims.to_jwt(&key);
+        JwtClaims::from_jwt(&token, &key, validation)
+            .unwrap_err()
+            .into_kind()
+    }
+
+    #[test]
+    fn test_access_token_round_trip() {
+        let now = Utc::now().timestamp();
+        let claims = access_claims(now, now + 60);
+        let key = JwtKey::hmac("secret");
+        let token = claims.to_jwt(&key);
+
+        let decoded =
+            JwtClaims::from_jwt(&token, &key, &validation("gandalf", Some("app"), 0)).unwrap();
+
+        assert_eq!(decoded.sid, claims.sid);
+        assert_eq!(decoded.jti, claims.jti);
+    }
+
+    #[test]
+    fn test_nested_actors_round_trip() {
+        let now = Utc::now().timestamp();
+        let mut claims = access_claims(now, now + 60);
+        claims.act = Some(Actor {
+            sub: "gateway".to_string(),
+            act: Some(Box::new(Actor {
+                sub: "frontend".to_string(),
+                act: None,
+            })),
+        });
+        let key = JwtKey::hmac("secret");
+
+        let token = claims.to_jwt(&key);
+        let decoded =
+            JwtClaims::from_jwt(&token, &key, &validation("gandalf", Some("app"), 0)).unwrap();
+
+        assert_eq!(decoded.act, claims.act);
+        assert!(access_claims(now, now + 60).act.is_none());
+    }
+
+    #[test]
+    fn test_leeway_tolerates_clock_skew() {
+        let now = Utc::now().timestamp();
+        let claims = access_claims(now - 120, now - 10);
+        let key = JwtKey::hmac("secret");
+        let token = claims.to_jwt(&key);
+
+        let lenient = validation("gandalf", Some("app"), 30);
+        assert!(JwtClaims::from_jwt(&token, &key, &lenient).is_ok());
+
+        let strict = validation("gandalf", Some("app"), 0);
+        assert_eq!(decode_err(&claims, &strict), ErrorKind::ExpiredSignature);
+    }
+
+    #[test]
+    fn test_token_not_yet_valid_is_rejected() {
+        let now = Utc::now().timestamp();
+        let claims = access_claims(now + 300, now + 600);
+
+        let validation = validation("gandalf", Some("app"), 30);
+        assert_eq!(
+            decode_err(&claims, &validation),
+            ErrorKind::ImmatureSignature
+        );
+    }
+
+    #[test]
+    fn test_issuer_and_audience_are_checked() {
+        let now = Utc::now().timestamp();
+        let claims = access_claims(now, now + 60);
+
+        let other_issuer = validation("someone-else", Some("app"), 0);
+        assert_eq!(decode_err(&claims, &other_issuer), ErrorKind::InvalidIssuer);
+
+        let other_audience = validation("gandalf", Some("other-app"), 0);
+        assert_eq!(
+            decode_err(&claims, &other_audience),
+            ErrorKind::InvalidAudience
+        );
+    }
+
+    #[test]
+    fn test_any_audience_is_accepted_when_unchecked() {
+        let now = Utc::now().timestamp();
+        let key = JwtKey::hmac("secret");
+        let token = access_claims(now, now + 60).to_jwt(&key);
+
+        let any_audience = validation("gandalf", None, 0);
+        let decoded = JwtClaims::from_jwt(&token, &key, &any_audience).unwrap();
+
+        assert_eq!(decoded.aud, "app");
+    }
+
+    #[test]
+    fn test_tokens_for_another_audience_are_not_for_the_issuer() {
+        let now = Utc::now().timestamp();
+        let mut claims = access_claims(now, now + 60);
+        assert!(claims.is_for_issuer("app.teta"));
+
+        claims.aud = "app.teta".to_string();
+        assert!(claims.is_for_issuer("app.teta"));
+
+        // Exchanged by "app" for a resource server
+        claims.aud = "resource-server".to_string();
+        assert!(!claims.is_for_issuer("app.teta"));
+    }
+
+    #[test]
+    fn test_only_tokens_of_our_own_app_are_first_party() {
+        let now = Utc::now().timestamp();
+        let mut claims = access_claims(now, now + 60);
+        claims.aud = "app.teta".to_string();
+        claims.azp = "app.teta".to_string();
+        assert!(claims.is_first_party("app.teta"));
+
+        // Issued to the "app" client, signed in as the user
+        claims.aud = "app".to_string();
+        claims.azp = "app".to_string();
+        assert!(!claims.is_first_party("app.teta"));
+
+        // Exchanged by "app" for our own audience
+        claims.aud = "app.teta".to_string();
+        assert!(!claims.is_first_party("app.teta"));
+    }
+
+    #[test]
+    fn test_wrong_secret_is_rejected() {
+        let now = Utc::now().timestamp();
+        let token = access_claims(now, now + 60).to_jwt(&JwtKey::hmac("secret"));
+
+        let validation = validation("gandalf", Some("app"), 0);
+        assert!(JwtClaims::from_jwt(&token, &JwtKey::hmac("other-secret"), &validation).is_err());
+    }
+}
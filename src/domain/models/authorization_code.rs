@@ -0,0 +1,27 @@
+/*
+This module holds the model for OAuth authorization codes
+*/
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::Acr;
+
+// An authorization code as stored in `auth.authorization_codes`
+#[derive(Debug, Clone)]
+pub struct AuthorizationCode {
+    pub code_hash: String,
+    pub client_id: String,
+    pub user_id: Uuid,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub nonce: Option<String>,
+    pub code_challenge: String,
+    pub acr: Acr, // how the user signed in for this code
+    pub auth_time: DateTime<Utc>,
+    pub max_age: Option<i32>, // seconds, as requested by the client
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub session_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
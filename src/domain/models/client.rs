@@ -0,0 +1,171 @@
+/*
+This module holds the model for registered OAuth clients
+*/
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// A client application registered in `auth.clients`
+#[derive(Debug, Clone)]
+pub struct Client {
+    pub id: Uuid,
+    pub client_id: String,
+    pub client_secret_hash: Option<String>,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<GrantType>,
+    pub scopes: Vec<String>,
+    pub audiences: Vec<String>, // Other clients it may exchange tokens for
+    pub access_token_lifetime: Option<i32>, // Seconds, None uses the server default
+    pub refresh_token_lifetime: Option<i32>, // Seconds, None uses the server default
+    pub jwks: Option<JwkSet>,   // Public keys for private_key_jwt
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Client {
+    pub fn new(
+        client_id: String,
+        name: String,
+        client_secret_hash: Option<String>,
+        redirect_uris: Vec<String>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            client_id,
+            client_secret_hash,
+            name,
+            redirect_uris,
+            grant_types: GrantType::defaults(),
+            scopes: Client::default_scopes(),
+            audiences: vec![],
+            access_token_lifetime: None,
+            refresh_token_lifetime: None,
+            jwks: None,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Scopes a client gets when none are registered explicitly.
+    pub fn default_scopes() -> Vec<String> {
+        vec!["openid".to_string(), "email".to_string()]
+    }
+
+    /// Public clients, like SPAs, can't keep a secret or a private key.
+    pub fn is_public(&self) -> bool {
+        self.client_secret_hash.is_none() && self.jwks.is_none()
+    }
+
+    /// Redirect URIs must match a registered one exactly.
+    pub fn allows_redirect_uri(&self, redirect_uri: &str) -> bool {
+        self.redirect_uris.iter().any(|uri| uri == redirect_uri)
+    }
+
+    pub fn allows_grant_type(&self, grant_type: GrantType) -> bool {
+        self.grant_types.contains(&grant_type)
+    }
+
+    /// Token exchange may only address other clients registered for it.
+    pub fn allows_audience(&self, audience: &str) -> bool {
+        self.audiences.iter().any(|allowed| allowed == audience)
+    }
+
+    /// Whether every scope of a space separated scope string is registered.
+    pub fn allows_scopes(&self, scopes: &str) -> bool {
+        scopes
+            .split_whitespace()
+            .all(|scope| self.scopes.iter().any(|allowed| allowed == scope))
+    }
+}
+
+// GrantType enum, the OAuth grants a client may use at the token endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantType {
+    AuthorizationCode,
+    RefreshToken,
+    ClientCredentials,
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
+    DeviceCode,
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:token-exchange")]
+    TokenExchange,
+}
+
+impl GrantType {
+    /// Grants a client gets when none are registered explicitly.
+    pub fn defaults() -> Vec<GrantType> {
+        vec![GrantType::AuthorizationCode, GrantType::RefreshToken]
+    }
+}
+
+impl std::str::FromStr for GrantType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "authorization_code" => Ok(GrantType::AuthorizationCode),
+            "refresh_token" => Ok(GrantType::RefreshToken),
+            "client_credentials" => Ok(GrantType::ClientCredentials),
+            "urn:ietf:params:oauth:grant-type:device_code" => Ok(GrantType::DeviceCode),
+            "urn:ietf:params:oauth:grant-type:token-exchange" => Ok(GrantType::TokenExchange),
+            _ => Err(format!("Invalid grant type: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for GrantType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grant_type_str = match self {
+            GrantType::AuthorizationCode => "authorization_code",
+            GrantType::RefreshToken => "refresh_token",
+            GrantType::ClientCredentials => "client_credentials",
+            GrantType::DeviceCode => "urn:ietf:params:oauth:grant-type:device_code",
+            GrantType::TokenExchange => "urn:ietf:params:oauth:grant-type:token-exchange",
+        };
+        write!(f, "{}", grant_type_str)
+    }
+}
+
+/// Access a client holds on a resource, for tokens it gets on its own behalf.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientPermission {
+    pub resource_server: String,
+    pub resource: String,
+    pub actions: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requested_scopes_must_be_registered() {
+        let client = Client::new("spa".to_string(), "SPA".to_string(), None, vec![]);
+
+        assert!(client.allows_scopes("openid email"));
+        assert!(client.allows_scopes(""));
+        assert!(!client.allows_scopes("openid admin"));
+    }
+
+    #[test]
+    fn test_grant_type_round_trip() {
+        for grant_type in [
+            GrantType::AuthorizationCode,
+            GrantType::RefreshToken,
+            GrantType::ClientCredentials,
+            GrantType::DeviceCode,
+            GrantType::TokenExchange,
+        ] {
+            assert_eq!(grant_type.to_string().parse::<GrantType>(), Ok(grant_type));
+        }
+        assert!("password".parse::<GrantType>().is_err());
+    }
+}
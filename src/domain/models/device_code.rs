@@ -0,0 +1,28 @@
+/*
+This module holds the model for OAuth device authorization requests
+*/
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::auth::Acr;
+
+// A device authorization request as stored in `auth.device_codes`
+#[derive(Debug, Clone)]
+pub struct DeviceCode {
+    pub device_code_hash: String,
+    pub user_code: String,
+    pub client_id: String,
+    pub scope: String,
+    pub polling_interval: i32, // seconds
+    pub last_polled_at: Option<DateTime<Utc>>,
+    pub user_id: Option<Uuid>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub denied_at: Option<DateTime<Utc>>,
+    pub acr: Option<Acr>, // of the session that approved the request
+    pub auth_time: Option<DateTime<Utc>>, // of the session that approved the request
+    pub expires_at: DateTime<Utc>,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub session_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,386 @@
+/*
+This module holds the models for resource based access control
+*/
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::auth::ResourceAccess;
+
+// A kind of resource a resource server protects, with the actions it supports
+#[derive(Debug, Clone)]
+pub struct ResourceType {
+    pub id: Uuid,
+    pub resource_server: String,
+    pub name: String,
+    pub actions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ResourceType {
+    pub fn new(resource_server: String, name: String, actions: Vec<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            resource_server,
+            name,
+            actions,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+// A single resource of a type, e.g. one channel
+#[derive(Debug, Clone)]
+pub struct Resource {
+    pub id: Uuid,
+    pub resource_type_id: Uuid,
+    pub name: String,
+    pub parent_id: Option<Uuid>, // grants on the parent apply to this resource too
+    pub created_at: DateTime<Utc>,
+}
+
+impl Resource {
+    pub fn new(resource_type: &ResourceType, name: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            resource_type_id: resource_type.id,
+            name,
+            parent_id: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// A resource nested in `parent`, which may be of another type, e.g. a
+    /// channel of a project.
+    pub fn new_child(resource_type: &ResourceType, name: String, parent: &Resource) -> Self {
+        Self {
+            parent_id: Some(parent.id),
+            ..Self::new(resource_type, name)
+        }
+    }
+
+    /// How a resource is named in `resource_access`, e.g. `channel/general`.
+    pub fn key(resource_type: &str, name: &str) -> String {
+        format!("{resource_type}/{name}")
+    }
+
+    /// Splits a resource key into its type and name.
+    pub fn parse_key(key: &str) -> Option<(&str, &str)> {
+        key.split_once('/')
+            .filter(|(resource_type, name)| !resource_type.is_empty() && !name.is_empty())
+    }
+}
+
+// A named set of actions on resources of one type
+#[derive(Debug, Clone)]
+pub struct Role {
+    pub id: Uuid,
+    pub resource_type_id: Uuid,
+    pub name: String,
+    pub actions: Vec<String>, // all supported by the resource type
+    pub created_at: DateTime<Utc>,
+}
+
+impl Role {
+    pub fn new(resource_type: &ResourceType, name: String, actions: Vec<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            resource_type_id: resource_type.id,
+            name,
+            actions,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+// Who a role can be assigned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Principal {
+    User(Uuid),
+    Group(Uuid), // and through it, every member of the group
+}
+
+impl Principal {
+    /// Stored in `iam.role_assignments.principal_type`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Principal::User(_) => "user",
+            Principal::Group(_) => "group",
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        match self {
+            Principal::User(id) | Principal::Group(id) => *id,
+        }
+    }
+
+    pub fn from_parts(kind: &str, id: Uuid) -> Result<Self, String> {
+        match kind {
+            "user" => Ok(Principal::User(id)),
+            "group" => Ok(Principal::Group(id)),
+            _ => Err(format!("Invalid principal type: {}", kind)),
+        }
+    }
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.kind(), self.id())
+    }
+}
+
+// A named set of users and other groups, roles assigned to it apply to all of them
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Group {
+    pub fn new(name: String, description: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            description,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+// A direct member of a group
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupMember {
+    pub group_id: Uuid,
+    pub member: Principal,
+    pub created_at: DateTime<Utc>,
+}
+
+// Whether an assignment grants or withholds its role's actions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Effect {
+    #[default]
+    Allow,
+    Deny, // overrides every allow, inherited or not
+}
+
+impl std::str::FromStr for Effect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Effect::Allow),
+            "deny" => Ok(Effect::Deny),
+            _ => Err(format!("Invalid effect: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Effect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let effect_str = match self {
+            Effect::Allow => "allow",
+            Effect::Deny => "deny",
+        };
+        write!(f, "{}", effect_str)
+    }
+}
+
+// What a role is assigned on
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignmentTarget {
+    Resource(Uuid),
+    // Every resource of the role's type whose name matches, `*` matching anything
+    Pattern(String),
+}
+
+// A role a principal holds on resources, along with their descendants
+#[derive(Debug, Clone)]
+pub struct RoleAssignment {
+    pub id: Uuid,
+    pub principal: Principal,
+    pub role_id: Uuid,
+    pub target: AssignmentTarget,
+    pub effect: Effect,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RoleAssignment {
+    pub fn new(principal: Principal, role: &Role, resource: &Resource) -> Self {
+        Self::on(principal, role, AssignmentTarget::Resource(resource.id))
+    }
+
+    /// Assigns `role` on every resource of its type whose name matches
+    /// `name_pattern`, e.g. `*` for all of them.
+    pub fn matching(principal: Principal, role: &Role, name_pattern: String) -> Self {
+        Self::on(principal, role, AssignmentTarget::Pattern(name_pattern))
+    }
+
+    /// Turns the assignment into one withholding the role's actions.
+    pub fn denied(self) -> Self {
+        Self {
+            effect: Effect::Deny,
+            ..self
+        }
+    }
+
+    fn on(principal: Principal, role: &Role, target: AssignmentTarget) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            principal,
+            role_id: role.id,
+            target,
+            effect: Effect::Allow,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Actions a principal is allowed on a resource it holds roles on, through
+/// any of its roles, less those a deny covers. Grants inherited from
+/// ancestors and pattern grants aren't expanded; only the authorization
+/// service evaluates those.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Permission {
+    pub resource_server: String,
+    pub resource: String, // `<type>/<name>`, see `Resource::key`
+    pub actions: Vec<String>,
+}
+
+impl Permission {
+    /// Groups permissions by resource server, as tokens carry them.
+    pub fn into_resource_access(permissions: Vec<Permission>) -> ResourceAccess {
+        let mut resource_access = ResourceAccess::new();
+        for permission in permissions {
+            resource_access
+                .entry(permission.resource_server)
+                .or_default()
+                .entry(permission.resource)
+                .or_default()
+                .extend(permission.actions);
+        }
+        resource_access
+    }
+}
+
+/// The role assignment an authorization decision rests on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grant {
+    pub role_id: Uuid,
+    pub role: String,
+    pub resource: String, // where it is assigned, an ancestor or a `<type>/<pattern>`
+    pub effect: Effect,
+}
+
+/// Whether a principal may take an action on a resource, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub grant: Option<Grant>, // None when nothing matched
+}
+
+impl Decision {
+    pub fn allow(grant: Grant) -> Self {
+        Self {
+            allowed: true,
+            grant: Some(grant),
+        }
+    }
+
+    pub fn deny() -> Self {
+        Self {
+            allowed: false,
+            grant: None,
+        }
+    }
+}
+
+impl From<Option<Grant>> for Decision {
+    // The grant that decides, a deny when there is one
+    fn from(grant: Option<Grant>) -> Self {
+        match grant {
+            Some(grant) if grant.effect == Effect::Allow => Decision::allow(grant),
+            grant => Decision {
+                allowed: false,
+                grant,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permission(resource_server: &str, resource: &str, actions: &[&str]) -> Permission {
+        Permission {
+            resource_server: resource_server.to_string(),
+            resource: resource.to_string(),
+            actions: actions.iter().map(|action| action.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_permissions_are_grouped_by_resource_server() {
+        let resource_access = Permission::into_resource_access(vec![
+            permission("chat", "channel/general", &["read"]),
+            permission("chat", "channel/random", &["read", "write"]),
+            permission("files", "folder/docs", &["read"]),
+        ]);
+
+        assert_eq!(resource_access.len(), 2);
+        assert_eq!(resource_access["chat"]["channel/general"], vec!["read"]);
+        assert_eq!(
+            resource_access["chat"]["channel/random"],
+            vec!["read", "write"]
+        );
+        assert_eq!(resource_access["files"]["folder/docs"], vec!["read"]);
+    }
+
+    #[test]
+    fn test_denying_grants_decide_a_deny() {
+        let grant = Grant {
+            role_id: Uuid::new_v4(),
+            role: "writer".to_string(),
+            resource: "project/web".to_string(),
+            effect: Effect::Deny,
+        };
+
+        let decision = Decision::from(Some(grant.clone()));
+
+        assert!(!decision.allowed);
+        assert_eq!(decision.grant, Some(grant));
+        assert_eq!(Decision::from(None), Decision::deny());
+    }
+
+    #[test]
+    fn test_resource_keys_need_a_type_and_a_name() {
+        assert_eq!(
+            Resource::parse_key("channel/general/2025"),
+            Some(("channel", "general/2025"))
+        );
+        assert_eq!(Resource::parse_key("channel"), None);
+        assert_eq!(Resource::parse_key("channel/"), None);
+        assert_eq!(Resource::parse_key("/general"), None);
+    }
+
+    #[test]
+    fn test_principal_round_trips_through_its_parts() {
+        for principal in [
+            Principal::User(Uuid::new_v4()),
+            Principal::Group(Uuid::new_v4()),
+        ] {
+            assert_eq!(
+                Principal::from_parts(principal.kind(), principal.id()),
+                Ok(principal)
+            );
+            assert!(Principal::from_parts("robot", principal.id()).is_err());
+        }
+    }
+}
@@ -1,7 +1,25 @@
 /* domain models module */
 
 mod auth;
+mod authorization_code;
+mod client;
+mod device_code;
+mod iam;
+mod revoked_token;
+mod signing_key;
 mod user;
 
-pub use auth::{AuthProvider, JwtClaims, RefreshTokenClaims, Session, TokenType};
+pub use auth::{
+    AccessRange, Acr, Actor, AuthProvider, Authentication, IdTokenClaims, JwtClaims,
+    ResourceAccess, RevocationReason, Session, TokenIntrospection, TokenType,
+};
+pub use authorization_code::AuthorizationCode;
+pub use client::{Client, ClientPermission, GrantType};
+pub use device_code::DeviceCode;
+pub use iam::{
+    AssignmentTarget, Decision, Effect, Grant, Group, GroupMember, Permission, Principal, Resource,
+    ResourceType, Role, RoleAssignment,
+};
+pub use revoked_token::{RevokedToken, RevokedTokenKind};
+pub use signing_key::{KeyState, SigningKey};
 pub use user::User;
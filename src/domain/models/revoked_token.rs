@@ -0,0 +1,81 @@
+/*
+This module holds the model for revoked access tokens
+*/
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+// What a revocation matches in an access token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevokedTokenKind {
+    Jti, // a single token
+    Sid, // every token of a session
+}
+
+impl std::str::FromStr for RevokedTokenKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jti" => Ok(RevokedTokenKind::Jti),
+            "sid" => Ok(RevokedTokenKind::Sid),
+            _ => Err(format!("Invalid revoked token kind: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for RevokedTokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind_str = match self {
+            RevokedTokenKind::Jti => "jti",
+            RevokedTokenKind::Sid => "sid",
+        };
+        write!(f, "{}", kind_str)
+    }
+}
+
+// A row of `auth.revoked_tokens`, also sent as JSON on the `token_revoked` channel
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RevokedToken {
+    pub kind: RevokedTokenKind,
+    pub value: String,
+    pub expires_at: DateTime<Utc>, // no matching token is valid after this
+}
+
+impl RevokedToken {
+    pub fn jti(jti: &str, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            kind: RevokedTokenKind::Jti,
+            value: jti.to_string(),
+            expires_at,
+        }
+    }
+
+    pub fn sid(session_id: Uuid, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            kind: RevokedTokenKind::Sid,
+            value: session_id.to_string(),
+            expires_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_payload_is_parsed() {
+        let payload = r#"{"kind" : "sid", "value" : "c21ff270-2b35-48fb-adcf-2b1756003c98", "expires_at" : "2025-05-04T09:57:13.479118+00:00"}"#;
+
+        let revoked: RevokedToken = serde_json::from_str(payload).unwrap();
+
+        let session_id = Uuid::parse_str("c21ff270-2b35-48fb-adcf-2b1756003c98").unwrap();
+        let expires_at = "2025-05-04T09:57:13.479118+00:00".parse().unwrap();
+        assert_eq!(revoked, RevokedToken::sid(session_id, expires_at));
+    }
+}
@@ -0,0 +1,60 @@
+/*
+This module holds the model for persisted JWT signing keys
+*/
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// Lifecycle of a signing key.
+///
+/// A `next` key is published ahead of use, the `active` key signs new tokens,
+/// a `retiring` key only verifies tokens it already signed, and a `retired`
+/// key is kept for audit with its private material wiped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyState {
+    Next,
+    Active,
+    Retiring,
+    Retired,
+}
+
+impl std::str::FromStr for KeyState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "next" => Ok(KeyState::Next),
+            "active" => Ok(KeyState::Active),
+            "retiring" => Ok(KeyState::Retiring),
+            "retired" => Ok(KeyState::Retired),
+            _ => Err(format!("Invalid key state: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for KeyState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Convert enum variant to string representation
+        let state_str = match self {
+            KeyState::Next => "next",
+            KeyState::Active => "active",
+            KeyState::Retiring => "retiring",
+            KeyState::Retired => "retired",
+        };
+        write!(f, "{}", state_str)
+    }
+}
+
+// A signing key as stored in `auth.signing_keys`
+#[derive(Debug, Clone)]
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: String,
+    pub key_state: KeyState,
+    pub encrypted_private_key: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+    pub activated_at: Option<DateTime<Utc>>,
+    pub retire_after: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
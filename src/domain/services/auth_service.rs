@@ -2,26 +2,57 @@ use std::collections::HashMap;
 use std::net::IpAddr;
 use std::sync::Arc;
 
-use chrono::{Duration, Utc};
-use tracing::error;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::errors::ErrorKind;
+use tokio_postgres::{AsyncMessage, NoTls};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use crate::adapters::repositories::{PgSessionRepository, SessionRepository};
+use crate::adapters::repositories::{
+    ClientRepository, IamRepository, PgClientRepository, PgIamRepository, PgRevokedTokenRepository,
+    PgSessionRepository, PgUserRepository, RevokedTokenRepository, SessionRepository,
+};
 use crate::app_modules::auth::{AuthMethod, AuthStrategy};
-use crate::config::database::PgPool;
+use crate::config::database::{DBConfig, PgPool};
 
-use crate::domain::models::{JwtClaims, RefreshTokenClaims, TokenType};
+use crate::domain::models::{
+    Actor, Authentication, IdTokenClaims, JwtClaims, Permission, Principal, ResourceAccess,
+    RevocationReason, RevokedToken, TokenIntrospection, TokenType,
+};
 
-use crate::adapters::dtos::{AuthUserDto, DeviceInfo};
+use crate::adapters::dtos::{
+    AuthUserDto, ClientGrant, DeviceInfo, OpenIdRequest, SessionLimit, SessionTokens,
+};
 use crate::app_modules::auth::errors::Error;
-use crate::config::app_config::{AppConfig, get_config};
-use crate::domain::models::Session;
+use crate::config::app_config::{AppConfig, SessionLimitPolicy, get_config};
+use crate::domain::models::{Client, Session};
+use crate::utils::RefreshTokenUtil;
+use crate::utils::jwt_keys::JwtKeySet;
+use crate::utils::security_events::{self, SecurityEvent};
+use crate::utils::token_denylist::TokenDenylist;
 
 type Result<T> = std::result::Result<T, Error>;
 
+// Minutes between two recordings of a session's activity
+const LAST_ACTIVE_RESOLUTION: i64 = 1;
+
+// Postgres channel revocations are announced on, see `auth.notify_token_revoked`
+const TOKEN_REVOKED_CHANNEL: &str = "token_revoked";
+// How often expired denylist entries are dropped
+const DENYLIST_PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+// Wait before listening again after losing the notification connection
+const DENYLIST_RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
 pub struct AuthService {
     pub strategies: HashMap<AuthMethod, Arc<dyn AuthStrategy + Send + Sync>>,
     session_repository: PgSessionRepository,
+    user_repository: PgUserRepository,
+    client_repository: PgClientRepository,
+    iam_repository: PgIamRepository,
+    revoked_token_repository: PgRevokedTokenRepository,
+    refresh_tokens: RefreshTokenUtil,
+    jwt_keys: Arc<JwtKeySet>,
+    denylist: TokenDenylist,
     config: &'static AppConfig,
 }
 
@@ -29,74 +60,63 @@ impl AuthService {
     pub fn new(
         auth_strategies: HashMap<AuthMethod, Arc<dyn AuthStrategy + Send + Sync>>,
         db: Arc<PgPool>,
+        jwt_keys: Arc<JwtKeySet>,
+    ) -> Self {
+        Self::with_config(auth_strategies, db, jwt_keys, get_config())
+    }
+
+    /// Like `new`, with settings other than the global ones.
+    pub fn with_config(
+        auth_strategies: HashMap<AuthMethod, Arc<dyn AuthStrategy + Send + Sync>>,
+        db: Arc<PgPool>,
+        jwt_keys: Arc<JwtKeySet>,
+        config: &'static AppConfig,
     ) -> Self {
         Self {
             strategies: auth_strategies,
             session_repository: PgSessionRepository::new(db.clone()),
-            config: get_config(),
+            user_repository: PgUserRepository::new(db.clone()),
+            client_repository: PgClientRepository::new(db.clone()),
+            iam_repository: PgIamRepository::new(db.clone()),
+            revoked_token_repository: PgRevokedTokenRepository::new(db.clone()),
+            refresh_tokens: RefreshTokenUtil::new(&config.refresh_token_secret),
+            jwt_keys,
+            denylist: TokenDenylist::new(),
+            config,
         }
     }
 
+    /// Creates a session and issues its tokens.
+    /// Tokens are issued to the client of `grant` with the scope the user
+    /// granted it, or to the first-party audience with the user's access
+    /// range when there is none. An ID token is only issued when `openid` is set.
+    ///
+    /// `authentication` records how and when the user authenticated; tokens of
+    /// the session carry it, refreshing doesn't change it.
+    ///
+    /// A user at their session limit either loses their least recently active
+    /// sessions or can't sign in, depending on the configured policy.
     pub async fn make_session(
         &self,
         user: AuthUserDto,
         ip: IpAddr,
         device_info: DeviceInfo,
-    ) -> Result<(String, String)> {
+        openid: Option<OpenIdRequest>,
+        grant: Option<ClientGrant<'_>>,
+        authentication: Authentication,
+    ) -> Result<SessionTokens> {
+        let client = grant.map(|grant| grant.client);
         let now = Utc::now();
-
-        // Generate expiration timestamps
-        let access_exp = now
-            .checked_add_signed(Duration::minutes(
-                self.config.access_token_expiration.into(),
-            ))
-            .ok_or_else(|| {
-                error!("Invalid access token expiration timestamp");
-                Error::InternalError
-            })?
-            .timestamp();
-
-        let refresh_exp = now
-            .checked_add_signed(Duration::hours(self.config.refresh_token_expiration.into()))
-            .ok_or_else(|| {
-                error!("Invalid refresh token expiration timestamp");
-                Error::InternalError
-            })?
-            .timestamp();
-
         let session_id = Uuid::new_v4();
 
-        // Refresh token claims
-        let refresh_claims = RefreshTokenClaims {
-            sub: user.id.to_string(),
-            session_id: session_id.to_string(),
-            exp: refresh_exp,
-            iat: now.timestamp(),
-            token_type: TokenType::Refresh.to_string(),
-        };
-
-        // TODO: Replace this stubbed permissions structure with actual DB-driven logic
-        let mut permissions = HashMap::new();
-        permissions.insert(
-            "test-tets".to_string(),
-            vec![
-                (
-                    "channel/test".to_string(),
-                    vec!["read".into(), "write".into()],
-                ),
-                (
-                    "channel/test2".to_string(),
-                    vec!["read".into(), "write".into()],
-                ),
-            ]
-            .into_iter()
-            .collect(),
-        );
+        let refresh_token = self.refresh_tokens.generate(session_id, 0);
 
+        // Client lifetimes can't outlast the absolute session lifetime
         let session_exp = now
-            .checked_add_signed(Duration::minutes(
-                self.config.refresh_token_expiration.into(),
-            ))
+            .checked_add_signed(
+                self.refresh_token_lifetime(client)
+                    .min(self.config.session_absolute_lifetime()),
+            )
             .ok_or_else(|| {
                 error!("Invalid session expiration timestamp");
                 Error::InternalError
@@ -105,7 +125,8 @@ impl AuthService {
         let session = Session {
             id: session_id,
             user_id: user.id,
-            refresh_token_hash: refresh_claims.to_jwt(&self.config.jwt_secret),
+            refresh_token_hash: self.refresh_tokens.digest(&refresh_token),
+            refresh_token_generation: 0,
             device_identifier: None,
             device_name: Some(device_info.device_name),
             device_type: Some(device_info.device_type),
@@ -117,36 +138,884 @@ impl AuthService {
             is_revoked: false,
             revoked_reason: None,
             revoked_at: None,
+            client_id: client.map(|client| client.client_id.clone()),
+            acr: authentication.acr,
+            auth_time: authentication.auth_time,
+            scope: grant.map(|grant| grant.scope.to_string()),
         };
 
+        let resource_access = self.resource_access(&user).await?;
+
         // Persist session
+        self.create_limited_session(&user, &session).await?;
+
+        let signing_key = self.jwt_keys.signing_key();
+        let access_claims = self.access_claims(&user, &session, client, resource_access, now)?;
+        let id_token = openid.map(|openid| {
+            IdTokenClaims {
+                iss: self.config.jwt_issuer.clone(),
+                sub: user.id.to_string(),
+                aud: access_claims.aud.clone(),
+                exp: access_claims.exp,
+                iat: now.timestamp(),
+                auth_time: session.auth_time.timestamp(),
+                acr: session.acr,
+                nonce: openid.nonce,
+                sid: session.id,
+                email: user.email.clone(),
+                email_verified: user.email_verified,
+            }
+            .to_jwt(&signing_key)
+        });
+
+        Ok(SessionTokens {
+            session_id: session.id,
+            access_token: access_claims.to_jwt(&signing_key),
+            refresh_token,
+            id_token,
+        })
+    }
+
+    /// Issues an access token a client holds on its own behalf, for the
+    /// client credentials grant. No session or refresh token is created, the
+    /// token is only good until it expires.
+    pub fn issue_client_token(
+        &self,
+        client: &Client,
+        scope: &str,
+        resource_access: ResourceAccess,
+    ) -> Result<String> {
+        let now = Utc::now();
+        let exp = now
+            .checked_add_signed(self.access_token_lifetime(Some(client)))
+            .ok_or_else(|| {
+                error!("Invalid access token expiration timestamp");
+                Error::InternalError
+            })?
+            .timestamp();
+
+        let claims = JwtClaims {
+            sub: client.client_id.clone(),
+            scope: scope.to_string(),
+            sid: None,
+            iss: self.config.jwt_issuer.clone(),
+            aud: client.client_id.clone(),
+            azp: client.client_id.clone(),
+            exp,
+            iat: now.timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            nbf: now.timestamp(),
+            auth_time: now.timestamp(),
+            resource_access,
+            token_type: TokenType::Access.to_string(),
+            act: None,
+            acr: None,
+        };
+
+        Ok(claims.to_jwt(&self.jwt_keys.signing_key()))
+    }
+
+    /// Issues an access token derived from `subject`, for token exchange
+    /// (RFC 8693). It keeps the subject's session, so revoking that session
+    /// revokes the derived token too, and never outlives the subject token.
+    pub fn issue_exchanged_token(
+        &self,
+        client: &Client,
+        subject: &JwtClaims,
+        audience: &str,
+        scope: &str,
+        resource_access: ResourceAccess,
+        act: Option<Actor>,
+    ) -> Result<(String, i64)> {
+        let now = Utc::now();
+        let exp = now
+            .checked_add_signed(self.access_token_lifetime(Some(client)))
+            .ok_or_else(|| {
+                error!("Invalid access token expiration timestamp");
+                Error::InternalError
+            })?
+            .timestamp()
+            .min(subject.exp);
+
+        let claims = JwtClaims {
+            sub: subject.sub.clone(),
+            scope: scope.to_string(),
+            sid: subject.sid,
+            iss: self.config.jwt_issuer.clone(),
+            aud: audience.to_string(),
+            azp: client.client_id.clone(),
+            exp,
+            iat: now.timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            nbf: now.timestamp(),
+            auth_time: subject.auth_time,
+            resource_access,
+            token_type: TokenType::Access.to_string(),
+            act,
+            acr: subject.acr,
+        };
+
+        Ok((
+            claims.to_jwt(&self.jwt_keys.signing_key()),
+            exp - now.timestamp(),
+        ))
+    }
+
+    /// Exchanges a valid refresh token for a new access/refresh pair.
+    /// The presented refresh token is rotated out and stops working.
+    ///
+    /// Only `client`, the one the session was issued to, can refresh it;
+    /// first-party sessions are refreshed without a client.
+    pub async fn refresh_session(
+        &self,
+        refresh_token: &str,
+        client: Option<&Client>,
+    ) -> Result<(String, String)> {
+        let parsed = self
+            .refresh_tokens
+            .parse(refresh_token)
+            .map_err(|_| Error::InvalidToken)?;
+
+        let session = self
+            .session_repository
+            .get_session_by_id(parsed.session_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load session: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::InvalidToken)?;
+
+        if session.is_revoked
+            || session.client_id.as_deref() != client.map(|client| client.client_id.as_str())
+        {
+            return Err(Error::InvalidToken);
+        }
+
+        // A token we signed for an older generation was already rotated out
+        if parsed.generation < session.refresh_token_generation {
+            return Err(self
+                .handle_refresh_token_reuse(&session, parsed.generation)
+                .await);
+        }
+
+        let now = Utc::now();
+        if parsed.generation != session.refresh_token_generation
+            || !self
+                .refresh_tokens
+                .verify_digest(refresh_token, &session.refresh_token_hash)
+        {
+            return Err(Error::InvalidToken);
+        }
+        if self.session_ends_at(&session) <= now {
+            return Err(Error::TokenExpired);
+        }
+
+        let user = self
+            .user_repository
+            .find_auth_user_by_id(&session.user_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load session user: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::InvalidToken)?;
+
+        let new_refresh_token = self
+            .refresh_tokens
+            .generate(session.id, session.refresh_token_generation + 1);
+
+        let rotated = self
+            .session_repository
+            .rotate_refresh_token(
+                session.id,
+                session.refresh_token_generation,
+                &self.refresh_tokens.digest(&new_refresh_token),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to rotate refresh token: {e}");
+                Error::InternalError
+            })?;
+
+        // A concurrent request presented the same token and rotated it first
+        if !rotated {
+            return Err(self
+                .handle_refresh_token_reuse(&session, parsed.generation)
+                .await);
+        }
+
+        let resource_access = self.resource_access(&user).await?;
+        let access_claims = self.access_claims(&user, &session, client, resource_access, now)?;
+        let access_token = access_claims.to_jwt(&self.jwt_keys.signing_key());
+
+        Ok((access_token, new_refresh_token))
+    }
+
+    /// Verifies an access token and returns its claims.
+    /// When enabled in config, also confirms the backing session is still active.
+    /// Client credentials tokens have no session, their client must always
+    /// still be active.
+    ///
+    /// Only tokens meant for our own endpoints are accepted, not those
+    /// exchanged for another audience.
+    pub async fn verify_access_token(&self, token: &str) -> Result<JwtClaims> {
+        let claims = self.decode_access_token(token)?;
+        if !claims.is_for_issuer(&self.config.jwt_audience) {
+            return Err(Error::InvalidToken);
+        }
+
+        match claims.sid {
+            Some(session_id) if self.config.verify_session_on_access => {
+                self.used_session(session_id).await?;
+            }
+            Some(_) => {}
+            None => self.check_client_active(&claims.azp).await?,
+        }
+
+        Ok(claims)
+    }
+
+    /// Whether `claims` were issued to our own app, which alone may use the
+    /// first-party endpoints. Tokens of clients only reach the endpoints their
+    /// scope opens.
+    pub fn is_first_party(&self, claims: &JwtClaims) -> bool {
+        claims.is_first_party(&self.config.jwt_audience)
+    }
+
+    /// Verifies an access token `client_id` presents for token exchange. It
+    /// may also be addressed to that client. Unlike `verify_access_token`, its
+    /// session is always checked, so tokens of a revoked session can't be
+    /// swapped for fresh ones.
+    pub async fn verify_exchangeable_token(
+        &self,
+        token: &str,
+        client_id: &str,
+    ) -> Result<JwtClaims> {
+        let claims = self.decode_access_token(token)?;
+        if !claims.is_for_issuer(&self.config.jwt_audience) && claims.aud != client_id {
+            return Err(Error::InvalidToken);
+        }
+
+        match claims.sid {
+            Some(session_id) => {
+                self.used_session(session_id).await?;
+            }
+            None => self.check_client_active(&claims.azp).await?,
+        }
+
+        Ok(claims)
+    }
+
+    /// Describes an access or refresh token to `client_id` for introspection
+    /// (RFC 7662).
+    ///
+    /// Returns `None` for tokens that are unknown, expired or whose session was
+    /// revoked, and for tokens neither issued to nor addressed to `client_id`,
+    /// so clients can't learn about others' tokens. `hint` only decides which
+    /// token type is tried first.
+    pub async fn introspect(
+        &self,
+        token: &str,
+        hint: Option<TokenType>,
+        client_id: &str,
+    ) -> Result<Option<TokenIntrospection>> {
+        let introspection = match hint {
+            Some(TokenType::Refresh) => match self.introspect_refresh_token(token).await? {
+                Some(introspection) => Some(introspection),
+                None => self.introspect_access_token(token).await?,
+            },
+            _ => match self.introspect_access_token(token).await? {
+                Some(introspection) => Some(introspection),
+                None => self.introspect_refresh_token(token).await?,
+            },
+        };
+
+        Ok(introspection.filter(|introspection| {
+            introspection.client_id == client_id || introspection.aud == client_id
+        }))
+    }
+
+    /// Revokes an access or refresh token (RFC 7009), along with its session.
+    /// Access tokens without a session, issued to clients, are denied by `jti`.
+    ///
+    /// Only tokens issued to `client_id` are revoked (RFC 7009 section 2.1).
+    /// Tokens that are invalid, already revoked or issued to another client are
+    /// ignored, so callers can't learn anything from the outcome. `hint` only
+    /// decides which token type is tried first.
+    pub async fn revoke_token(
+        &self,
+        token: &str,
+        hint: Option<TokenType>,
+        client_id: &str,
+    ) -> Result<()> {
+        match hint {
+            Some(TokenType::Refresh) => {
+                if !self.revoke_refresh_token(token, client_id).await? {
+                    self.revoke_access_token(token, client_id).await?;
+                }
+            }
+            _ => {
+                if !self.revoke_access_token(token, client_id).await? {
+                    self.revoke_refresh_token(token, client_id).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether `token` is an access token, even one left untouched
+    /// because it belongs to another client.
+    async fn revoke_access_token(&self, token: &str, client_id: &str) -> Result<bool> {
+        let Ok(claims) = self.decode_access_token(token) else {
+            return Ok(false);
+        };
+        if claims.azp != client_id {
+            return Ok(true);
+        }
+
+        // Exchanged tokens share the session of another client's token
+        if let Some(session_id) = claims.sid {
+            let session = match self.active_session(session_id).await {
+                Ok(session) => Some(session),
+                Err(Error::InternalError) => return Err(Error::InternalError),
+                Err(_) => None,
+            };
+            if let Some(session) = session
+                && session.client_id.as_deref() == Some(client_id)
+            {
+                self.revoke_session(session.id, RevocationReason::TokenRevoked)
+                    .await?;
+            }
+        }
+
+        let expires_at = DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now)
+            + Duration::seconds(self.config.jwt_leeway.into());
+        self.deny(vec![RevokedToken::jti(&claims.jti, expires_at)])
+            .await?;
+        Ok(true)
+    }
+
+    /// Returns whether `token` is a refresh token. Only the current token of a
+    /// session issued to `client_id` revokes it.
+    async fn revoke_refresh_token(&self, token: &str, client_id: &str) -> Result<bool> {
+        let Ok(parsed) = self.refresh_tokens.parse(token) else {
+            return Ok(false);
+        };
+
+        let session = match self.active_session(parsed.session_id).await {
+            Ok(session) => session,
+            Err(Error::InternalError) => return Err(Error::InternalError),
+            Err(_) => return Ok(true),
+        };
+        if session.client_id.as_deref() == Some(client_id)
+            && parsed.generation == session.refresh_token_generation
+            && self
+                .refresh_tokens
+                .verify_digest(token, &session.refresh_token_hash)
+        {
+            self.revoke_session(session.id, RevocationReason::TokenRevoked)
+                .await?;
+        }
+        Ok(true)
+    }
+
+    /// Revokes a single session.
+    pub async fn logout(&self, session_id: Uuid) -> Result<()> {
+        self.revoke_session(session_id, RevocationReason::Logout)
+            .await
+    }
+
+    /// Revokes a single session, recording why. Its access tokens are denied
+    /// right away rather than once they expire.
+    pub async fn revoke_session(&self, session_id: Uuid, reason: RevocationReason) -> Result<()> {
         self.session_repository
-            .create_session(&session)
+            .revoke_session(session_id, Some(reason.to_string()))
             .await
             .map_err(|e| {
-                error!("Failed to create session: {e}");
+                error!("Failed to revoke session: {e}");
                 Error::InternalError
             })?;
 
-        // Access token claims
-        let access_claims = JwtClaims {
+        self.deny_sessions(&[session_id]).await
+    }
+
+    /// Revokes every active session of a user, except `keep_session_id` when given.
+    /// Returns the number of sessions revoked.
+    pub async fn logout_all(&self, user_id: Uuid, keep_session_id: Option<Uuid>) -> Result<u64> {
+        let session_ids = self
+            .session_repository
+            .revoke_user_sessions(
+                user_id,
+                keep_session_id,
+                Some(RevocationReason::LogoutAll.to_string()),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to revoke user sessions: {e}");
+                Error::InternalError
+            })?;
+
+        self.deny_sessions(&session_ids).await?;
+        Ok(session_ids.len() as u64)
+    }
+
+    /// Lists the sessions of a user that can still be used.
+    pub async fn list_sessions(&self, user_id: Uuid) -> Result<Vec<Session>> {
+        let now = Utc::now();
+        self.session_repository
+            .list_active_sessions(user_id)
+            .await
+            .map(|sessions| {
+                // Idle and too old sessions are over, even if not yet expired
+                sessions
+                    .into_iter()
+                    .filter(|session| self.session_ends_at(session) > now)
+                    .collect()
+            })
+            .map_err(|e| {
+                error!("Failed to list user sessions: {e}");
+                Error::InternalError
+            })
+    }
+
+    /// Revokes one of the user's own sessions.
+    pub async fn revoke_user_session(&self, user_id: Uuid, session_id: Uuid) -> Result<()> {
+        let revoked = self
+            .session_repository
+            .revoke_user_session(
+                user_id,
+                session_id,
+                Some(RevocationReason::UserRevoked.to_string()),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to revoke user session: {e}");
+                Error::InternalError
+            })?;
+
+        // Sessions of other users are reported as missing, not forbidden
+        if !revoked {
+            return Err(Error::SessionNotFound);
+        }
+        self.deny_sessions(&[session_id]).await
+    }
+
+    /// Loads the revocations stored by every replica, replacing the denylist.
+    async fn load_denylist(&self) -> Result<()> {
+        let revoked = self
+            .revoked_token_repository
+            .list_revoked()
+            .await
+            .map_err(|e| {
+                error!("Failed to load revoked tokens: {e}");
+                Error::InternalError
+            })?;
+
+        self.denylist.replace(revoked);
+        Ok(())
+    }
+
+    /// Keeps the denylist in sync with the other replicas in the background,
+    /// by listening for revocations, and drops entries that have expired.
+    ///
+    /// Notifications sent while the connection is down are lost, so the whole
+    /// denylist is reloaded every time listening starts.
+    pub fn spawn_denylist_listener(self: &Arc<Self>) {
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = service.listen_for_revocations().await {
+                    error!("Listening for token revocations failed: {e}");
+                }
+                tokio::time::sleep(DENYLIST_RECONNECT_DELAY).await;
+            }
+        });
+    }
+
+    async fn listen_for_revocations(&self) -> std::result::Result<(), tokio_postgres::Error> {
+        let database_url = DBConfig::from_env().database_url;
+        let (client, mut connection) = tokio_postgres::connect(&database_url, NoTls).await?;
+        let (notifications, mut received) = tokio::sync::mpsc::unbounded_channel();
+
+        // The connection yields notifications only while it is being polled
+        let connection = tokio::spawn(async move {
+            loop {
+                match std::future::poll_fn(|cx| connection.poll_message(cx)).await {
+                    Some(Ok(AsyncMessage::Notification(notification))) => {
+                        let _ = notifications.send(notification);
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e),
+                    None => return Ok(()),
+                }
+            }
+        });
+
+        client
+            .batch_execute(&format!("LISTEN {TOKEN_REVOKED_CHANNEL}"))
+            .await?;
+        if self.load_denylist().await.is_err() {
+            warn!("Token denylist may miss revocations until the next reload");
+        }
+        info!("Listening for token revocations");
+
+        let mut purge = tokio::time::interval(DENYLIST_PURGE_INTERVAL);
+        loop {
+            tokio::select! {
+                notification = received.recv() => {
+                    let Some(notification) = notification else {
+                        break;
+                    };
+                    match serde_json::from_str::<RevokedToken>(notification.payload()) {
+                        Ok(revoked) => self.denylist.insert(revoked),
+                        Err(e) => error!("Invalid token revocation notification: {e}"),
+                    }
+                }
+                _ = purge.tick() => self.purge_denylist().await,
+            }
+        }
+
+        drop(client);
+        match connection.await {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Token revocation listener panicked: {e}");
+                Ok(())
+            }
+        }
+    }
+
+    async fn purge_denylist(&self) {
+        self.denylist.purge(Utc::now());
+        if let Err(e) = self.revoked_token_repository.purge_expired().await {
+            error!("Failed to purge revoked tokens: {e}");
+        }
+    }
+
+    /// Denies the access tokens of revoked sessions until every one of them
+    /// has expired. Client lifetimes are capped, so no token of the session
+    /// outlives the cap; the session check on verification may be disabled.
+    async fn deny_sessions(&self, session_ids: &[Uuid]) -> Result<()> {
+        if session_ids.is_empty() {
+            return Ok(());
+        }
+
+        let expires_at = Utc::now()
+            + self.config.max_access_token_lifetime()
+            + Duration::seconds(self.config.jwt_leeway.into());
+        let revoked = session_ids
+            .iter()
+            .map(|session_id| RevokedToken::sid(*session_id, expires_at))
+            .collect();
+        self.deny(revoked).await
+    }
+
+    /// Denies tokens on this replica at once, and on the others once they are
+    /// notified of the stored revocations.
+    async fn deny(&self, revoked: Vec<RevokedToken>) -> Result<()> {
+        for revoked in &revoked {
+            self.denylist.insert(revoked.clone());
+        }
+
+        self.revoked_token_repository
+            .revoke(&revoked)
+            .await
+            .map_err(|e| {
+                error!("Failed to store revoked tokens: {e}");
+                Error::InternalError
+            })
+    }
+
+    /// Treats a replayed refresh token as theft and revokes the whole session.
+    async fn handle_refresh_token_reuse(&self, session: &Session, generation: i32) -> Error {
+        security_events::emit(SecurityEvent::RefreshTokenReuse {
+            user_id: session.user_id,
+            session_id: session.id,
+            presented_generation: generation,
+            current_generation: session.refresh_token_generation,
+        });
+
+        if let Err(e) = self
+            .revoke_session(session.id, RevocationReason::RefreshTokenReuse)
+            .await
+        {
+            return e;
+        }
+
+        Error::RefreshTokenReused
+    }
+
+    async fn introspect_access_token(&self, token: &str) -> Result<Option<TokenIntrospection>> {
+        let Ok(claims) = self.decode_access_token(token) else {
+            return Ok(None);
+        };
+
+        let checked = match claims.sid {
+            Some(session_id) => self.used_session(session_id).await.map(|_| ()),
+            None => self.check_client_active(&claims.azp).await,
+        };
+        match checked {
+            Ok(()) => {}
+            Err(Error::InternalError) => return Err(Error::InternalError),
+            Err(_) => return Ok(None),
+        }
+
+        Ok(Some(TokenIntrospection {
+            sub: claims.sub,
+            scope: claims.scope,
+            sid: claims.sid,
+            iss: claims.iss,
+            aud: claims.aud,
+            client_id: claims.azp,
+            exp: claims.exp,
+            iat: claims.iat,
+            jti: Some(claims.jti),
+            act: claims.act,
+            auth_time: claims.sid.map(|_| claims.auth_time),
+            acr: claims.acr,
+            token_type: TokenType::Access,
+        }))
+    }
+
+    async fn introspect_refresh_token(&self, token: &str) -> Result<Option<TokenIntrospection>> {
+        let Ok(parsed) = self.refresh_tokens.parse(token) else {
+            return Ok(None);
+        };
+
+        let session = match self.active_session(parsed.session_id).await {
+            Ok(session) => session,
+            Err(Error::InternalError) => return Err(Error::InternalError),
+            Err(_) => return Ok(None),
+        };
+        if parsed.generation != session.refresh_token_generation
+            || !self
+                .refresh_tokens
+                .verify_digest(token, &session.refresh_token_hash)
+        {
+            return Ok(None);
+        }
+
+        let Some(user) = self
+            .user_repository
+            .find_auth_user_by_id(&session.user_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load session user: {e}");
+                Error::InternalError
+            })?
+        else {
+            return Ok(None);
+        };
+
+        let client_id = self.audience(session.client_id.as_deref());
+        Ok(Some(TokenIntrospection {
             sub: user.id.to_string(),
-            scope: user.access_range.clone(),
-            sid: session.id,
-            iss: self.config.app_host.clone(),
-            aud: "app.teta".into(),
+            scope: session.token_scope(&user.access_range),
+            sid: Some(session.id),
+            iss: self.config.jwt_issuer.clone(),
+            aud: client_id.clone(),
+            client_id,
+            exp: session.expires_at.timestamp(),
+            // Rotated refresh tokens belong to the grant started at login
+            iat: session.created_at.timestamp(),
+            jti: None,
+            act: None,
+            auth_time: Some(session.auth_time.timestamp()),
+            acr: Some(session.acr),
+            token_type: TokenType::Refresh,
+        }))
+    }
+
+    /// Checks an access token's signature, lifetime and issuer.
+    /// Tokens of every audience are decoded, callers check whether theirs is
+    /// one they accept; introspection and revocation describe them all.
+    fn decode_access_token(&self, token: &str) -> Result<JwtClaims> {
+        let key = self
+            .jwt_keys
+            .verification_key(token)
+            .ok_or(Error::InvalidToken)?;
+        let validation = JwtClaims::validation(
+            &self.config.jwt_issuer,
+            None,
+            self.config.jwt_leeway.into(),
+            key.algorithm,
+        );
+
+        let claims = JwtClaims::from_jwt(token, &key, &validation).map_err(|e| match e.kind() {
+            ErrorKind::ExpiredSignature => Error::TokenExpired,
+            _ => Error::InvalidToken,
+        })?;
+
+        if claims.token_type != TokenType::Access.to_string()
+            || self.denylist.is_revoked(&claims, Utc::now())
+        {
+            return Err(Error::InvalidToken);
+        }
+        Ok(claims)
+    }
+
+    /// Loads a session that is neither revoked nor expired.
+    async fn active_session(&self, session_id: Uuid) -> Result<Session> {
+        let session = self
+            .session_repository
+            .get_session_by_id(session_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load session: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::InvalidToken)?;
+
+        if session.is_revoked {
+            return Err(Error::InvalidToken);
+        }
+        if self.session_ends_at(&session) <= Utc::now() {
+            return Err(Error::TokenExpired);
+        }
+        Ok(session)
+    }
+
+    /// Tokens a client got on its own behalf end with the client, when it is
+    /// deactivated or deleted.
+    async fn check_client_active(&self, client_id: &str) -> Result<()> {
+        self.client_repository
+            .find_by_client_id(client_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load token client: {e}");
+                Error::InternalError
+            })?
+            .filter(|client| client.is_active)
+            .map(|_| ())
+            .ok_or(Error::InvalidToken)
+    }
+
+    /// Stores a session of `user`, after making room for it or refusing it
+    /// when they hold as many sessions as they may.
+    async fn create_limited_session(&self, user: &AuthUserDto, session: &Session) -> Result<()> {
+        let max_sessions = match user.max_sessions {
+            Some(max_sessions) => usize::try_from(max_sessions).unwrap_or_default(),
+            None => self.config.max_sessions_per_user.into(),
+        };
+        let limit = SessionLimit {
+            max_sessions,
+            evicted_reason: match self.config.session_limit_policy {
+                SessionLimitPolicy::Reject => None,
+                SessionLimitPolicy::EvictOldest => Some(RevocationReason::Evicted.to_string()),
+            },
+            idle_timeout: self.config.session_idle_timeout(),
+            absolute_lifetime: self.config.session_absolute_lifetime(),
+        };
+
+        let evicted = self
+            .session_repository
+            .create_limited_session(session, &limit)
+            .await
+            .map_err(|e| {
+                error!("Failed to create session: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::SessionLimitReached)?;
+
+        for session_id in &evicted {
+            info!(user_id = %user.id, session_id = %session_id, "Evicted session");
+        }
+        self.deny_sessions(&evicted).await
+    }
+
+    /// Loads an active session a token is being used with, and records the use
+    /// so the session doesn't time out while idle.
+    async fn used_session(&self, session_id: Uuid) -> Result<Session> {
+        let session = self.active_session(session_id).await?;
+
+        // Recording every request would cost a write each, a minute is precise enough
+        if Utc::now() - session.last_active_at >= Duration::minutes(LAST_ACTIVE_RESOLUTION) {
+            self.session_repository
+                .update_last_active(session.id)
+                .await
+                .map_err(|e| {
+                    error!("Failed to record session activity: {e}");
+                    Error::InternalError
+                })?;
+        }
+        Ok(session)
+    }
+
+    fn session_ends_at(&self, session: &Session) -> DateTime<Utc> {
+        session.ends_at(
+            self.config.session_idle_timeout(),
+            self.config.session_absolute_lifetime(),
+        )
+    }
+
+    /// How long access tokens issued to `client` stay valid. Lifetimes
+    /// registered before the cap was lowered are capped too.
+    pub fn access_token_lifetime(&self, client: Option<&Client>) -> Duration {
+        client
+            .and_then(|client| client.access_token_lifetime)
+            .map(|seconds| Duration::seconds(seconds.into()))
+            .map(|lifetime| lifetime.min(self.config.max_access_token_lifetime()))
+            .unwrap_or_else(|| self.config.access_token_lifetime())
+    }
+
+    fn refresh_token_lifetime(&self, client: Option<&Client>) -> Duration {
+        client
+            .and_then(|client| client.refresh_token_lifetime)
+            .map(|seconds| Duration::seconds(seconds.into()))
+            .unwrap_or_else(|| self.config.refresh_token_lifetime())
+    }
+
+    // Tokens are addressed to the client they were issued to
+    fn audience(&self, client_id: Option<&str>) -> String {
+        client_id.unwrap_or(&self.config.jwt_audience).to_string()
+    }
+
+    fn access_claims(
+        &self,
+        user: &AuthUserDto,
+        session: &Session,
+        client: Option<&Client>,
+        resource_access: ResourceAccess,
+        now: DateTime<Utc>,
+    ) -> Result<JwtClaims> {
+        let access_exp = now
+            .checked_add_signed(self.access_token_lifetime(client))
+            .ok_or_else(|| {
+                error!("Invalid access token expiration timestamp");
+                Error::InternalError
+            })?
+            .timestamp();
+
+        let audience = self.audience(session.client_id.as_deref());
+        Ok(JwtClaims {
+            sub: user.id.to_string(),
+            scope: session.token_scope(&user.access_range),
+            sid: Some(session.id),
+            iss: self.config.jwt_issuer.clone(),
+            aud: audience.clone(),
+            azp: audience,
             exp: access_exp,
             iat: now.timestamp(),
             jti: Uuid::new_v4().to_string(),
             nbf: now.timestamp(),
-            auth_time: now.timestamp(),
-            resource_access: permissions,
+            auth_time: session.auth_time.timestamp(),
+            resource_access,
             token_type: TokenType::Access.to_string(),
-        };
-
-        let access_token = access_claims.to_jwt(&self.config.jwt_secret);
-        let refresh_token = refresh_claims.to_jwt(&self.config.jwt_secret);
+            act: None,
+            acr: Some(session.acr),
+        })
+    }
 
-        Ok((access_token, refresh_token))
+    /// What the user may do on each resource, through the roles they hold.
+    async fn resource_access(&self, user: &AuthUserDto) -> Result<ResourceAccess> {
+        self.iam_repository
+            .list_permissions(Principal::User(user.id))
+            .await
+            .map(Permission::into_resource_access)
+            .map_err(|e| {
+                error!("Failed to load user permissions: {e}");
+                Error::InternalError
+            })
     }
 }
@@ -0,0 +1,131 @@
+/* Authorization services module */
+
+use std::sync::Arc;
+
+use tracing::error;
+use uuid::Uuid;
+
+use crate::adapters::dtos::{ActionCheck, ResourcePage};
+use crate::adapters::repositories::{IamRepository, PgIamRepository};
+use crate::config::database::PgPool;
+use crate::domain::models::{Decision, Principal, Resource};
+
+use super::errors::Error;
+
+type Result<T> = std::result::Result<T, Error>;
+
+// Most pairs a single batch check may ask about
+const MAX_BATCH_CHECKS: usize = 500;
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+pub struct AuthorizationService {
+    repo: PgIamRepository,
+}
+
+impl AuthorizationService {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self {
+            repo: PgIamRepository::new(db_pool),
+        }
+    }
+
+    /// Decides whether `principal` may take `action` on `resource`, named
+    /// `<type>/<name>`, of `resource_server`. Roles assigned on an ancestor of
+    /// the resource, or on a pattern matching its name, count too, and a deny
+    /// overrides every allow. Decisions carry the assignment that decided them.
+    pub async fn check(
+        &self,
+        principal: Principal,
+        resource_server: &str,
+        resource: &str,
+        action: &str,
+    ) -> Result<Decision> {
+        let mut decisions = self
+            .check_many(principal, resource_server, &[(resource, action)])
+            .await?;
+
+        decisions.pop().ok_or(Error::InternalError)
+    }
+
+    /// Decides many `(resource, action)` pairs at once, in a single query, as
+    /// `check` does for one. Decisions come in the order of `checks`.
+    pub async fn check_many(
+        &self,
+        principal: Principal,
+        resource_server: &str,
+        checks: &[(&str, &str)],
+    ) -> Result<Vec<Decision>> {
+        if checks.len() > MAX_BATCH_CHECKS {
+            return Err(Error::TooManyChecks(MAX_BATCH_CHECKS));
+        }
+
+        let checks = checks
+            .iter()
+            .map(|(resource, action)| {
+                let (resource_type, resource_name) = Resource::parse_key(resource)
+                    .ok_or_else(|| Error::InvalidResource(resource.to_string()))?;
+                Ok(ActionCheck {
+                    resource_type,
+                    resource_name,
+                    action,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let grants = self
+            .repo
+            .find_grants(principal, resource_server, &checks)
+            .await
+            .map_err(|e| {
+                error!("Failed to look up grants: {e}");
+                Error::InternalError
+            })?;
+
+        Ok(grants.into_iter().map(Decision::from).collect())
+    }
+
+    /// Lists the resources of a type on which `principal` may take `action`,
+    /// a page at a time. Pass the previous page's `next_cursor` as `after`
+    /// to continue. `limit` defaults to 50 and is capped at 500.
+    pub async fn list_resources(
+        &self,
+        principal: Principal,
+        resource_server: &str,
+        resource_type: &str,
+        action: &str,
+        after: Option<Uuid>,
+        limit: Option<u32>,
+    ) -> Result<ResourcePage> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+        // One more than asked tells whether there is a next page
+        let mut resources = self
+            .repo
+            .list_resources_with_action(
+                principal,
+                resource_server,
+                resource_type,
+                action,
+                after,
+                i64::from(limit) + 1,
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to list permitted resources: {e}");
+                Error::InternalError
+            })?;
+
+        let has_more = resources.len() > limit as usize;
+        resources.truncate(limit as usize);
+        let next_cursor = resources
+            .last()
+            .map(|resource| resource.id)
+            .filter(|_| has_more);
+
+        Ok(ResourcePage {
+            resources,
+            next_cursor,
+        })
+    }
+}
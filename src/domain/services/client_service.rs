@@ -0,0 +1,298 @@
+/* Client services module */
+
+use std::sync::Arc;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::DateTime;
+use rand::{RngCore, rngs::OsRng};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::adapters::dtos::ClientSettings;
+use crate::adapters::repositories::{ClientRepository, PgClientRepository};
+use crate::config::app_config::{AppConfig, get_config};
+use crate::config::database::PgPool;
+use crate::domain::models::{AccessRange, Client, ClientPermission, GrantType};
+use crate::utils::PasswordUtil;
+use crate::utils::client_assertion;
+
+use super::errors::Error;
+
+type Result<T> = std::result::Result<T, Error>;
+
+const CLIENT_SECRET_BYTES: usize = 32;
+
+pub struct ClientService {
+    repo: PgClientRepository,
+    password_util: PasswordUtil,
+    config: &'static AppConfig,
+}
+
+impl ClientService {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self {
+            repo: PgClientRepository::new(db_pool),
+            password_util: PasswordUtil::new(),
+            config: get_config(),
+        }
+    }
+
+    /// Registers a client and returns it with its plain secret, if it has one.
+    /// Public clients, and clients that authenticate with their own keys
+    /// (`private_key_jwt`), get no secret. Secrets are only stored hashed and
+    /// can not be shown again.
+    pub async fn register_client(
+        &self,
+        settings: ClientSettings,
+        public: bool,
+    ) -> Result<(Client, Option<String>)> {
+        let needs_secret = !public && settings.jwks.is_none();
+        let client_secret = needs_secret.then(|| {
+            let mut secret = [0u8; CLIENT_SECRET_BYTES];
+            OsRng.fill_bytes(&mut secret);
+            URL_SAFE_NO_PAD.encode(secret)
+        });
+
+        let secret_hash = client_secret
+            .as_deref()
+            .map(|secret| self.password_util.hash_password(secret))
+            .transpose()
+            .map_err(|e| {
+                error!("Failed to hash client secret: {e}");
+                Error::InternalError
+            })?;
+
+        let mut client = Client::new(
+            Uuid::new_v4().simple().to_string(),
+            settings.name.clone(),
+            secret_hash,
+            vec![],
+        );
+        apply_settings(&mut client, settings);
+        check_client(&client, self.config)?;
+        self.repo.create_client(&client).await?;
+
+        Ok((client, client_secret))
+    }
+
+    pub async fn list_clients(&self) -> Result<Vec<Client>> {
+        Ok(self.repo.list_clients().await?)
+    }
+
+    /// Loads a client whether or not it is active.
+    pub async fn get_client(&self, client_id: &str) -> Result<Client> {
+        self.repo
+            .find_by_client_id(client_id)
+            .await?
+            .ok_or(Error::ClientNotFound)
+    }
+
+    /// Replaces the registration metadata of a client. Its id and secret stay
+    /// the same, so the client keeps working if it is still active.
+    pub async fn update_client(
+        &self,
+        client_id: &str,
+        settings: ClientSettings,
+        is_active: bool,
+    ) -> Result<Client> {
+        let mut client = self.get_client(client_id).await?;
+        apply_settings(&mut client, settings);
+        client.is_active = is_active;
+        check_client(&client, self.config)?;
+
+        if !self.repo.update_client(&client).await? {
+            return Err(Error::ClientNotFound);
+        }
+        Ok(client)
+    }
+
+    /// Deletes a client. Its sessions go with it, so its tokens stop working.
+    pub async fn delete_client(&self, client_id: &str) -> Result<()> {
+        if !self.repo.delete_client(client_id).await? {
+            return Err(Error::ClientNotFound);
+        }
+        Ok(())
+    }
+
+    /// Finds an active client without authenticating it.
+    pub async fn find_client(&self, client_id: &str) -> Result<Option<Client>> {
+        let client = self.repo.find_by_client_id(client_id).await?;
+        Ok(client.filter(|client| client.is_active))
+    }
+
+    /// Authenticates a client by its id and secret.
+    /// Public clients have no secret and are identified by their id alone.
+    pub async fn authenticate(
+        &self,
+        client_id: &str,
+        client_secret: Option<&str>,
+    ) -> Result<Client> {
+        let client = self
+            .repo
+            .find_by_client_id(client_id)
+            .await?
+            .filter(|client| client.is_active)
+            .ok_or(Error::InvalidClient)?;
+
+        let (secret_hash, client_secret) =
+            match (client.client_secret_hash.as_deref(), client_secret) {
+                (Some(secret_hash), Some(client_secret)) => (secret_hash, client_secret),
+                (None, None) if client.is_public() => return Ok(client),
+                _ => return Err(Error::InvalidClient),
+            };
+        let verified = self
+            .password_util
+            .verify_password(client_secret, secret_hash)
+            .map_err(|e| {
+                error!("Failed to verify client secret: {e}");
+                Error::InternalError
+            })?;
+
+        if !verified {
+            return Err(Error::InvalidClient);
+        }
+        Ok(client)
+    }
+
+    /// Authenticates a client by a JWT signed with one of its registered keys
+    /// (`private_key_jwt`, RFC 7523). Each assertion is accepted only once.
+    pub async fn authenticate_assertion(
+        &self,
+        assertion: &str,
+        audiences: &[String],
+        leeway: u64,
+    ) -> Result<Client> {
+        let client_id =
+            client_assertion::unverified_subject(assertion).ok_or(Error::InvalidClient)?;
+        let client = self
+            .repo
+            .find_by_client_id(&client_id)
+            .await?
+            .filter(|client| client.is_active)
+            .ok_or(Error::InvalidClient)?;
+        let jwks = client.jwks.as_ref().ok_or(Error::InvalidClient)?;
+
+        let claims =
+            client_assertion::verify(assertion, &client.client_id, jwks, audiences, leeway)
+                .ok_or(Error::InvalidClient)?;
+        let expires_at = DateTime::from_timestamp(claims.exp, 0).ok_or(Error::InvalidClient)?;
+
+        if !self
+            .repo
+            .record_assertion(&client.client_id, &claims.jti, expires_at)
+            .await?
+        {
+            return Err(Error::InvalidClient);
+        }
+        Ok(client)
+    }
+
+    pub async fn list_permissions(&self, client_id: &str) -> Result<Vec<ClientPermission>> {
+        Ok(self.repo.list_permissions(client_id).await?)
+    }
+
+    /// Replaces what a client may do on its own behalf. Tokens already issued
+    /// keep their permissions until they expire.
+    pub async fn replace_permissions(
+        &self,
+        client_id: &str,
+        permissions: Vec<ClientPermission>,
+    ) -> Result<Vec<ClientPermission>> {
+        // Fails for unknown clients
+        self.get_client(client_id).await?;
+
+        if permissions.iter().any(|permission| {
+            permission.resource_server.is_empty()
+                || permission.resource.is_empty()
+                || permission.actions.iter().any(String::is_empty)
+        }) {
+            return Err(Error::InvalidClientMetadata(
+                "Permissions need a resource server, a resource and actions".to_string(),
+            ));
+        }
+
+        self.repo
+            .replace_permissions(client_id, &permissions)
+            .await?;
+        Ok(permissions)
+    }
+}
+
+// Rejects metadata that would leave the client unusable or unsafe
+fn check_client(client: &Client, config: &AppConfig) -> Result<()> {
+    let invalid = |message: &str| Err(Error::InvalidClientMetadata(message.to_string()));
+
+    if client.name.trim().is_empty() {
+        return invalid("Name is required");
+    }
+    // Redirect URIs must be absolute and have no fragment (RFC 6749 section 3.1.2)
+    if client
+        .redirect_uris
+        .iter()
+        .any(|uri| !uri.contains("://") || uri.contains('#'))
+    {
+        return invalid("Redirect URIs must be absolute and have no fragment");
+    }
+    if client.grant_types.contains(&GrantType::AuthorizationCode) && client.redirect_uris.is_empty()
+    {
+        return invalid("The authorization_code grant needs a redirect URI");
+    }
+    if client
+        .scopes
+        .iter()
+        .any(|scope| scope.is_empty() || scope.contains(char::is_whitespace))
+    {
+        return invalid("Scopes must be single words");
+    }
+    // Access ranges open our admin endpoints, they only go to first-party sessions
+    if client
+        .scopes
+        .iter()
+        .any(|scope| scope.parse::<AccessRange>().is_ok())
+    {
+        return invalid("The user and global scopes are reserved");
+    }
+    if client
+        .audiences
+        .iter()
+        .any(|audience| audience.trim().is_empty())
+    {
+        return invalid("Audiences must be client ids");
+    }
+    if [client.access_token_lifetime, client.refresh_token_lifetime]
+        .into_iter()
+        .flatten()
+        .any(|lifetime| lifetime <= 0)
+    {
+        return invalid("Token lifetimes must be positive");
+    }
+    // Signing keys and revocations are only kept as long as the longest token
+    if client.access_token_lifetime.is_some_and(|lifetime| {
+        i64::from(lifetime) > config.max_access_token_lifetime().num_seconds()
+    }) {
+        return invalid("Access token lifetime exceeds MAX_ACCESS_TOKEN_EXPIRATION");
+    }
+    if client
+        .jwks
+        .as_ref()
+        .is_some_and(|jwks| !client_assertion::is_supported_key_set(jwks))
+    {
+        return invalid("Keys must be public RSA, EC or OKP keys");
+    }
+    // Anyone could get tokens for a client that can't authenticate
+    if client.grant_types.contains(&GrantType::ClientCredentials) && client.is_public() {
+        return invalid("The client_credentials grant needs a confidential client");
+    }
+    Ok(())
+}
+
+fn apply_settings(client: &mut Client, settings: ClientSettings) {
+    client.name = settings.name;
+    client.redirect_uris = settings.redirect_uris;
+    client.grant_types = settings.grant_types;
+    client.scopes = settings.scopes;
+    client.audiences = settings.audiences;
+    client.access_token_lifetime = settings.access_token_lifetime;
+    client.refresh_token_lifetime = settings.refresh_token_lifetime;
+    client.jwks = settings.jwks;
+}
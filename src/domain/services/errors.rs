@@ -14,9 +14,51 @@ pub enum Error {
     #[error("User already exists")]
     UserAlreadyExists,
 
+    #[error("Invalid client")]
+    InvalidClient,
+
+    #[error("Client not found")]
+    ClientNotFound,
+
+    #[error("Invalid client metadata: {0}")]
+    InvalidClientMetadata(String),
+
+    #[error("Session limit can't be negative")]
+    InvalidSessionLimit,
+
+    #[error("Group not found")]
+    GroupNotFound,
+
+    #[error("Group already exists")]
+    GroupAlreadyExists,
+
+    #[error("Group name can't be empty")]
+    InvalidGroupName,
+
+    #[error("Member not found")]
+    MemberNotFound,
+
+    #[error("A group can't be a member of itself")]
+    GroupCycle,
+
+    #[error("Invalid resource: {0}")]
+    InvalidResource(String),
+
+    #[error("At most {0} checks can be made at once")]
+    TooManyChecks(usize),
+
     #[error("Internal server error")]
     InternalError,
 
+    #[error("Signing keys are not managed by the key store")]
+    KeyStoreDisabled,
+
+    #[error("No active signing key")]
+    NoActiveSigningKey,
+
+    #[error("Signing key error: {0}")]
+    KeyError(#[from] crate::utils::jwt_keys::KeyError),
+
     #[error("Repository error: {0}")]
     RepositoryError(#[from] crate::adapters::repositories::Error),
 }
@@ -26,7 +68,36 @@ impl From<Error> for AppError {
         match error {
             Error::UserNotFound => AppError::NotFound("User not found".to_string()),
             Error::UserAlreadyExists => AppError::BadRequest("User already exists".to_string()),
+            Error::InvalidClient => AppError::Unauthorized("Invalid client".to_string()),
+            Error::ClientNotFound => AppError::NotFound("Client not found".to_string()),
+            Error::InvalidClientMetadata(message) => AppError::BadRequest(message),
+            Error::InvalidSessionLimit => {
+                AppError::BadRequest("Session limit can't be negative".to_string())
+            }
+            Error::GroupNotFound => AppError::NotFound("Group not found".to_string()),
+            Error::GroupAlreadyExists => AppError::BadRequest("Group already exists".to_string()),
+            Error::InvalidGroupName => {
+                AppError::BadRequest("Group name can't be empty".to_string())
+            }
+            Error::MemberNotFound => AppError::NotFound("Member not found".to_string()),
+            Error::GroupCycle => AppError::BadRequest(
+                "A group can't be a member of itself, directly or through its members".to_string(),
+            ),
+            Error::InvalidResource(resource) => AppError::BadRequest(format!(
+                "Invalid resource {resource}, expected <type>/<name>"
+            )),
+            Error::TooManyChecks(max) => {
+                AppError::BadRequest(format!("At most {max} checks can be made at once"))
+            }
             Error::InternalError => AppError::Internal("Internal server error".to_string()),
+            Error::KeyStoreDisabled => {
+                AppError::BadRequest("Signing keys are not managed by the key store".to_string())
+            }
+            Error::NoActiveSigningKey => AppError::Internal("Internal server error".to_string()),
+            Error::KeyError(err) => {
+                error!("{}", err);
+                AppError::Internal("Internal server error".to_string())
+            }
             Error::RepositoryError(err) => {
                 error!("{}", err);
                 AppError::Internal("Internal server error".to_string())
@@ -0,0 +1,104 @@
+/* Group services module */
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::adapters::dtos::MemberAddition;
+use crate::adapters::repositories::{GroupRepository, PgGroupRepository, PgUserRepository};
+use crate::config::database::PgPool;
+use crate::domain::models::{Group, GroupMember, Principal};
+
+use super::errors::Error;
+
+type Result<T> = std::result::Result<T, Error>;
+
+pub struct GroupService {
+    repo: PgGroupRepository,
+    user_repo: PgUserRepository,
+}
+
+impl GroupService {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self {
+            repo: PgGroupRepository::new(db_pool.clone()),
+            user_repo: PgUserRepository::new(db_pool),
+        }
+    }
+
+    pub async fn create_group(&self, name: String, description: Option<String>) -> Result<Group> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(Error::InvalidGroupName);
+        }
+
+        let group = Group::new(name, description);
+        if !self.repo.create_group(&group).await? {
+            return Err(Error::GroupAlreadyExists);
+        }
+        Ok(group)
+    }
+
+    pub async fn list_groups(&self) -> Result<Vec<Group>> {
+        Ok(self.repo.list_groups().await?)
+    }
+
+    pub async fn get_group(&self, group_id: Uuid) -> Result<Group> {
+        self.repo
+            .find_group(group_id)
+            .await?
+            .ok_or(Error::GroupNotFound)
+    }
+
+    /// Deletes a group. Its members lose the roles they held through it.
+    pub async fn delete_group(&self, group_id: Uuid) -> Result<()> {
+        if !self.repo.delete_group(group_id).await? {
+            return Err(Error::GroupNotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn list_members(&self, group_id: Uuid) -> Result<Vec<GroupMember>> {
+        // Unknown groups are reported instead of listing nothing
+        self.get_group(group_id).await?;
+        Ok(self.repo.list_members(group_id).await?)
+    }
+
+    /// Adds a user or a group to a group. Returns `false` when it already is
+    /// a member. Groups can't end up containing themselves, so a group that
+    /// is a member of `group_id`, at any depth, can't become its parent.
+    pub async fn add_member(&self, group_id: Uuid, member: Principal) -> Result<bool> {
+        self.get_group(group_id).await?;
+        let member_exists = match member {
+            Principal::User(user_id) => self.user_repo.find_by_id(&user_id).await?.is_some(),
+            Principal::Group(member_id) => self.repo.find_group(member_id).await?.is_some(),
+        };
+        if !member_exists {
+            return Err(Error::MemberNotFound);
+        }
+
+        match self.repo.add_member(group_id, member).await? {
+            MemberAddition::Added => Ok(true),
+            MemberAddition::AlreadyMember => Ok(false),
+            MemberAddition::Cycle => Err(Error::GroupCycle),
+        }
+    }
+
+    pub async fn remove_member(&self, group_id: Uuid, member: Principal) -> Result<()> {
+        if !self.repo.remove_member(group_id, member).await? {
+            return Err(Error::MemberNotFound);
+        }
+        Ok(())
+    }
+
+    /// Every group a user belongs to, directly or through other groups.
+    pub async fn list_user_groups(&self, user_id: Uuid) -> Result<Vec<Group>> {
+        if self.user_repo.find_by_id(&user_id).await?.is_none() {
+            return Err(Error::UserNotFound);
+        }
+        Ok(self
+            .repo
+            .list_effective_groups(Principal::User(user_id))
+            .await?)
+    }
+}
@@ -0,0 +1,232 @@
+use std::fs::read_to_string;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::Algorithm;
+use tracing::{error, info};
+
+use crate::adapters::repositories::{PgSigningKeyRepository, SigningKeyRepository};
+use crate::config::app_config::{AppConfig, get_config};
+use crate::config::database::PgPool;
+use crate::domain::models::{KeyState, SigningKey};
+use crate::utils::jwt_keys::{JwtKey, JwtKeySet, KeyError};
+use crate::utils::key_encryption::KeyEncryptionUtil;
+
+use super::errors::{Error, Result};
+
+// How often each instance retires expired keys, rotates when due and reloads
+const ROTATION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Owns the JWT signing keys.
+///
+/// HS256 secrets and keys read from `JWT_PRIVATE_KEY_PATH` are static. Other
+/// asymmetric keys live in the key store, encrypted with `KEY_ENCRYPTION_KEY`,
+/// and are rotated on a schedule or on demand.
+pub struct KeyService {
+    jwt_keys: Arc<JwtKeySet>,
+    store: Option<KeyStore>,
+    config: &'static AppConfig,
+}
+
+struct KeyStore {
+    repository: PgSigningKeyRepository,
+    encryption: KeyEncryptionUtil,
+    algorithm: Algorithm,
+}
+
+impl KeyService {
+    pub async fn new(db: Arc<PgPool>) -> Result<Self> {
+        let config = get_config();
+        let algorithm = Algorithm::from_str(&config.jwt_algorithm)
+            .map_err(|_| KeyError::UnsupportedAlgorithm(config.jwt_algorithm.clone()))?;
+
+        let static_key = match (algorithm, &config.jwt_private_key_path) {
+            (Algorithm::HS256, _) => Some(JwtKey::hmac(&config.jwt_secret)),
+            (algorithm, Some(path)) => Some(JwtKey::from_pem(
+                algorithm,
+                &read_to_string(path).map_err(KeyError::from)?,
+            )?),
+            (_, None) => None,
+        };
+        if let Some(key) = static_key {
+            return Ok(Self {
+                jwt_keys: Arc::new(JwtKeySet::new(key)),
+                store: None,
+                config,
+            });
+        }
+
+        let encryption_key = config.key_encryption_key.as_deref().ok_or_else(|| {
+            KeyError::InvalidKey("KEY_ENCRYPTION_KEY must be set to store signing keys".into())
+        })?;
+        let store = KeyStore {
+            repository: PgSigningKeyRepository::new(db),
+            encryption: KeyEncryptionUtil::new(encryption_key)?,
+            algorithm,
+        };
+
+        // The first rotation creates a `next` key and the second promotes it.
+        // Both are no-ops once any instance has an active key.
+        for _ in 0..2 {
+            store
+                .rotate(Self::retire_after(config), Some(DateTime::<Utc>::MIN_UTC))
+                .await?;
+        }
+
+        let (signing_key, verification_keys) = store.load().await?;
+        Ok(Self {
+            jwt_keys: Arc::new(JwtKeySet::with_verification_keys(
+                signing_key,
+                verification_keys,
+            )),
+            store: Some(store),
+            config,
+        })
+    }
+
+    pub fn jwt_keys(&self) -> Arc<JwtKeySet> {
+        Arc::clone(&self.jwt_keys)
+    }
+
+    /// Lists every stored key, newest first.
+    pub async fn list_keys(&self) -> Result<Vec<SigningKey>> {
+        let store = self.store()?;
+
+        Ok(store.repository.list_keys().await?)
+    }
+
+    /// Rotates the keys immediately.
+    pub async fn rotate(&self) -> Result<()> {
+        let store = self.store()?;
+
+        store.rotate(Self::retire_after(self.config), None).await?;
+        info!("Signing keys rotated on demand");
+        self.reload().await
+    }
+
+    /// Retires keys whose tokens have expired, rotates the active key once it
+    /// is older than `KEY_ROTATION_INTERVAL` and picks up rotations made by
+    /// other instances.
+    pub async fn run_scheduled_rotation(&self) -> Result<()> {
+        let store = self.store()?;
+
+        let retired = store.repository.retire_expired_keys().await?;
+        if retired > 0 {
+            info!("Retired {retired} signing key(s)");
+        }
+
+        let rotate_before = Utc::now() - Duration::days(self.config.key_rotation_interval.into());
+        if store
+            .rotate(Self::retire_after(self.config), Some(rotate_before))
+            .await?
+        {
+            info!("Signing keys rotated on schedule");
+        }
+
+        self.reload().await
+    }
+
+    /// Runs the scheduled rotation in the background when keys are stored.
+    pub fn spawn_rotation_task(self: &Arc<Self>) {
+        if self.store.is_none() {
+            return;
+        }
+
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ROTATION_CHECK_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.run_scheduled_rotation().await {
+                    error!("Scheduled signing key rotation failed: {e}");
+                }
+            }
+        });
+    }
+
+    async fn reload(&self) -> Result<()> {
+        let (signing_key, verification_keys) = self.store()?.load().await?;
+        self.jwt_keys.replace(signing_key, verification_keys);
+        Ok(())
+    }
+
+    fn store(&self) -> Result<&KeyStore> {
+        self.store.as_ref().ok_or(Error::KeyStoreDisabled)
+    }
+
+    // A retiring key must outlive every access token it signed, whatever its
+    // client. Other instances keep signing with it until their next check.
+    fn retire_after(config: &AppConfig) -> DateTime<Utc> {
+        Utc::now()
+            + config.max_access_token_lifetime()
+            + Duration::seconds(config.jwt_leeway.into())
+            + Duration::from_std(ROTATION_CHECK_INTERVAL).expect("interval fits a Duration")
+    }
+}
+
+impl KeyStore {
+    async fn rotate(
+        &self,
+        retire_after: DateTime<Utc>,
+        activated_before: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        // Generating a key is costly, don't on every check of every instance
+        if let Some(activated_before) = activated_before
+            && !self.repository.is_rotation_due(activated_before).await?
+        {
+            return Ok(false);
+        }
+
+        let algorithm = self.algorithm;
+        let key = tokio::task::spawn_blocking(move || JwtKey::generate(algorithm))
+            .await
+            .map_err(|e| KeyError::Generation(e.to_string()))??;
+        let private_pem = key
+            .private_pem()
+            .ok_or_else(|| KeyError::Generation("Generated key has no private key".into()))?;
+
+        let new_key = SigningKey {
+            kid: key.kid.clone(),
+            algorithm: format!("{:?}", key.algorithm),
+            key_state: KeyState::Next,
+            encrypted_private_key: Some(self.encryption.encrypt(&key.kid, private_pem.as_bytes())?),
+            created_at: Utc::now(),
+            activated_at: None,
+            retire_after: None,
+            retired_at: None,
+        };
+
+        Ok(self
+            .repository
+            .rotate_keys(&new_key, retire_after, activated_before)
+            .await?)
+    }
+
+    /// Decrypts the usable keys into the active signing key and the `next`
+    /// and `retiring` keys accepted for verification.
+    async fn load(&self) -> Result<(JwtKey, Vec<JwtKey>)> {
+        let mut signing_key = None;
+        let mut verification_keys = Vec::new();
+
+        for stored in self.repository.list_usable_keys().await? {
+            let Some(encrypted) = &stored.encrypted_private_key else {
+                continue;
+            };
+            let algorithm = Algorithm::from_str(&stored.algorithm)
+                .map_err(|_| KeyError::UnsupportedAlgorithm(stored.algorithm.clone()))?;
+            let private_pem = String::from_utf8(self.encryption.decrypt(&stored.kid, encrypted)?)
+                .map_err(|e| KeyError::InvalidKey(e.to_string()))?;
+            let key = JwtKey::from_pem(algorithm, &private_pem)?;
+
+            match stored.key_state {
+                KeyState::Active => signing_key = Some(key),
+                _ => verification_keys.push(key),
+            }
+        }
+
+        let signing_key = signing_key.ok_or(Error::NoActiveSigningKey)?;
+        Ok((signing_key, verification_keys))
+    }
+}
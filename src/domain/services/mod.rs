@@ -1,11 +1,21 @@
 /* Application Services module */
 
 mod auth_service;
+mod authorization_service;
+mod client_service;
 mod email_service;
+mod group_service;
+mod key_service;
+mod oauth_service;
 mod user_service;
 
 pub mod errors;
 
 pub use auth_service::AuthService;
+pub use authorization_service::AuthorizationService;
+pub use client_service::ClientService;
 pub use email_service::EmailService;
+pub use group_service::GroupService;
+pub use key_service::KeyService;
+pub use oauth_service::{OAuthService, has_scope};
 pub use user_service::UserService;
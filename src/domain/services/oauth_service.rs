@@ -0,0 +1,584 @@
+/* OAuth services module */
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{Duration, Utc};
+use rand::{RngCore, rngs::OsRng};
+use sha2::{Digest, Sha256};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::adapters::dtos::{
+    AuthorizationRequest, ClientGrant, DeviceAuthorization, DeviceInfo, OpenIdRequest,
+    SessionTokens, TokenExchangeRequest,
+};
+use crate::adapters::repositories::{
+    AuthorizationCodeRepository, ClientRepository, DeviceCodeRepository,
+    PgAuthorizationCodeRepository, PgClientRepository, PgDeviceCodeRepository, PgUserRepository,
+};
+use crate::app_modules::auth::errors::Error;
+use crate::config::app_config::{AppConfig, get_config};
+use crate::config::database::PgPool;
+use crate::domain::models::{
+    Actor, Authentication, AuthorizationCode, Client, DeviceCode, ResourceAccess, RevocationReason,
+};
+use crate::utils::security_events::{self, SecurityEvent};
+use crate::utils::{pkce, user_code};
+
+use super::AuthService;
+
+type Result<T> = std::result::Result<T, Error>;
+
+const AUTHORIZATION_CODE_BYTES: usize = 32;
+const DEVICE_CODE_BYTES: usize = 32;
+// User codes are short enough to collide, a new one is drawn this many times
+const USER_CODE_ATTEMPTS: usize = 3;
+// Seconds added to the polling interval of a device polling too fast (RFC 8628 section 3.5)
+const SLOW_DOWN_STEP: i32 = 5;
+// Session device type of the devices signed in through the device flow
+const DEVICE_FLOW_DEVICE_TYPE: &str = "CLI";
+
+/// Runs the OAuth grants of the token endpoint.
+pub struct OAuthService {
+    codes: PgAuthorizationCodeRepository,
+    device_codes: PgDeviceCodeRepository,
+    client_repository: PgClientRepository,
+    user_repository: PgUserRepository,
+    auth_service: Arc<AuthService>,
+    config: &'static AppConfig,
+}
+
+impl OAuthService {
+    pub fn new(db: Arc<PgPool>, auth_service: Arc<AuthService>) -> Self {
+        Self {
+            codes: PgAuthorizationCodeRepository::new(db.clone()),
+            device_codes: PgDeviceCodeRepository::new(db.clone()),
+            client_repository: PgClientRepository::new(db.clone()),
+            user_repository: PgUserRepository::new(db),
+            auth_service,
+            config: get_config(),
+        }
+    }
+
+    /// Issues a single-use authorization code for a user who approved `request`
+    /// after signing in with `authentication`.
+    pub async fn issue_authorization_code(
+        &self,
+        request: &AuthorizationRequest,
+        user_id: Uuid,
+        authentication: Authentication,
+    ) -> Result<String> {
+        let max_age = request
+            .max_age
+            .map(|max_age| Duration::seconds(max_age.into()));
+        if !authentication.meets(max_age, request.acr, Utc::now()) {
+            return Err(Error::ReauthenticationRequired {
+                max_age: request.max_age.map(i64::from),
+                acr: request.acr,
+            });
+        }
+
+        let mut secret = [0u8; AUTHORIZATION_CODE_BYTES];
+        OsRng.fill_bytes(&mut secret);
+        let code = URL_SAFE_NO_PAD.encode(secret);
+
+        let now = Utc::now();
+        let authorization_code = AuthorizationCode {
+            code_hash: code_digest(&code),
+            client_id: request.client_id.clone(),
+            user_id,
+            redirect_uri: request.redirect_uri.clone(),
+            scope: request.scope.clone(),
+            nonce: request.nonce.clone(),
+            code_challenge: request.code_challenge.clone(),
+            acr: authentication.acr,
+            auth_time: authentication.auth_time,
+            max_age: request.max_age,
+            expires_at: now + Duration::seconds(self.config.authorization_code_expiration.into()),
+            consumed_at: None,
+            session_id: None,
+            created_at: now,
+        };
+
+        self.codes
+            .create_code(&authorization_code)
+            .await
+            .map_err(|e| {
+                error!("Failed to store authorization code: {e}");
+                Error::InternalError
+            })?;
+
+        Ok(code)
+    }
+
+    /// Exchanges an authorization code for a new session (RFC 6749 section 4.1.3).
+    ///
+    /// The code must have been issued to `client` for `redirect_uri`, and
+    /// `code_verifier` must match its PKCE challenge. Presenting a code twice
+    /// revokes the session it was first exchanged for.
+    pub async fn exchange_authorization_code(
+        &self,
+        client: &Client,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: &str,
+        ip: IpAddr,
+        device_info: DeviceInfo,
+    ) -> Result<(SessionTokens, String)> {
+        let code_hash = code_digest(code);
+
+        let Some(authorization_code) = self.codes.consume_code(&code_hash).await.map_err(|e| {
+            error!("Failed to consume authorization code: {e}");
+            Error::InternalError
+        })?
+        else {
+            return Err(self.handle_authorization_code_reuse(&code_hash).await);
+        };
+
+        if authorization_code.client_id != client.client_id
+            || authorization_code.redirect_uri != redirect_uri
+            || !pkce::verify_s256(code_verifier, &authorization_code.code_challenge)
+        {
+            return Err(Error::InvalidGrant);
+        }
+
+        let user = self
+            .user_repository
+            .find_auth_user_by_id(&authorization_code.user_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load authorization code user: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::InvalidGrant)?;
+
+        // The sign-in may have grown older than the client allows since
+        let authentication = Authentication {
+            acr: authorization_code.acr,
+            auth_time: authorization_code.auth_time,
+        };
+        let max_age = authorization_code
+            .max_age
+            .map(|max_age| Duration::seconds(max_age.into()));
+        if !authentication.meets(max_age, None, Utc::now()) {
+            return Err(Error::InvalidGrant);
+        }
+
+        let openid = has_scope(&authorization_code.scope, "openid").then(|| OpenIdRequest {
+            nonce: authorization_code.nonce.clone(),
+        });
+        let tokens = self
+            .auth_service
+            .make_session(
+                user,
+                ip,
+                device_info,
+                openid,
+                Some(ClientGrant {
+                    client,
+                    scope: &authorization_code.scope,
+                }),
+                authentication,
+            )
+            .await?;
+
+        self.codes
+            .attach_session(&code_hash, tokens.session_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to link authorization code to its session: {e}");
+                Error::InternalError
+            })?;
+
+        Ok((tokens, authorization_code.scope))
+    }
+
+    /// Starts a device authorization (RFC 8628 section 3.1) for `client`.
+    /// The device polls with the device code while the user approves the
+    /// user code from another, signed in, browser.
+    pub async fn start_device_authorization(
+        &self,
+        client: &Client,
+        scope: &str,
+    ) -> Result<DeviceAuthorization> {
+        let mut secret = [0u8; DEVICE_CODE_BYTES];
+        OsRng.fill_bytes(&mut secret);
+        let device_code = URL_SAFE_NO_PAD.encode(secret);
+
+        let now = Utc::now();
+        let expires_in = i64::from(self.config.device_code_expiration);
+        let interval = i32::from(self.config.device_code_interval);
+        let mut request = DeviceCode {
+            device_code_hash: code_digest(&device_code),
+            user_code: user_code::generate(),
+            client_id: client.client_id.clone(),
+            scope: scope.to_string(),
+            polling_interval: interval,
+            last_polled_at: None,
+            user_id: None,
+            approved_at: None,
+            denied_at: None,
+            acr: None,
+            auth_time: None,
+            expires_at: now + Duration::seconds(expires_in),
+            consumed_at: None,
+            session_id: None,
+            created_at: now,
+        };
+
+        let mut attempts = 1;
+        while !self
+            .device_codes
+            .create_device_code(&request)
+            .await
+            .map_err(|e| {
+                error!("Failed to store device code: {e}");
+                Error::InternalError
+            })?
+        {
+            if attempts == USER_CODE_ATTEMPTS {
+                error!("No free device user code after {attempts} attempts");
+                return Err(Error::InternalError);
+            }
+            attempts += 1;
+            request.user_code = user_code::generate();
+        }
+
+        Ok(DeviceAuthorization {
+            device_code,
+            user_code: request.user_code,
+            expires_in,
+            interval: interval.into(),
+        })
+    }
+
+    /// Finds the pending device authorization a user typed the code of, so
+    /// they can see which client asks for access before approving it.
+    pub async fn find_device_authorization(
+        &self,
+        typed_user_code: &str,
+    ) -> Result<(DeviceCode, Client)> {
+        let user_code = user_code::normalize(typed_user_code).ok_or(Error::InvalidGrant)?;
+
+        let request = self
+            .device_codes
+            .find_pending(&user_code)
+            .await
+            .map_err(|e| {
+                error!("Failed to load device code: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::InvalidGrant)?;
+        let client = self
+            .client_repository
+            .find_by_client_id(&request.client_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load device code client: {e}");
+                Error::InternalError
+            })?
+            .filter(|client| client.is_active)
+            .ok_or(Error::InvalidGrant)?;
+
+        Ok((request, client))
+    }
+
+    /// Records a signed in user's decision on a pending device authorization.
+    /// The device's session gets the `acr` and `auth_time` of the session that
+    /// approved it.
+    pub async fn decide_device_authorization(
+        &self,
+        typed_user_code: &str,
+        user_id: Uuid,
+        approved: bool,
+        authentication: Authentication,
+    ) -> Result<()> {
+        let user_code = user_code::normalize(typed_user_code).ok_or(Error::InvalidGrant)?;
+
+        let decided = self
+            .device_codes
+            .decide(&user_code, user_id, approved, authentication)
+            .await
+            .map_err(|e| {
+                error!("Failed to record device code decision: {e}");
+                Error::InternalError
+            })?;
+
+        if !decided {
+            return Err(Error::InvalidGrant);
+        }
+        Ok(())
+    }
+
+    /// Answers a device polling the token endpoint (RFC 8628 section 3.4).
+    ///
+    /// Until the user decides, the device is told to keep waiting, or to slow
+    /// down when it polls faster than its interval. Once approved, the device
+    /// code is exchanged for a new session, once.
+    pub async fn exchange_device_code(
+        &self,
+        client: &Client,
+        device_code: &str,
+        ip: IpAddr,
+        device_info: DeviceInfo,
+    ) -> Result<(SessionTokens, String)> {
+        let device_code_hash = code_digest(device_code);
+
+        let (request, previous_poll) = self
+            .device_codes
+            .record_poll(&device_code_hash)
+            .await
+            .map_err(|e| {
+                error!("Failed to record device code poll: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::InvalidGrant)?;
+
+        if request.client_id != client.client_id || request.consumed_at.is_some() {
+            return Err(Error::InvalidGrant);
+        }
+        if request.expires_at <= Utc::now() {
+            return Err(Error::DeviceCodeExpired);
+        }
+        if request.denied_at.is_some() {
+            return Err(Error::AccessDenied);
+        }
+        if previous_poll.is_some_and(|polled_at| {
+            Utc::now() - polled_at < Duration::seconds(request.polling_interval.into())
+        }) {
+            self.device_codes
+                .slow_down(&device_code_hash, SLOW_DOWN_STEP)
+                .await
+                .map_err(|e| {
+                    error!("Failed to slow down device code polling: {e}");
+                    Error::InternalError
+                })?;
+            return Err(Error::SlowDown);
+        }
+        if request.approved_at.is_none() {
+            return Err(Error::AuthorizationPending);
+        }
+
+        // Consuming guards against two polls racing for the same approval
+        let request = self
+            .device_codes
+            .consume_device_code(&device_code_hash)
+            .await
+            .map_err(|e| {
+                error!("Failed to consume device code: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::InvalidGrant)?;
+        let user_id = request.user_id.ok_or(Error::InvalidGrant)?;
+        let authentication = Authentication {
+            acr: request.acr.ok_or(Error::InvalidGrant)?,
+            auth_time: request.auth_time.ok_or(Error::InvalidGrant)?,
+        };
+
+        let user = self
+            .user_repository
+            .find_auth_user_by_id(&user_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load device code user: {e}");
+                Error::InternalError
+            })?
+            .ok_or(Error::InvalidGrant)?;
+
+        let device_info = DeviceInfo {
+            device_type: DEVICE_FLOW_DEVICE_TYPE.to_string(),
+            ..device_info
+        };
+        let openid = has_scope(&request.scope, "openid").then(OpenIdRequest::default);
+        let tokens = self
+            .auth_service
+            .make_session(
+                user,
+                ip,
+                device_info,
+                openid,
+                Some(ClientGrant {
+                    client,
+                    scope: &request.scope,
+                }),
+                authentication,
+            )
+            .await?;
+
+        self.device_codes
+            .attach_session(&device_code_hash, tokens.session_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to link device code to its session: {e}");
+                Error::InternalError
+            })?;
+
+        Ok((tokens, request.scope))
+    }
+
+    /// Issues a token a client holds on its own behalf (RFC 6749 section 4.4).
+    /// Its `resource_access` lists the permissions granted to the client.
+    pub async fn client_credentials(&self, client: &Client, scope: &str) -> Result<String> {
+        let permissions = self
+            .client_repository
+            .list_permissions(&client.client_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to load client permissions: {e}");
+                Error::InternalError
+            })?;
+
+        let mut resource_access = ResourceAccess::new();
+        for permission in permissions {
+            resource_access
+                .entry(permission.resource_server)
+                .or_default()
+                .insert(permission.resource, permission.actions);
+        }
+
+        self.auth_service
+            .issue_client_token(client, scope, resource_access)
+    }
+
+    /// Swaps a user's access token for one meant for another audience, with
+    /// at most the same scope (RFC 8693). With an actor token, the new token
+    /// names the actor in its `act` claim, for delegated calls, ahead of the
+    /// actors the subject token already names; without one, the client simply
+    /// impersonates the subject.
+    ///
+    /// The client may only ask for scopes and other audiences it is registered
+    /// for. Without a requested scope, the subject's scopes the client is
+    /// registered for are kept.
+    ///
+    /// Returns the token, its lifetime in seconds and its scope.
+    pub async fn exchange_token(
+        &self,
+        client: &Client,
+        request: TokenExchangeRequest,
+    ) -> Result<(String, i64, String)> {
+        let subject = self
+            .auth_service
+            .verify_exchangeable_token(&request.subject_token, &client.client_id)
+            .await?;
+
+        let act = match request.actor_token.as_deref() {
+            Some(actor_token) => {
+                let actor = self
+                    .auth_service
+                    .verify_exchangeable_token(actor_token, &client.client_id)
+                    .await?;
+                // The new actor is the current one, the subject's ones came before
+                Some(Actor {
+                    sub: actor.sub,
+                    act: subject.act.clone().map(Box::new),
+                })
+            }
+            None => subject.act.clone(),
+        };
+
+        // Other audiences must be registered, and only get their own permissions
+        let (audience, resource_access) = match request.audience {
+            Some(audience) if audience != subject.aud => {
+                if !client.allows_audience(&audience) {
+                    return Err(Error::InvalidTarget);
+                }
+                let target = self
+                    .client_repository
+                    .find_by_client_id(&audience)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to load token exchange audience: {e}");
+                        Error::InternalError
+                    })?
+                    .filter(|target| target.is_active)
+                    .ok_or(Error::InvalidTarget)?;
+
+                let resource_access: ResourceAccess = subject
+                    .resource_access
+                    .get(&target.client_id)
+                    .map(|access| (target.client_id.clone(), access.clone()))
+                    .into_iter()
+                    .collect();
+                (target.client_id, resource_access)
+            }
+            _ => (subject.aud.clone(), subject.resource_access.clone()),
+        };
+
+        let scope = match request.scope {
+            Some(scope) => {
+                if !client.allows_scopes(&scope)
+                    || !scope
+                        .split_whitespace()
+                        .all(|s| has_scope(&subject.scope, s))
+                {
+                    return Err(Error::InvalidScope);
+                }
+                scope
+            }
+            None => subject
+                .scope
+                .split_whitespace()
+                .filter(|s| client.allows_scopes(s))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+
+        let (access_token, expires_in) = self.auth_service.issue_exchanged_token(
+            client,
+            &subject,
+            &audience,
+            &scope,
+            resource_access,
+            act,
+        )?;
+
+        Ok((access_token, expires_in, scope))
+    }
+
+    /// A code that was already exchanged may have been intercepted, so the
+    /// session created from it is revoked.
+    async fn handle_authorization_code_reuse(&self, code_hash: &str) -> Error {
+        let authorization_code = match self.codes.find_code(code_hash).await {
+            Ok(Some(code)) if code.consumed_at.is_some() => code,
+            Ok(_) => return Error::InvalidGrant,
+            Err(e) => {
+                error!("Failed to load authorization code: {e}");
+                return Error::InternalError;
+            }
+        };
+
+        security_events::emit(SecurityEvent::AuthorizationCodeReuse {
+            client_id: authorization_code.client_id,
+            user_id: authorization_code.user_id,
+            session_id: authorization_code.session_id,
+        });
+
+        if let Some(session_id) = authorization_code.session_id
+            && let Err(e) = self
+                .auth_service
+                .revoke_session(session_id, RevocationReason::AuthorizationCodeReuse)
+                .await
+        {
+            return e;
+        }
+
+        Error::InvalidGrant
+    }
+}
+
+/// Whether a space separated scope string contains `scope`.
+pub fn has_scope(scopes: &str, scope: &str) -> bool {
+    scopes
+        .split_whitespace()
+        .any(|requested| requested == scope)
+}
+
+// Codes are stored like refresh tokens, as a hex encoded SHA-256 digest
+fn code_digest(code: &str) -> String {
+    Sha256::digest(code.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
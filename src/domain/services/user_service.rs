@@ -1,12 +1,14 @@
 /* User services module */
 
 use std::sync::Arc;
+use tracing::error;
 use uuid::Uuid;
 
 use crate::adapters::dtos::AuthUserDto;
 use crate::adapters::repositories::{PgUserRepository, UserRepository};
 use crate::config::database::PgPool;
 use crate::domain::models::User;
+use crate::utils::PasswordUtil;
 
 use super::errors::Error;
 
@@ -14,12 +16,14 @@ type Result<T> = std::result::Result<T, Error>;
 
 pub struct UserService {
     repo: PgUserRepository,
+    password_util: PasswordUtil,
 }
 
 impl UserService {
     pub fn new(db_pool: Arc<PgPool>) -> Self {
         Self {
             repo: PgUserRepository::new(db_pool),
+            password_util: PasswordUtil::new(),
         }
     }
 
@@ -28,6 +32,44 @@ impl UserService {
         Ok(auth_user)
     }
 
+    /// Overrides the server-wide session limit for one user; `None` restores
+    /// the default and 0 lifts the limit. Users are the only level limits are
+    /// overridden at, as there is no tenant model to hold a shared one.
+    pub async fn set_session_limit(&self, id: &Uuid, max_sessions: Option<i32>) -> Result<()> {
+        if max_sessions.is_some_and(|max_sessions| max_sessions < 0) {
+            return Err(Error::InvalidSessionLimit);
+        }
+        if !self.repo.set_max_sessions(id, max_sessions).await? {
+            return Err(Error::UserNotFound);
+        }
+        Ok(())
+    }
+
+    /// Replaces the user's password.
+    pub async fn change_password(&self, id: &Uuid, password: &str) -> Result<()> {
+        let password_hash = self.password_util.hash_password(password).map_err(|e| {
+            error!("Failed to hash password: {e}");
+            Error::InternalError
+        })?;
+        if !self.repo.set_password_hash(id, &password_hash).await? {
+            return Err(Error::UserNotFound);
+        }
+        Ok(())
+    }
+
+    /// Deletes the user's account, signing them out everywhere.
+    pub async fn delete_user(&self, id: &Uuid) -> Result<()> {
+        if !self.repo.delete(id).await? {
+            return Err(Error::UserNotFound);
+        }
+        Ok(())
+    }
+
+    pub async fn find_user_by_id(&self, id: &Uuid) -> Result<Option<User>> {
+        let user = self.repo.find_by_id(id).await?;
+        Ok(user)
+    }
+
     pub async fn user_exists(&self, email: &str) -> Result<bool> {
         let exists = self.repo.email_exists(email).await?;
         Ok(exists)
@@ -27,6 +27,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await
         .expect("Failed to bind to address");
     let app = app::build_app(Arc::new(db_connection_pool.clone()))
+        .await
         .into_make_service_with_connect_info::<SocketAddr>();
 
     tracing::info!("listening on {}", listener.local_addr().unwrap());
@@ -0,0 +1,161 @@
+use jsonwebtoken::jwk::{AlgorithmParameters, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use serde::Deserialize;
+
+/// `client_assertion_type` of JWT client assertions (RFC 7523 section 2.2).
+pub const JWT_BEARER: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+/// Assertions must be signed with a client's private key, never a shared secret.
+pub const ALGORITHMS: [Algorithm; 3] = [Algorithm::RS256, Algorithm::ES256, Algorithm::EdDSA];
+
+/// The claims of a verified client assertion we rely on.
+#[derive(Debug, Deserialize)]
+pub struct ClientAssertionClaims {
+    pub sub: String,
+    pub exp: i64,
+    pub jti: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UnverifiedSubject {
+    sub: String,
+}
+
+/// Reads the client id an assertion claims to come from, without verifying it.
+/// Only used to find the keys the assertion must be verified with.
+pub fn unverified_subject(assertion: &str) -> Option<String> {
+    let mut validation = Validation::new(decode_header(assertion).ok()?.alg);
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation.required_spec_claims.clear();
+
+    decode::<UnverifiedSubject>(assertion, &DecodingKey::from_secret(&[]), &validation)
+        .ok()
+        .map(|data| data.claims.sub)
+}
+
+/// Verifies a `private_key_jwt` assertion (RFC 7523 section 3): signed by one
+/// of the client's keys, issued by and about `client_id`, addressed to one of
+/// `audiences` and not expired.
+pub fn verify(
+    assertion: &str,
+    client_id: &str,
+    jwks: &JwkSet,
+    audiences: &[String],
+    leeway: u64,
+) -> Option<ClientAssertionClaims> {
+    let header = decode_header(assertion).ok()?;
+    if !ALGORITHMS.contains(&header.alg) {
+        return None;
+    }
+
+    // Without a `kid` the client must have registered a single key
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => jwks.find(kid)?,
+        None if jwks.keys.len() == 1 => &jwks.keys[0],
+        None => return None,
+    };
+    let key = DecodingKey::from_jwk(jwk).ok()?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.leeway = leeway;
+    validation.set_issuer(&[client_id]);
+    validation.sub = Some(client_id.to_string());
+    validation.set_audience(audiences);
+    validation.set_required_spec_claims(&["exp", "iss", "sub", "aud"]);
+
+    decode::<ClientAssertionClaims>(assertion, &key, &validation)
+        .ok()
+        .map(|data| data.claims)
+}
+
+/// Whether a client can register this key set: at least one key, and only
+/// public keys we can verify assertions with.
+pub fn is_supported_key_set(jwks: &JwkSet) -> bool {
+    !jwks.keys.is_empty()
+        && jwks.keys.iter().all(|jwk| {
+            !matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_))
+                && DecodingKey::from_jwk(jwk).is_ok()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::jwt_keys::{JwkSet as PublishedJwkSet, JwtKey};
+    use chrono::Utc;
+    use jsonwebtoken::encode;
+    use serde_json::{Value, json};
+
+    const TOKEN_ENDPOINT: &str = "https://auth.example.com/api/v1/oauth/token";
+
+    fn key_set(key: &JwtKey) -> JwkSet {
+        let published = PublishedJwkSet {
+            keys: vec![key.jwk().unwrap().clone()],
+        };
+        serde_json::from_value(serde_json::to_value(published).unwrap()).unwrap()
+    }
+
+    fn assertion(key: &JwtKey, claims: Value) -> String {
+        encode(&key.header(), &claims, key.encoding_key()).unwrap()
+    }
+
+    fn claims(client_id: &str) -> Value {
+        json!({
+            "iss": client_id,
+            "sub": client_id,
+            "aud": TOKEN_ENDPOINT,
+            "exp": Utc::now().timestamp() + 60,
+            "jti": "assertion-1",
+        })
+    }
+
+    #[test]
+    fn test_valid_assertion_is_verified() {
+        let key = JwtKey::generate(Algorithm::ES256).unwrap();
+        let token = assertion(&key, claims("backend"));
+
+        assert_eq!(unverified_subject(&token).as_deref(), Some("backend"));
+
+        let verified = verify(
+            &token,
+            "backend",
+            &key_set(&key),
+            &[TOKEN_ENDPOINT.to_string()],
+            0,
+        )
+        .unwrap();
+        assert_eq!(verified.jti, "assertion-1");
+    }
+
+    #[test]
+    fn test_assertion_from_other_key_or_client_is_rejected() {
+        let key = JwtKey::generate(Algorithm::ES256).unwrap();
+        let other_key = JwtKey::generate(Algorithm::ES256).unwrap();
+        let audiences = [TOKEN_ENDPOINT.to_string()];
+
+        let forged = assertion(&other_key, claims("backend"));
+        assert!(verify(&forged, "backend", &key_set(&key), &audiences, 0).is_none());
+
+        let other_client = assertion(&key, claims("other"));
+        assert!(verify(&other_client, "backend", &key_set(&key), &audiences, 0).is_none());
+
+        let token = assertion(&key, claims("backend"));
+        let elsewhere = ["https://other.example.com/token".to_string()];
+        assert!(verify(&token, "backend", &key_set(&key), &elsewhere, 0).is_none());
+    }
+
+    #[test]
+    fn test_shared_secret_assertions_are_rejected() {
+        let key = JwtKey::hmac("secret");
+        let token = assertion(&key, claims("backend"));
+        let jwks: JwkSet = serde_json::from_value(json!({
+            "keys": [{ "kty": "oct", "k": "c2VjcmV0" }]
+        }))
+        .unwrap();
+
+        assert!(!is_supported_key_set(&jwks));
+        assert!(verify(&token, "backend", &jwks, &[TOKEN_ENDPOINT.to_string()], 0).is_none());
+    }
+}
@@ -0,0 +1,422 @@
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, decode_header};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rand::rngs::OsRng;
+use rsa::pkcs8::{DecodePrivateKey, EncodePrivateKey, LineEnding};
+use rsa::traits::PublicKeyParts;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+const HMAC_KEY_ID: &str = "hs256";
+const RSA_KEY_BITS: usize = 2048;
+
+/// Signing key related errors
+#[derive(Debug, Error)]
+pub enum KeyError {
+    #[error("Unsupported JWT algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    #[error("Invalid private key: {0}")]
+    InvalidKey(String),
+
+    #[error("Failed to read private key file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to generate key: {0}")]
+    Generation(String),
+
+    #[error("Key encryption failed")]
+    Encryption,
+}
+
+/// A public key in JWK format (RFC 7517).
+#[derive(Debug, Clone, Serialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub alg: String,
+    pub kid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+/// The document served at `/.well-known/jwks.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+/// A key used to sign and verify JWTs.
+///
+/// Asymmetric keys expose their public half as a JWK; HMAC keys never do.
+pub struct JwtKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    jwk: Option<Jwk>,
+    private_pem: Option<String>,
+}
+
+impl JwtKey {
+    /// Creates an HS256 key from a shared secret.
+    pub fn hmac(secret: &str) -> Self {
+        Self {
+            kid: HMAC_KEY_ID.to_string(),
+            algorithm: Algorithm::HS256,
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+            jwk: None,
+            private_pem: None,
+        }
+    }
+
+    /// Loads an asymmetric key from a PKCS#8 PEM encoded private key.
+    /// The public key and `kid` are derived from it.
+    pub fn from_pem(algorithm: Algorithm, private_pem: &str) -> Result<Self, KeyError> {
+        let invalid = |e: &dyn std::fmt::Display| KeyError::InvalidKey(e.to_string());
+
+        let (encoding_key, decoding_key, jwk) = match algorithm {
+            Algorithm::RS256 => {
+                let key =
+                    rsa::RsaPrivateKey::from_pkcs8_pem(private_pem).map_err(|e| invalid(&e))?;
+                let n = URL_SAFE_NO_PAD.encode(key.n().to_bytes_be());
+                let e = URL_SAFE_NO_PAD.encode(key.e().to_bytes_be());
+
+                let thumbprint = format!(r#"{{"e":"{e}","kty":"RSA","n":"{n}"}}"#);
+                let jwk = Jwk {
+                    n: Some(n.clone()),
+                    e: Some(e.clone()),
+                    ..Jwk::new("RSA", algorithm, &thumbprint)
+                };
+
+                (
+                    EncodingKey::from_rsa_pem(private_pem.as_bytes()).map_err(|e| invalid(&e))?,
+                    DecodingKey::from_rsa_components(&n, &e).map_err(|e| invalid(&e))?,
+                    jwk,
+                )
+            }
+            Algorithm::ES256 => {
+                let key = p256::SecretKey::from_pkcs8_pem(private_pem).map_err(|e| invalid(&e))?;
+                let point = key.public_key().to_encoded_point(false);
+                let (x, y) = match (point.x(), point.y()) {
+                    (Some(x), Some(y)) => (URL_SAFE_NO_PAD.encode(x), URL_SAFE_NO_PAD.encode(y)),
+                    _ => return Err(KeyError::InvalidKey("Invalid EC public key".to_string())),
+                };
+
+                let thumbprint = format!(r#"{{"crv":"P-256","kty":"EC","x":"{x}","y":"{y}"}}"#);
+                let jwk = Jwk {
+                    crv: Some("P-256".to_string()),
+                    x: Some(x.clone()),
+                    y: Some(y.clone()),
+                    ..Jwk::new("EC", algorithm, &thumbprint)
+                };
+
+                (
+                    EncodingKey::from_ec_pem(private_pem.as_bytes()).map_err(|e| invalid(&e))?,
+                    DecodingKey::from_ec_components(&x, &y).map_err(|e| invalid(&e))?,
+                    jwk,
+                )
+            }
+            Algorithm::EdDSA => {
+                let key = ed25519_dalek::SigningKey::from_pkcs8_pem(private_pem)
+                    .map_err(|e| invalid(&e))?;
+                let x = URL_SAFE_NO_PAD.encode(key.verifying_key().to_bytes());
+
+                let thumbprint = format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{x}"}}"#);
+                let jwk = Jwk {
+                    crv: Some("Ed25519".to_string()),
+                    x: Some(x.clone()),
+                    ..Jwk::new("OKP", algorithm, &thumbprint)
+                };
+
+                (
+                    EncodingKey::from_ed_pem(private_pem.as_bytes()).map_err(|e| invalid(&e))?,
+                    DecodingKey::from_ed_components(&x).map_err(|e| invalid(&e))?,
+                    jwk,
+                )
+            }
+            other => return Err(KeyError::UnsupportedAlgorithm(format!("{other:?}"))),
+        };
+
+        Ok(Self {
+            kid: jwk.kid.clone(),
+            algorithm,
+            encoding_key,
+            decoding_key,
+            jwk: Some(jwk),
+            private_pem: Some(private_pem.to_string()),
+        })
+    }
+
+    /// Generates a fresh asymmetric key for the algorithm.
+    pub fn generate(algorithm: Algorithm) -> Result<Self, KeyError> {
+        let generation = |e: &dyn std::fmt::Display| KeyError::Generation(e.to_string());
+
+        let private_pem = match algorithm {
+            Algorithm::RS256 => rsa::RsaPrivateKey::new(&mut OsRng, RSA_KEY_BITS)
+                .map_err(|e| generation(&e))?
+                .to_pkcs8_pem(LineEnding::LF)
+                .map_err(|e| generation(&e))?,
+            Algorithm::ES256 => p256::SecretKey::random(&mut OsRng)
+                .to_pkcs8_pem(LineEnding::LF)
+                .map_err(|e| generation(&e))?,
+            Algorithm::EdDSA => ed25519_dalek::SigningKey::generate(&mut OsRng)
+                .to_pkcs8_pem(LineEnding::LF)
+                .map_err(|e| generation(&e))?,
+            other => return Err(KeyError::UnsupportedAlgorithm(format!("{other:?}"))),
+        };
+
+        Self::from_pem(algorithm, &private_pem)
+    }
+
+    /// A JWT header carrying this key's algorithm and `kid`.
+    pub fn header(&self) -> Header {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.kid.clone());
+        header
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding_key
+    }
+
+    pub fn decoding_key(&self) -> &DecodingKey {
+        &self.decoding_key
+    }
+
+    /// The public key, `None` for symmetric keys.
+    pub fn jwk(&self) -> Option<&Jwk> {
+        self.jwk.as_ref()
+    }
+
+    /// The PEM encoded private key, `None` for symmetric keys.
+    pub fn private_pem(&self) -> Option<&str> {
+        self.private_pem.as_deref()
+    }
+}
+
+impl Jwk {
+    /// A signing JWK whose `kid` is the RFC 7638 thumbprint of its required members.
+    fn new(kty: &str, algorithm: Algorithm, thumbprint_input: &str) -> Self {
+        Self {
+            kty: kty.to_string(),
+            key_use: "sig".to_string(),
+            alg: format!("{algorithm:?}"),
+            kid: URL_SAFE_NO_PAD.encode(Sha256::digest(thumbprint_input.as_bytes())),
+            n: None,
+            e: None,
+            crv: None,
+            x: None,
+            y: None,
+        }
+    }
+}
+
+/// The keys gandalf signs tokens with and accepts tokens from.
+///
+/// Shared by every request and swapped as a whole when keys rotate.
+pub struct JwtKeySet {
+    ring: RwLock<KeyRing>,
+}
+
+struct KeyRing {
+    signing_key: Arc<JwtKey>,
+    keys: Vec<Arc<JwtKey>>,
+}
+
+impl JwtKeySet {
+    pub fn new(signing_key: JwtKey) -> Self {
+        Self::with_verification_keys(signing_key, Vec::new())
+    }
+
+    /// A key set that also accepts tokens signed by `verification_keys`.
+    pub fn with_verification_keys(signing_key: JwtKey, verification_keys: Vec<JwtKey>) -> Self {
+        Self {
+            ring: RwLock::new(KeyRing::new(signing_key, verification_keys)),
+        }
+    }
+
+    /// Replaces the signing key and the extra keys accepted for verification.
+    pub fn replace(&self, signing_key: JwtKey, verification_keys: Vec<JwtKey>) {
+        let ring = KeyRing::new(signing_key, verification_keys);
+        *self.ring.write().expect("JWT key set lock poisoned") = ring;
+    }
+
+    /// The key new tokens are signed with.
+    pub fn signing_key(&self) -> Arc<JwtKey> {
+        Arc::clone(&self.read().signing_key)
+    }
+
+    /// Finds the key a token claims to be signed with.
+    /// Tokens without a `kid` are checked against the signing key.
+    pub fn verification_key(&self, token: &str) -> Option<Arc<JwtKey>> {
+        let header = decode_header(token).ok()?;
+        let ring = self.read();
+
+        match header.kid {
+            Some(kid) => ring.keys.iter().find(|key| key.kid == kid).cloned(),
+            None => Some(Arc::clone(&ring.signing_key)),
+        }
+    }
+
+    /// The public keys to publish, symmetric keys are left out.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .read()
+                .keys
+                .iter()
+                .filter_map(|key| key.jwk().cloned())
+                .collect(),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, KeyRing> {
+        self.ring.read().expect("JWT key set lock poisoned")
+    }
+}
+
+impl KeyRing {
+    fn new(signing_key: JwtKey, verification_keys: Vec<JwtKey>) -> Self {
+        let signing_key = Arc::new(signing_key);
+        let mut keys = vec![Arc::clone(&signing_key)];
+        keys.extend(
+            verification_keys
+                .into_iter()
+                .filter(|key| key.kid != signing_key.kid)
+                .map(Arc::new),
+        );
+
+        Self { signing_key, keys }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{Validation, decode, encode};
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+        exp: i64,
+    }
+
+    fn round_trip(key: &JwtKey) -> Claims {
+        let claims = Claims {
+            sub: "user".to_string(),
+            exp: chrono::Utc::now().timestamp() + 60,
+        };
+        let token = encode(&key.header(), &claims, key.encoding_key()).unwrap();
+
+        let keys =
+            JwtKeySet::new(JwtKey::from_pem(key.algorithm, key.private_pem().unwrap()).unwrap());
+        let verification_key = keys.verification_key(&token).unwrap();
+        decode::<Claims>(
+            &token,
+            verification_key.decoding_key(),
+            &Validation::new(key.algorithm),
+        )
+        .unwrap()
+        .claims
+    }
+
+    #[test]
+    fn test_generated_keys_sign_and_verify() {
+        for algorithm in [Algorithm::RS256, Algorithm::ES256, Algorithm::EdDSA] {
+            let key = JwtKey::generate(algorithm).unwrap();
+
+            assert_eq!(round_trip(&key).sub, "user", "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn test_kid_is_stable_for_the_same_key() {
+        let key = JwtKey::generate(Algorithm::ES256).unwrap();
+        let reloaded = JwtKey::from_pem(Algorithm::ES256, key.private_pem().unwrap()).unwrap();
+
+        assert_eq!(key.kid, reloaded.kid);
+        assert_ne!(key.kid, JwtKey::generate(Algorithm::ES256).unwrap().kid);
+    }
+
+    #[test]
+    fn test_jwks_only_publishes_public_keys() {
+        let hmac = JwtKeySet::new(JwtKey::hmac("secret"));
+        assert!(hmac.jwks().keys.is_empty());
+
+        let keys = JwtKeySet::new(JwtKey::generate(Algorithm::EdDSA).unwrap());
+        let jwks = serde_json::to_value(keys.jwks()).unwrap();
+        let jwk = &jwks["keys"][0];
+
+        assert_eq!(jwk["kty"], "OKP");
+        assert_eq!(jwk["crv"], "Ed25519");
+        assert_eq!(jwk["alg"], "EdDSA");
+        assert_eq!(jwk["use"], "sig");
+        assert_eq!(jwk["kid"], keys.signing_key().kid.as_str());
+        assert!(jwk.get("d").is_none());
+    }
+
+    #[test]
+    fn test_unknown_kid_has_no_verification_key() {
+        let key = JwtKey::generate(Algorithm::ES256).unwrap();
+        let other = JwtKey::generate(Algorithm::ES256).unwrap();
+        let token = encode(
+            &other.header(),
+            &Claims {
+                sub: "user".to_string(),
+                exp: 0,
+            },
+            other.encoding_key(),
+        )
+        .unwrap();
+
+        assert!(JwtKeySet::new(key).verification_key(&token).is_none());
+    }
+
+    #[test]
+    fn test_replaced_keys_keep_verifying_old_tokens() {
+        let old = JwtKey::generate(Algorithm::ES256).unwrap();
+        let token = encode(
+            &old.header(),
+            &Claims {
+                sub: "user".to_string(),
+                exp: 0,
+            },
+            old.encoding_key(),
+        )
+        .unwrap();
+        let old_kid = old.kid.clone();
+
+        let keys = JwtKeySet::new(old);
+        let new = JwtKey::generate(Algorithm::ES256).unwrap();
+        let new_kid = new.kid.clone();
+        let retiring =
+            JwtKey::from_pem(Algorithm::ES256, keys.signing_key().private_pem().unwrap()).unwrap();
+        keys.replace(new, vec![retiring]);
+
+        assert_eq!(keys.signing_key().kid, new_kid);
+        assert_eq!(keys.verification_key(&token).unwrap().kid, old_kid);
+        assert_eq!(keys.jwks().keys.len(), 2);
+    }
+
+    #[test]
+    fn test_unsupported_algorithm_is_rejected() {
+        let result = JwtKey::generate(Algorithm::PS512);
+
+        assert!(matches!(result, Err(KeyError::UnsupportedAlgorithm(_))));
+    }
+}
@@ -0,0 +1,100 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use super::jwt_keys::KeyError;
+
+const NONCE_BYTES: usize = 12;
+
+/// Encrypts signing keys at rest with AES-256-GCM.
+///
+/// Ciphertexts are stored as `nonce || ciphertext`, and the key id is bound as
+/// associated data so an encrypted key cannot be swapped onto another row.
+pub struct KeyEncryptionUtil {
+    cipher: Aes256Gcm,
+}
+
+impl KeyEncryptionUtil {
+    /// Builds the cipher from a base64 encoded 32-byte key.
+    pub fn new(encoded_key: &str) -> Result<Self, KeyError> {
+        let key = STANDARD
+            .decode(encoded_key.trim())
+            .ok()
+            .filter(|key| key.len() == 32)
+            .ok_or_else(|| {
+                KeyError::InvalidKey("KEY_ENCRYPTION_KEY must be 32 base64 encoded bytes".into())
+            })?;
+
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        })
+    }
+
+    pub fn encrypt(&self, kid: &str, plaintext: &[u8]) -> Result<Vec<u8>, KeyError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: kid.as_bytes(),
+                },
+            )
+            .map_err(|_| KeyError::Encryption)?;
+
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    pub fn decrypt(&self, kid: &str, encrypted: &[u8]) -> Result<Vec<u8>, KeyError> {
+        if encrypted.len() < NONCE_BYTES {
+            return Err(KeyError::Encryption);
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_BYTES);
+
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: kid.as_bytes(),
+                },
+            )
+            .map_err(|_| KeyError::Encryption)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let util = KeyEncryptionUtil::new(KEY).unwrap();
+        let encrypted = util.encrypt("kid-1", b"private key").unwrap();
+
+        assert_ne!(&encrypted[NONCE_BYTES..], b"private key");
+        assert_eq!(util.decrypt("kid-1", &encrypted).unwrap(), b"private key");
+    }
+
+    #[test]
+    fn test_ciphertext_is_bound_to_kid() {
+        let util = KeyEncryptionUtil::new(KEY).unwrap();
+        let encrypted = util.encrypt("kid-1", b"private key").unwrap();
+
+        assert!(matches!(
+            util.decrypt("kid-2", &encrypted),
+            Err(KeyError::Encryption)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_key_is_rejected() {
+        assert!(matches!(
+            KeyEncryptionUtil::new("too-short"),
+            Err(KeyError::InvalidKey(_))
+        ));
+    }
+}
@@ -1,5 +1,14 @@
 /* General utils module */
 
+pub mod client_assertion;
+pub mod jwt_keys;
+pub mod key_encryption;
 pub mod password;
+pub mod pkce;
+pub mod refresh_token;
+pub mod security_events;
+pub mod token_denylist;
 pub mod user_agent;
+pub mod user_code;
 pub use password::PasswordUtil;
+pub use refresh_token::RefreshTokenUtil;
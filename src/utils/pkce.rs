@@ -0,0 +1,68 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// The only PKCE method we accept, `plain` offers no protection.
+pub const S256: &str = "S256";
+
+/// Checks that a `code_challenge` looks like a base64url SHA-256 digest.
+pub fn is_valid_challenge(challenge: &str) -> bool {
+    challenge.len() == 43
+        && URL_SAFE_NO_PAD
+            .decode(challenge)
+            .is_ok_and(|digest| digest.len() == 32)
+}
+
+/// Verifies a `code_verifier` against an S256 `code_challenge` (RFC 7636).
+pub fn verify_s256(verifier: &str, challenge: &str) -> bool {
+    if !is_valid_verifier(verifier) {
+        return false;
+    }
+
+    let computed = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    computed.as_bytes().ct_eq(challenge.as_bytes()).into()
+}
+
+// 43 to 128 unreserved characters, see RFC 7636 section 4.1
+fn is_valid_verifier(verifier: &str) -> bool {
+    (43..=128).contains(&verifier.len())
+        && verifier
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"-._~".contains(&byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example from RFC 7636 appendix B
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    #[test]
+    fn test_rfc_example_verifies() {
+        assert!(is_valid_challenge(CHALLENGE));
+        assert!(verify_s256(VERIFIER, CHALLENGE));
+    }
+
+    #[test]
+    fn test_wrong_verifier_is_rejected() {
+        let other = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXl";
+
+        assert!(!verify_s256(other, CHALLENGE));
+    }
+
+    #[test]
+    fn test_verifier_must_be_long_and_unreserved() {
+        assert!(!verify_s256("too-short", CHALLENGE));
+        assert!(!verify_s256(&format!("{VERIFIER}!"), CHALLENGE));
+        assert!(!verify_s256(&"a".repeat(129), CHALLENGE));
+    }
+
+    #[test]
+    fn test_malformed_challenge_is_rejected() {
+        assert!(!is_valid_challenge("not a challenge"));
+        assert!(!is_valid_challenge(&format!("{CHALLENGE}A")));
+        assert!(!is_valid_challenge(&CHALLENGE.replace('-', "+")));
+    }
+}
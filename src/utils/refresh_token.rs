@@ -0,0 +1,185 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use hmac::{Hmac, Mac};
+use rand::{RngCore, rngs::OsRng};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SECRET_BYTES: usize = 32;
+
+/// Refresh-token related errors
+#[derive(Debug, Error)]
+pub enum RefreshTokenError {
+    #[error("Malformed refresh token")]
+    Malformed,
+
+    #[error("Invalid refresh token signature")]
+    InvalidSignature,
+}
+
+/// The public parts of a refresh token, recovered after its signature is checked.
+#[derive(Debug, PartialEq)]
+pub struct ParsedRefreshToken {
+    pub session_id: Uuid,
+    pub generation: i32,
+}
+
+/// Issues opaque refresh tokens of the form `<session>.<generation>.<secret>.<tag>`.
+///
+/// The random secret makes every token unguessable, and only its SHA-256 digest is
+/// persisted. The HMAC tag proves a token was minted by us, which lets reuse of an
+/// already rotated generation be detected without keeping its digest around.
+pub struct RefreshTokenUtil {
+    key: Vec<u8>,
+}
+
+impl RefreshTokenUtil {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.as_bytes().to_vec(),
+        }
+    }
+
+    /// Generates a new refresh token for the given session generation.
+    pub fn generate(&self, session_id: Uuid, generation: i32) -> String {
+        let mut secret = [0u8; SECRET_BYTES];
+        OsRng.fill_bytes(&mut secret);
+
+        let body = format!(
+            "{}.{}.{}",
+            session_id.simple(),
+            generation,
+            URL_SAFE_NO_PAD.encode(secret)
+        );
+        let tag = URL_SAFE_NO_PAD.encode(self.sign(&body));
+
+        format!("{body}.{tag}")
+    }
+
+    /// Verifies the token tag and extracts the session it belongs to.
+    pub fn parse(&self, token: &str) -> Result<ParsedRefreshToken, RefreshTokenError> {
+        let (body, tag) = token.rsplit_once('.').ok_or(RefreshTokenError::Malformed)?;
+        let tag = URL_SAFE_NO_PAD
+            .decode(tag)
+            .map_err(|_| RefreshTokenError::Malformed)?;
+
+        let mut mac = self.mac();
+        mac.update(body.as_bytes());
+        mac.verify_slice(&tag)
+            .map_err(|_| RefreshTokenError::InvalidSignature)?;
+
+        let mut parts = body.splitn(3, '.');
+        let session_id = parts
+            .next()
+            .and_then(|part| Uuid::parse_str(part).ok())
+            .ok_or(RefreshTokenError::Malformed)?;
+        let generation = parts
+            .next()
+            .and_then(|part| part.parse().ok())
+            .ok_or(RefreshTokenError::Malformed)?;
+
+        Ok(ParsedRefreshToken {
+            session_id,
+            generation,
+        })
+    }
+
+    /// Returns the hex encoded SHA-256 digest stored for a token.
+    pub fn digest(&self, token: &str) -> String {
+        Sha256::digest(token.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Compares a token against a stored digest in constant time.
+    pub fn verify_digest(&self, token: &str, digest: &str) -> bool {
+        self.digest(token)
+            .as_bytes()
+            .ct_eq(digest.as_bytes())
+            .into()
+    }
+
+    fn sign(&self, body: &str) -> Vec<u8> {
+        let mut mac = self.mac();
+        mac.update(body.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_and_parse() {
+        let util = RefreshTokenUtil::new("secret");
+        let session_id = Uuid::new_v4();
+
+        let token = util.generate(session_id, 3);
+        let parsed = util.parse(&token).expect("Should parse token");
+
+        assert_eq!(
+            parsed,
+            ParsedRefreshToken {
+                session_id,
+                generation: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_tokens_are_unique() {
+        let util = RefreshTokenUtil::new("secret");
+        let session_id = Uuid::new_v4();
+
+        assert_ne!(util.generate(session_id, 0), util.generate(session_id, 0));
+    }
+
+    #[test]
+    fn test_tampered_generation_is_rejected() {
+        let util = RefreshTokenUtil::new("secret");
+        let token = util.generate(Uuid::new_v4(), 3);
+        let tampered = token.replacen(".3.", ".2.", 1);
+
+        let result = util.parse(&tampered);
+        assert!(matches!(result, Err(RefreshTokenError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_wrong_key_is_rejected() {
+        let token = RefreshTokenUtil::new("secret").generate(Uuid::new_v4(), 0);
+
+        let result = RefreshTokenUtil::new("other-secret").parse(&token);
+        assert!(matches!(result, Err(RefreshTokenError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_malformed_token_is_rejected() {
+        let util = RefreshTokenUtil::new("secret");
+
+        assert!(matches!(
+            util.parse("not-a-token"),
+            Err(RefreshTokenError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn test_digest_verification() {
+        let util = RefreshTokenUtil::new("secret");
+        let token = util.generate(Uuid::new_v4(), 0);
+        let digest = util.digest(&token);
+
+        assert_ne!(digest, token);
+        assert_eq!(digest.len(), 64);
+        assert!(util.verify_digest(&token, &digest));
+        assert!(!util.verify_digest(&util.generate(Uuid::new_v4(), 0), &digest));
+    }
+}
@@ -0,0 +1,36 @@
+/* Security event reporting */
+
+use tracing::warn;
+use uuid::Uuid;
+
+/// Events that indicate a possible attack and should be alerted on.
+#[derive(Debug)]
+pub enum SecurityEvent {
+    /// A refresh token that was already rotated out was presented again.
+    RefreshTokenReuse {
+        user_id: Uuid,
+        session_id: Uuid,
+        presented_generation: i32,
+        current_generation: i32,
+    },
+    /// An authorization code was presented after it had been exchanged.
+    AuthorizationCodeReuse {
+        client_id: String,
+        user_id: Uuid,
+        session_id: Option<Uuid>,
+    },
+}
+
+impl SecurityEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SecurityEvent::RefreshTokenReuse { .. } => "refresh_token_reuse",
+            SecurityEvent::AuthorizationCodeReuse { .. } => "authorization_code_reuse",
+        }
+    }
+}
+
+/// Emits a security event on the `security` tracing target.
+pub fn emit(event: SecurityEvent) {
+    warn!(target: "security", event = event.name(), details = ?event, "security event");
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::models::{JwtClaims, RevokedToken, RevokedTokenKind};
+
+/// In-memory set of revoked access tokens, checked on every verification.
+///
+/// Entries are kept until every token they match has expired anyway; `purge`
+/// drops them after that. Replicas learn of each other's revocations through
+/// Postgres notifications, see `AuthService::spawn_denylist_listener`.
+#[derive(Debug, Default)]
+pub struct TokenDenylist {
+    entries: RwLock<HashMap<(RevokedTokenKind, String), DateTime<Utc>>>,
+}
+
+impl TokenDenylist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a revocation, keeping the later expiry if it is already listed.
+    pub fn insert(&self, revoked: RevokedToken) {
+        let mut entries = self.write();
+        let expires_at = entries
+            .entry((revoked.kind, revoked.value))
+            .or_insert(revoked.expires_at);
+        *expires_at = (*expires_at).max(revoked.expires_at);
+    }
+
+    /// Replaces every entry, after (re)loading them from the database.
+    pub fn replace(&self, revoked: Vec<RevokedToken>) {
+        let entries = revoked
+            .into_iter()
+            .map(|revoked| ((revoked.kind, revoked.value), revoked.expires_at))
+            .collect();
+        *self.write() = entries;
+    }
+
+    /// Whether the token itself or its session was revoked.
+    pub fn is_revoked(&self, claims: &JwtClaims, now: DateTime<Utc>) -> bool {
+        let entries = self.entries.read().expect("Token denylist lock poisoned");
+        let listed = |kind, value: String| {
+            entries
+                .get(&(kind, value))
+                .is_some_and(|expires_at| *expires_at > now)
+        };
+
+        listed(RevokedTokenKind::Jti, claims.jti.clone())
+            || claims
+                .sid
+                .is_some_and(|sid| listed(RevokedTokenKind::Sid, sid.to_string()))
+    }
+
+    /// Drops entries whose tokens have all expired. Returns how many were dropped.
+    pub fn purge(&self, now: DateTime<Utc>) -> usize {
+        let mut entries = self.write();
+        let before = entries.len();
+        entries.retain(|_, expires_at| *expires_at > now);
+        before - entries.len()
+    }
+
+    fn write(
+        &self,
+    ) -> std::sync::RwLockWriteGuard<'_, HashMap<(RevokedTokenKind, String), DateTime<Utc>>> {
+        self.entries.write().expect("Token denylist lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    use crate::domain::models::TokenType;
+
+    fn claims(jti: &str, sid: Option<Uuid>) -> JwtClaims {
+        JwtClaims {
+            sub: "user-id".to_string(),
+            scope: "user".to_string(),
+            sid,
+            iss: "localhost".to_string(),
+            aud: "spa".to_string(),
+            azp: "spa".to_string(),
+            exp: 0,
+            iat: 0,
+            jti: jti.to_string(),
+            nbf: 0,
+            auth_time: 0,
+            resource_access: Default::default(),
+            token_type: TokenType::Access.to_string(),
+            act: None,
+            acr: None,
+        }
+    }
+
+    #[test]
+    fn test_revoked_token_or_session_is_denied() {
+        let now = Utc::now();
+        let sid = Uuid::new_v4();
+        let denylist = TokenDenylist::new();
+        denylist.insert(RevokedToken::jti("revoked", now + Duration::minutes(5)));
+        denylist.insert(RevokedToken::sid(sid, now + Duration::minutes(5)));
+
+        assert!(denylist.is_revoked(&claims("revoked", None), now));
+        assert!(denylist.is_revoked(&claims("other", Some(sid)), now));
+        assert!(!denylist.is_revoked(&claims("other", Some(Uuid::new_v4())), now));
+    }
+
+    #[test]
+    fn test_expired_entries_are_ignored_and_purged() {
+        let now = Utc::now();
+        let denylist = TokenDenylist::new();
+        denylist.insert(RevokedToken::jti("old", now - Duration::seconds(1)));
+        denylist.insert(RevokedToken::jti("new", now + Duration::minutes(5)));
+
+        assert!(!denylist.is_revoked(&claims("old", None), now));
+        assert_eq!(denylist.purge(now), 1);
+        assert!(denylist.is_revoked(&claims("new", None), now));
+    }
+
+    #[test]
+    fn test_later_expiry_is_kept() {
+        let now = Utc::now();
+        let denylist = TokenDenylist::new();
+        denylist.insert(RevokedToken::jti("token", now + Duration::minutes(5)));
+        denylist.insert(RevokedToken::jti("token", now - Duration::minutes(5)));
+
+        assert!(denylist.is_revoked(&claims("token", None), now));
+    }
+}
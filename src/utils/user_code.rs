@@ -0,0 +1,58 @@
+use rand::{Rng, rngs::OsRng};
+
+// Consonants only, so codes can't spell words and don't mix up 0/O or 1/I
+// (RFC 8628 section 6.1)
+const ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const LENGTH: usize = 8;
+
+/// Generates a user code in its normalized form, e.g. `WDJBMJHT`.
+pub fn generate() -> String {
+    (0..LENGTH)
+        .map(|_| ALPHABET[OsRng.gen_range(0..ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Normalizes what a user typed: case, dashes and spaces don't matter.
+/// Returns `None` if it can't be a user code.
+pub fn normalize(input: &str) -> Option<String> {
+    let code: String = input
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    (code.len() == LENGTH && code.bytes().all(|byte| ALPHABET.contains(&byte))).then_some(code)
+}
+
+/// Formats a normalized user code for display, e.g. `WDJB-MJHT`.
+pub fn display(code: &str) -> String {
+    let (first, second) = code.split_at(code.len() / 2);
+    format!("{first}-{second}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_codes_are_normalized() {
+        let code = generate();
+
+        assert_eq!(normalize(&code), Some(code.clone()));
+        assert_ne!(generate(), code);
+    }
+
+    #[test]
+    fn test_typed_codes_are_normalized() {
+        assert_eq!(normalize("wdjb-mjht"), Some("WDJBMJHT".to_string()));
+        assert_eq!(normalize(" WDJB MJHT "), Some("WDJBMJHT".to_string()));
+        assert_eq!(display("WDJBMJHT"), "WDJB-MJHT");
+    }
+
+    #[test]
+    fn test_malformed_codes_are_rejected() {
+        assert_eq!(normalize("WDJB-MJH"), None);
+        assert_eq!(normalize("WDJB-MJHA"), None); // vowel
+        assert_eq!(normalize("WDJB-MJH0"), None);
+    }
+}
@@ -3,7 +3,7 @@
 use bb8::Pool;
 use bb8_postgres::PostgresConnectionManager;
 use once_cell::sync::Lazy;
-use std::fs::read_to_string;
+use std::fs::{read_dir, read_to_string};
 use std::sync::Arc;
 use testcontainers::{
     ContainerAsync, GenericImage, ImageExt,
@@ -46,6 +46,11 @@ pub async fn get_test_db() -> Arc<PgPool> {
 async fn init_database() -> TestDatabase {
     println!("Initializing test database...");
 
+    // Services read the global config, which needs a secret
+    if std::env::var_os("JWT_SECRET").is_none() {
+        unsafe { std::env::set_var("JWT_SECRET", "test-secret") };
+    }
+
     let container = GenericImage::new("postgres", "17.4")
         .with_exposed_port(5432.tcp())
         .with_wait_for(WaitFor::message_on_stdout(
@@ -74,12 +79,20 @@ async fn init_database() -> TestDatabase {
 
     let conn = pool.get().await.expect("Failed to get DB connection");
 
-    let init_script =
-        read_to_string("local/sql/dev_initial/0000_initial.sql").expect("Failed to read init SQL");
-
-    conn.batch_execute(&init_script)
-        .await
-        .expect("Failed to run init script");
+    // Apply the init scripts in the same order as the postgres entrypoint
+    let mut scripts: Vec<_> = read_dir("local/sql/dev_initial")
+        .expect("Failed to read init SQL directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+        .collect();
+    scripts.sort();
+
+    for script in scripts {
+        let init_script = read_to_string(&script).expect("Failed to read init SQL");
+        conn.batch_execute(&init_script)
+            .await
+            .unwrap_or_else(|e| panic!("Failed to run init script {script:?}: {e}"));
+    }
 
     TestDatabase {
         _container: container, // Store container to keep it alive
@@ -111,7 +124,7 @@ pub async fn get_test_db_pool() -> Arc<PgPool> {
     let pool = get_test_db().await;
 
     // tables to reset per test
-    let tables = ["auth.users"];
+    let tables = ["auth.users", "auth.signing_keys", "auth.clients"];
     reset_database(pool.clone(), tables).await.unwrap();
     pool
 }
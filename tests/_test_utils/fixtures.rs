@@ -0,0 +1,69 @@
+/* Test fixtures */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use gandalf::adapters::dtos::{AuthUserDto, ClientSettings, DeviceInfo};
+use gandalf::adapters::repositories::{PgUserRepository, UserRepository};
+use gandalf::config::app_config::{AppConfig, get_config};
+use gandalf::config::database::PgPool;
+use gandalf::domain::models::{Client, GrantType, User};
+use gandalf::domain::services::AuthService;
+use gandalf::utils::jwt_keys::{JwtKey, JwtKeySet};
+
+// Client metadata with the server defaults
+pub fn client_settings(
+    name: &str,
+    redirect_uris: Vec<String>,
+    grant_types: Vec<GrantType>,
+) -> ClientSettings {
+    ClientSettings {
+        name: name.to_string(),
+        redirect_uris,
+        grant_types,
+        scopes: Client::default_scopes(),
+        audiences: vec![],
+        access_token_lifetime: None,
+        refresh_token_lifetime: None,
+        jwks: None,
+    }
+}
+
+// Server settings with the defaults, changed by `configure`.
+// Needs the test database to be set up first, for the environment.
+pub fn test_config(configure: impl FnOnce(&mut AppConfig)) -> &'static AppConfig {
+    let mut config = get_config().clone();
+    configure(&mut config);
+    Box::leak(Box::new(config))
+}
+
+// Auth service signing with a shared secret, without login strategies
+pub fn auth_service(pool: Arc<PgPool>, config: &'static AppConfig) -> AuthService {
+    let jwt_keys = JwtKeySet::new(JwtKey::hmac(&config.jwt_secret));
+    AuthService::with_config(HashMap::new(), pool, Arc::new(jwt_keys), config)
+}
+
+// A saved user, as the auth service loads it
+pub async fn auth_user(pool: Arc<PgPool>, email: &str) -> AuthUserDto {
+    let user_repo = PgUserRepository::new(pool);
+    // Signing in is not tested through the service, any hash will do
+    let user = User {
+        password_hash: Some("unused".to_string()),
+        ..User::new(email.to_string())
+    };
+    user_repo.save(&user).await.unwrap();
+    user_repo
+        .find_auth_user_by_id(&user.id)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+pub fn device_info() -> DeviceInfo {
+    DeviceInfo {
+        device_type: "desktop".to_string(),
+        device_name: "Test".to_string(),
+        browser: "Firefox".to_string(),
+        os: "Linux".to_string(),
+    }
+}
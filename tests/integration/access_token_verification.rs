@@ -0,0 +1,146 @@
+/* Access token verification integration test */
+
+use crate::fixtures::{auth_service, auth_user, device_info, test_config};
+use crate::{client_settings, get_test_db_pool};
+
+use gandalf::adapters::dtos::ClientGrant;
+use gandalf::domain::models::{Acr, Authentication, GrantType, ResourceAccess};
+use gandalf::domain::services::ClientService;
+
+#[tokio::test]
+async fn tokens_exchanged_for_another_audience_are_rejected() {
+    let pool = get_test_db_pool().await;
+    let service = auth_service(pool.clone(), test_config(|_| {}));
+    let clients = ClientService::new(pool.clone());
+
+    let redirect_uris = vec!["https://app.example.com/callback".to_string()];
+    let (client, _) = clients
+        .register_client(
+            client_settings("app", redirect_uris, GrantType::defaults()),
+            false,
+        )
+        .await
+        .unwrap();
+
+    let user = auth_user(pool.clone(), "audience@mail.com").await;
+    let tokens = service
+        .make_session(
+            user,
+            "127.0.0.1".parse().unwrap(),
+            device_info(),
+            None,
+            Some(ClientGrant {
+                client: &client,
+                scope: "openid",
+            }),
+            Authentication::now(Acr::Password),
+        )
+        .await
+        .unwrap();
+    let subject = service
+        .verify_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(subject.aud, client.client_id);
+
+    let (exchanged, _) = service
+        .issue_exchanged_token(
+            &client,
+            &subject,
+            "resource-server",
+            &subject.scope,
+            ResourceAccess::new(),
+            None,
+        )
+        .unwrap();
+
+    // Meant for the resource server, not for our endpoints or its client
+    assert!(service.verify_access_token(&exchanged).await.is_err());
+    assert!(
+        service
+            .verify_exchangeable_token(&exchanged, &client.client_id)
+            .await
+            .is_err()
+    );
+    assert!(
+        service
+            .verify_exchangeable_token(&exchanged, "resource-server")
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn client_tokens_end_with_their_client() {
+    let pool = get_test_db_pool().await;
+    let service = auth_service(pool.clone(), test_config(|_| {}));
+    let clients = ClientService::new(pool.clone());
+
+    let (client, _) = clients
+        .register_client(
+            client_settings("worker", vec![], vec![GrantType::ClientCredentials]),
+            false,
+        )
+        .await
+        .unwrap();
+    let token = service
+        .issue_client_token(&client, "", ResourceAccess::new())
+        .unwrap();
+    assert!(service.verify_access_token(&token).await.is_ok());
+
+    clients.delete_client(&client.client_id).await.unwrap();
+    assert!(service.verify_access_token(&token).await.is_err());
+    assert!(
+        service
+            .introspect(&token, None, &client.client_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn tokens_are_only_introspected_by_their_client_or_audience() {
+    let pool = get_test_db_pool().await;
+    let service = auth_service(pool.clone(), test_config(|_| {}));
+    let clients = ClientService::new(pool.clone());
+
+    let (client, _) = clients
+        .register_client(
+            client_settings("introspected", vec![], vec![GrantType::ClientCredentials]),
+            false,
+        )
+        .await
+        .unwrap();
+    let token = service
+        .issue_client_token(&client, "", ResourceAccess::new())
+        .unwrap();
+    let subject = service.verify_access_token(&token).await.unwrap();
+    let (exchanged, _) = service
+        .issue_exchanged_token(
+            &client,
+            &subject,
+            "resource-server",
+            "",
+            ResourceAccess::new(),
+            None,
+        )
+        .unwrap();
+
+    // The client it was issued to, and the audience it was exchanged for
+    let introspect = |token, client_id| service.introspect(token, None, client_id);
+    assert!(
+        introspect(&token, &client.client_id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(introspect(&token, "stranger").await.unwrap().is_none());
+    assert!(
+        introspect(&exchanged, "resource-server")
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(introspect(&exchanged, "stranger").await.unwrap().is_none());
+}
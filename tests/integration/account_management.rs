@@ -0,0 +1,125 @@
+/* Account management integration test */
+
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::Body;
+use axum::http::{Request, StatusCode, header::AUTHORIZATION, header::CONTENT_TYPE};
+use chrono::{Duration, Utc};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::fixtures::{auth_user, device_info};
+use crate::get_test_db_pool;
+
+use gandalf::adapters::dtos::SessionTokens;
+use gandalf::adapters::repositories::PgUserRepository;
+use gandalf::app_modules::AppState;
+use gandalf::app_modules::api::v1::routes::v1_routes;
+use gandalf::config::database::PgPool;
+use gandalf::domain::models::{Acr, Authentication};
+
+// Signs the user in, `signed_in_ago` after they last authenticated
+async fn sign_in(
+    pool: &Arc<PgPool>,
+    state: &AppState,
+    user_id: Uuid,
+    signed_in_ago: Duration,
+) -> SessionTokens {
+    let user = PgUserRepository::new(pool.clone())
+        .find_auth_user_by_id(&user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    state
+        .auth_service
+        .make_session(
+            user,
+            "127.0.0.1".parse().unwrap(),
+            device_info(),
+            None,
+            None,
+            Authentication {
+                acr: Acr::Password,
+                auth_time: Utc::now() - signed_in_ago,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+async fn send(state: &AppState, request: Request<Body>) -> StatusCode {
+    let app = Router::new().nest("/api/v1", v1_routes().with_state(state.clone()));
+    app.oneshot(request).await.unwrap().status()
+}
+
+fn change_password(tokens: &SessionTokens) -> Request<Body> {
+    Request::put("/api/v1/me/password")
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.access_token))
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"newPassword":"correct horse battery"}"#))
+        .unwrap()
+}
+
+fn delete_account(tokens: &SessionTokens) -> Request<Body> {
+    Request::delete("/api/v1/me")
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.access_token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn changing_the_password_takes_a_recent_sign_in() {
+    let pool = get_test_db_pool().await;
+    let state = AppState::new(pool.clone()).await;
+    let user_id = auth_user(pool.clone(), "password@mail.com").await.id;
+
+    let stale = sign_in(&pool, &state, user_id, Duration::hours(1)).await;
+    assert_eq!(
+        send(&state, change_password(&stale)).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let fresh = sign_in(&pool, &state, user_id, Duration::zero()).await;
+    assert_eq!(
+        send(&state, change_password(&fresh)).await,
+        StatusCode::NO_CONTENT
+    );
+    let user = PgUserRepository::new(pool.clone())
+        .find_auth_user_by_id(&user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_ne!(user.password_hash, "unused");
+    // Other devices are signed out
+    let sessions = state.auth_service.list_sessions(user_id).await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, fresh.session_id);
+}
+
+#[tokio::test]
+async fn deleting_the_account_takes_a_recent_sign_in() {
+    let pool = get_test_db_pool().await;
+    let state = AppState::new(pool.clone()).await;
+    let user_id = auth_user(pool.clone(), "delete@mail.com").await.id;
+
+    let stale = sign_in(&pool, &state, user_id, Duration::hours(1)).await;
+    assert_eq!(
+        send(&state, delete_account(&stale)).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let fresh = sign_in(&pool, &state, user_id, Duration::zero()).await;
+    assert_eq!(
+        send(&state, delete_account(&fresh)).await,
+        StatusCode::NO_CONTENT
+    );
+    assert!(
+        state
+            .user_service
+            .find_user_by_id(&user_id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
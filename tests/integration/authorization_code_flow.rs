@@ -0,0 +1,85 @@
+/* Authorization code flow integration test */
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+
+use crate::fixtures::{auth_service, auth_user, device_info, test_config};
+use crate::{client_settings, get_test_db_pool};
+
+use gandalf::adapters::dtos::AuthorizationRequest;
+use gandalf::app_modules::auth::Error;
+use gandalf::domain::models::{Acr, Authentication, GrantType};
+use gandalf::domain::services::{ClientService, OAuthService};
+
+const CALLBACK: &str = "https://spa.example.com/callback";
+// The PKCE pair of RFC 7636 appendix B
+const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+#[tokio::test]
+async fn sign_ins_older_than_max_age_get_no_tokens() {
+    let pool = get_test_db_pool().await;
+    let auth = Arc::new(auth_service(pool.clone(), test_config(|_| {})));
+    let oauth = OAuthService::new(pool.clone(), auth.clone());
+    let (client, _) = ClientService::new(pool.clone())
+        .register_client(
+            client_settings(
+                "max-age",
+                vec![CALLBACK.to_string()],
+                vec![GrantType::AuthorizationCode],
+            ),
+            false,
+        )
+        .await
+        .unwrap();
+    let user_id = auth_user(pool.clone(), "max-age@mail.com").await.id;
+    let request = AuthorizationRequest {
+        client_id: client.client_id.clone(),
+        redirect_uri: CALLBACK.to_string(),
+        scope: "openid".to_string(),
+        state: None,
+        nonce: None,
+        code_challenge: CHALLENGE.to_string(),
+        max_age: Some(60),
+        acr: Some(Acr::Password),
+    };
+
+    let stale = Authentication {
+        acr: Acr::Password,
+        auth_time: Utc::now() - Duration::minutes(5),
+    };
+    let refused = oauth
+        .issue_authorization_code(&request, user_id, stale)
+        .await;
+    assert!(matches!(
+        refused,
+        Err(Error::ReauthenticationRequired {
+            max_age: Some(60),
+            ..
+        })
+    ));
+
+    let signed_in = Authentication::now(Acr::Password);
+    let code = oauth
+        .issue_authorization_code(&request, user_id, signed_in)
+        .await
+        .unwrap();
+    let (tokens, _) = oauth
+        .exchange_authorization_code(
+            &client,
+            &code,
+            CALLBACK,
+            VERIFIER,
+            "127.0.0.1".parse().unwrap(),
+            device_info(),
+        )
+        .await
+        .unwrap();
+    // The tokens tell when the user signed in, not when the code was exchanged
+    let claims = auth
+        .verify_access_token(&tokens.access_token)
+        .await
+        .unwrap();
+    assert_eq!(claims.auth_time, signed_in.auth_time.timestamp());
+}
@@ -0,0 +1,58 @@
+/* Authorization code repository integration test */
+
+use chrono::{Duration, Utc};
+
+use crate::{client_settings, get_test_db_pool};
+
+use gandalf::adapters::repositories::{
+    AuthorizationCodeRepository, PgAuthorizationCodeRepository, PgUserRepository, UserRepository,
+};
+use gandalf::domain::models::{Acr, AuthorizationCode, GrantType, User};
+use gandalf::domain::services::ClientService;
+
+#[tokio::test]
+async fn authorization_code_is_consumed_once() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let code_repo = PgAuthorizationCodeRepository::new(pool.clone());
+
+    let redirect_uri = "https://spa.example.com/callback".to_string();
+    let (client, _) = ClientService::new(pool.clone())
+        .register_client(
+            client_settings("spa", vec![redirect_uri.clone()], GrantType::defaults()),
+            true,
+        )
+        .await
+        .unwrap();
+    let user = User::new("code@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+
+    let now = Utc::now();
+    let code = AuthorizationCode {
+        code_hash: "a".repeat(64),
+        client_id: client.client_id.clone(),
+        user_id: user.id,
+        redirect_uri,
+        scope: "openid".to_string(),
+        nonce: None,
+        code_challenge: "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM".to_string(),
+        acr: Acr::Password,
+        auth_time: now,
+        max_age: None,
+        expires_at: now + Duration::seconds(60),
+        consumed_at: None,
+        session_id: None,
+        created_at: now,
+    };
+    code_repo.create_code(&code).await.unwrap();
+
+    let consumed = code_repo.consume_code(&code.code_hash).await.unwrap();
+    assert_eq!(consumed.unwrap().user_id, user.id);
+
+    // Replaying the code finds nothing to consume, but it is still on record
+    let replayed = code_repo.consume_code(&code.code_hash).await.unwrap();
+    assert!(replayed.is_none());
+
+    let stored = code_repo.find_code(&code.code_hash).await.unwrap().unwrap();
+    assert!(stored.consumed_at.is_some());
+}
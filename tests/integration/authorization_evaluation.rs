@@ -0,0 +1,400 @@
+/* Authorization evaluation integration tests */
+
+use uuid::Uuid;
+
+use crate::get_test_db_pool;
+
+use gandalf::adapters::repositories::{
+    GroupRepository, IamRepository, PgGroupRepository, PgIamRepository, PgUserRepository,
+    UserRepository,
+};
+use gandalf::domain::models::{
+    Decision, Effect, Group, Permission, Principal, Resource, ResourceType, Role, RoleAssignment,
+    User,
+};
+use gandalf::domain::services::AuthorizationService;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+// A resource server of orgs, holding projects, holding channels
+struct Tree {
+    server: String,
+    principal: Principal,
+    org: ResourceType,
+    project: ResourceType,
+    channel: ResourceType,
+    iam: PgIamRepository,
+    groups: PgGroupRepository,
+    authorization: AuthorizationService,
+}
+
+impl Tree {
+    async fn new(name: &str) -> Self {
+        let pool = get_test_db_pool().await;
+        let user = User::new(format!("{name}@mail.com"));
+        PgUserRepository::new(pool.clone())
+            .save(&user)
+            .await
+            .unwrap();
+
+        let server = format!("{name}-{}", Uuid::new_v4());
+        let iam = PgIamRepository::new(pool.clone());
+        let actions = strings(&["read", "write"]);
+        let org = ResourceType::new(server.clone(), "org".to_string(), actions.clone());
+        let project = ResourceType::new(server.clone(), "project".to_string(), actions.clone());
+        let channel = ResourceType::new(server.clone(), "channel".to_string(), actions);
+        for resource_type in [&org, &project, &channel] {
+            iam.create_resource_type(resource_type).await.unwrap();
+        }
+
+        Self {
+            server,
+            principal: Principal::User(user.id),
+            org,
+            project,
+            channel,
+            iam,
+            groups: PgGroupRepository::new(pool.clone()),
+            authorization: AuthorizationService::new(pool),
+        }
+    }
+
+    async fn resource(
+        &self,
+        resource_type: &ResourceType,
+        name: &str,
+        parent: Option<&Resource>,
+    ) -> Resource {
+        let resource = match parent {
+            Some(parent) => Resource::new_child(resource_type, name.to_string(), parent),
+            None => Resource::new(resource_type, name.to_string()),
+        };
+        self.iam.create_resource(&resource).await.unwrap();
+        resource
+    }
+
+    async fn role(&self, resource_type: &ResourceType, name: &str, actions: &[&str]) -> Role {
+        let role = Role::new(resource_type, name.to_string(), strings(actions));
+        self.iam.create_role(&role).await.unwrap();
+        role
+    }
+
+    async fn assign(&self, assignment: RoleAssignment) {
+        assert!(self.iam.assign_role(&assignment).await.unwrap());
+    }
+
+    async fn group(&self, name: &str, members: &[Principal]) -> Group {
+        let group = Group::new(format!("{}-{name}", self.server), None);
+        assert!(self.groups.create_group(&group).await.unwrap());
+        for member in members {
+            self.groups.add_member(group.id, *member).await.unwrap();
+        }
+        group
+    }
+
+    // What the principal's tokens list on this server
+    async fn permissions(&self) -> Vec<Permission> {
+        self.iam
+            .list_permissions(self.principal)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|permission| permission.resource_server == self.server)
+            .collect()
+    }
+
+    async fn check(&self, resource: &str, action: &str) -> Decision {
+        self.authorization
+            .check(self.principal, &self.server, resource, action)
+            .await
+            .unwrap()
+    }
+}
+
+#[tokio::test]
+async fn grants_on_a_parent_apply_to_its_descendants() {
+    let tree = Tree::new("inherit").await;
+    let acme = tree.resource(&tree.org, "acme", None).await;
+    let web = tree.resource(&tree.project, "web", Some(&acme)).await;
+    tree.resource(&tree.channel, "general", Some(&web)).await;
+    tree.resource(&tree.channel, "elsewhere", None).await;
+    let reader = tree.role(&tree.org, "reader", &["read"]).await;
+    tree.assign(RoleAssignment::new(tree.principal, &reader, &acme))
+        .await;
+
+    let decision = tree.check("channel/general", "read").await;
+    assert!(decision.allowed);
+    let grant = decision.grant.unwrap();
+    assert_eq!(grant.resource, "org/acme");
+    assert_eq!(grant.role, "reader");
+
+    assert!(tree.check("project/web", "read").await.allowed);
+    assert_eq!(
+        tree.check("channel/general", "write").await,
+        Decision::deny()
+    );
+    assert_eq!(
+        tree.check("channel/elsewhere", "read").await,
+        Decision::deny()
+    );
+
+    // Tokens list the descendants the grant reaches too
+    let resources: Vec<_> = tree
+        .permissions()
+        .await
+        .into_iter()
+        .map(|permission| permission.resource)
+        .collect();
+    assert_eq!(
+        resources,
+        vec!["channel/general", "org/acme", "project/web"]
+    );
+}
+
+#[tokio::test]
+async fn explicit_deny_overrides_inherited_allow() {
+    let tree = Tree::new("deny").await;
+    let acme = tree.resource(&tree.org, "acme", None).await;
+    let web = tree.resource(&tree.project, "web", Some(&acme)).await;
+    let api = tree.resource(&tree.project, "api", Some(&acme)).await;
+    tree.resource(&tree.channel, "web-general", Some(&web))
+        .await;
+    tree.resource(&tree.channel, "api-general", Some(&api))
+        .await;
+    let org_writer = tree.role(&tree.org, "writer", &["read", "write"]).await;
+    let project_writer = tree.role(&tree.project, "writer", &["write"]).await;
+    tree.assign(RoleAssignment::new(tree.principal, &org_writer, &acme))
+        .await;
+    tree.assign(RoleAssignment::new(tree.principal, &project_writer, &web).denied())
+        .await;
+
+    // The deny on the project wins over the closer grant's absence and the org's allow
+    let denied = tree.check("channel/web-general", "write").await;
+    assert!(!denied.allowed);
+    let grant = denied.grant.unwrap();
+    assert_eq!(grant.effect, Effect::Deny);
+    assert_eq!(grant.resource, "project/web");
+
+    // Actions the deny doesn't name, and siblings, keep the inherited allow
+    assert!(tree.check("channel/web-general", "read").await.allowed);
+    assert!(tree.check("channel/api-general", "write").await.allowed);
+
+    // A direct allow doesn't beat an inherited deny either
+    let channel_writer = tree.role(&tree.channel, "writer", &["write"]).await;
+    let web_general = tree
+        .iam
+        .find_resource(tree.channel.id, "web-general")
+        .await
+        .unwrap()
+        .unwrap();
+    tree.assign(RoleAssignment::new(
+        tree.principal,
+        &channel_writer,
+        &web_general,
+    ))
+    .await;
+    assert!(!tree.check("channel/web-general", "write").await.allowed);
+
+    let page = tree
+        .authorization
+        .list_resources(tree.principal, &tree.server, "channel", "write", None, None)
+        .await
+        .unwrap();
+    let names: Vec<_> = page.resources.into_iter().map(|r| r.name).collect();
+    assert_eq!(names, vec!["api-general"]);
+
+    let writable: Vec<_> = tree
+        .permissions()
+        .await
+        .into_iter()
+        .filter(|permission| permission.actions.contains(&"write".to_string()))
+        .map(|permission| permission.resource)
+        .collect();
+    assert_eq!(
+        writable,
+        vec!["channel/api-general", "org/acme", "project/api"]
+    );
+}
+
+#[tokio::test]
+async fn deep_hierarchies_report_the_closest_grant() {
+    let tree = Tree::new("deep").await;
+    let acme = tree.resource(&tree.org, "acme", None).await;
+    let mut parent = tree.resource(&tree.project, "level-0", Some(&acme)).await;
+    for level in 1..10 {
+        parent = tree
+            .resource(&tree.project, &format!("level-{level}"), Some(&parent))
+            .await;
+    }
+    tree.resource(&tree.channel, "bottom", Some(&parent)).await;
+
+    let org_reader = tree.role(&tree.org, "reader", &["read"]).await;
+    tree.assign(RoleAssignment::new(tree.principal, &org_reader, &acme))
+        .await;
+    let decision = tree.check("channel/bottom", "read").await;
+    assert_eq!(decision.grant.unwrap().resource, "org/acme");
+
+    let project_reader = tree.role(&tree.project, "reader", &["read"]).await;
+    let level_5 = tree
+        .iam
+        .find_resource(tree.project.id, "level-5")
+        .await
+        .unwrap()
+        .unwrap();
+    tree.assign(RoleAssignment::new(
+        tree.principal,
+        &project_reader,
+        &level_5,
+    ))
+    .await;
+    let decision = tree.check("channel/bottom", "read").await;
+    assert_eq!(decision.grant.unwrap().resource, "project/level-5");
+}
+
+#[tokio::test]
+async fn wildcard_grants_match_resource_names() {
+    let tree = Tree::new("wildcard").await;
+    let acme = tree.resource(&tree.org, "acme", None).await;
+    let team_web = tree.resource(&tree.project, "team-web", Some(&acme)).await;
+    tree.resource(&tree.project, "team_api", Some(&acme)).await;
+    tree.resource(&tree.project, "ops", Some(&acme)).await;
+    let general = tree
+        .resource(&tree.channel, "general", Some(&team_web))
+        .await;
+
+    let project_reader = tree.role(&tree.project, "reader", &["read"]).await;
+    tree.assign(RoleAssignment::matching(
+        tree.principal,
+        &project_reader,
+        "team-*".to_string(),
+    ))
+    .await;
+
+    let decision = tree.check("project/team-web", "read").await;
+    assert!(decision.allowed);
+    assert_eq!(decision.grant.unwrap().resource, "project/team-*");
+    assert!(tree.check("channel/general", "read").await.allowed);
+    // `_` is no wildcard, only `*` is
+    assert!(!tree.check("project/team_api", "read").await.allowed);
+    assert!(!tree.check("project/ops", "read").await.allowed);
+
+    let channel_writer = tree.role(&tree.channel, "writer", &["write"]).await;
+    tree.assign(RoleAssignment::matching(
+        tree.principal,
+        &channel_writer,
+        "*".to_string(),
+    ))
+    .await;
+    assert!(tree.check("channel/general", "write").await.allowed);
+    // Tokens list the resources patterns match, and their descendants
+    assert_eq!(
+        tree.permissions().await,
+        vec![
+            Permission {
+                resource_server: tree.server.clone(),
+                resource: "channel/general".to_string(),
+                actions: strings(&["read", "write"]),
+            },
+            Permission {
+                resource_server: tree.server.clone(),
+                resource: "project/team-web".to_string(),
+                actions: strings(&["read"]),
+            },
+        ]
+    );
+
+    let channel_reader = tree.role(&tree.channel, "reader", &["read"]).await;
+    for role in [&channel_reader, &channel_writer] {
+        tree.assign(RoleAssignment::new(tree.principal, role, &general))
+            .await;
+    }
+    // A denying pattern on an ancestor takes the action away from tokens too,
+    // direct grants included
+    let project_writer = tree.role(&tree.project, "writer", &["write"]).await;
+    tree.assign(
+        RoleAssignment::matching(tree.principal, &project_writer, "team-*".to_string()).denied(),
+    )
+    .await;
+    assert!(!tree.check("channel/general", "write").await.allowed);
+    assert_eq!(
+        tree.permissions().await,
+        vec![
+            Permission {
+                resource_server: tree.server.clone(),
+                resource: "channel/general".to_string(),
+                actions: strings(&["read"]),
+            },
+            Permission {
+                resource_server: tree.server.clone(),
+                resource: "project/team-web".to_string(),
+                actions: strings(&["read"]),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn roles_of_nested_groups_apply_to_their_members() {
+    let tree = Tree::new("groups").await;
+    let acme = tree.resource(&tree.org, "acme", None).await;
+    let web = tree.resource(&tree.project, "web", Some(&acme)).await;
+    tree.resource(&tree.channel, "general", Some(&web)).await;
+    let backend = tree.group("backend", &[tree.principal]).await;
+    let engineering = tree
+        .group("engineering", &[Principal::Group(backend.id)])
+        .await;
+    let org_reader = tree.role(&tree.org, "reader", &["read"]).await;
+    let project_writer = tree.role(&tree.project, "writer", &["write"]).await;
+    tree.assign(RoleAssignment::new(
+        Principal::Group(engineering.id),
+        &org_reader,
+        &acme,
+    ))
+    .await;
+    tree.assign(RoleAssignment::new(
+        Principal::Group(backend.id),
+        &project_writer,
+        &web,
+    ))
+    .await;
+
+    let decision = tree.check("channel/general", "read").await;
+    assert!(decision.allowed);
+    assert_eq!(decision.grant.unwrap().resource, "org/acme");
+    assert!(tree.check("channel/general", "write").await.allowed);
+
+    // A group's deny reaches its members too
+    tree.assign(
+        RoleAssignment::new(Principal::Group(engineering.id), &project_writer, &web).denied(),
+    )
+    .await;
+    assert!(!tree.check("channel/general", "write").await.allowed);
+
+    // The denied write is left out of tokens, the inherited read is kept
+    let permissions: Vec<_> = tree
+        .permissions()
+        .await
+        .into_iter()
+        .map(|permission| (permission.resource, permission.actions))
+        .collect();
+    assert_eq!(
+        permissions,
+        vec![
+            ("channel/general".to_string(), strings(&["read"])),
+            ("org/acme".to_string(), strings(&["read"])),
+            ("project/web".to_string(), strings(&["read"])),
+        ]
+    );
+
+    // Leaving the group takes its roles away
+    tree.groups
+        .remove_member(backend.id, tree.principal)
+        .await
+        .unwrap();
+    assert_eq!(
+        tree.check("channel/general", "read").await,
+        Decision::deny()
+    );
+}
@@ -0,0 +1,185 @@
+/* Client authentication integration test */
+
+use crate::{client_settings, get_test_db_pool};
+
+use gandalf::domain::models::{ClientPermission, GrantType};
+use gandalf::domain::services::ClientService;
+use gandalf::domain::services::errors::Error;
+
+#[tokio::test]
+async fn registered_client_authenticates_with_its_secret() {
+    let pool = get_test_db_pool().await;
+    let service = ClientService::new(pool.clone());
+
+    let (client, secret) = service
+        .register_client(client_settings("resource-server", vec![], vec![]), false)
+        .await
+        .unwrap();
+    let secret = secret.unwrap();
+    assert_ne!(client.client_secret_hash.as_deref(), Some(secret.as_str()));
+
+    let authenticated = service
+        .authenticate(&client.client_id, Some(&secret))
+        .await
+        .unwrap();
+    assert_eq!(authenticated.id, client.id);
+
+    let wrong_secret = service.authenticate(&client.client_id, Some("wrong")).await;
+    assert!(matches!(wrong_secret, Err(Error::InvalidClient)));
+
+    let unknown = service.authenticate("unknown", Some(&secret)).await;
+    assert!(matches!(unknown, Err(Error::InvalidClient)));
+
+    // A confidential client can not skip its secret
+    let no_secret = service.authenticate(&client.client_id, None).await;
+    assert!(matches!(no_secret, Err(Error::InvalidClient)));
+}
+
+#[tokio::test]
+async fn public_client_authenticates_without_secret() {
+    let pool = get_test_db_pool().await;
+    let service = ClientService::new(pool.clone());
+
+    let redirect_uri = "https://spa.example.com/callback".to_string();
+    let (client, secret) = service
+        .register_client(
+            client_settings("spa", vec![redirect_uri.clone()], GrantType::defaults()),
+            true,
+        )
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+    assert!(client.is_public());
+
+    let stored = service
+        .find_client(&client.client_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.redirect_uris, vec![redirect_uri]);
+
+    let authenticated = service.authenticate(&client.client_id, None).await.unwrap();
+    assert_eq!(authenticated.id, client.id);
+
+    let with_secret = service.authenticate(&client.client_id, Some("guess")).await;
+    assert!(matches!(with_secret, Err(Error::InvalidClient)));
+}
+
+#[tokio::test]
+async fn admin_can_update_and_delete_clients() {
+    let pool = get_test_db_pool().await;
+    let service = ClientService::new(pool.clone());
+
+    let (client, secret) = service
+        .register_client(client_settings("api", vec![], vec![]), false)
+        .await
+        .unwrap();
+    let secret = secret.unwrap();
+
+    let mut settings = client_settings("api", vec![], vec![GrantType::RefreshToken]);
+    settings.access_token_lifetime = Some(300);
+    let updated = service
+        .update_client(&client.client_id, settings, false)
+        .await
+        .unwrap();
+    assert_eq!(updated.grant_types, vec![GrantType::RefreshToken]);
+    assert_eq!(updated.client_secret_hash, client.client_secret_hash);
+
+    // Deactivated clients can no longer authenticate
+    let inactive = service.authenticate(&client.client_id, Some(&secret)).await;
+    assert!(matches!(inactive, Err(Error::InvalidClient)));
+
+    let stored = service.get_client(&client.client_id).await.unwrap();
+    assert_eq!(stored.access_token_lifetime, Some(300));
+
+    service.delete_client(&client.client_id).await.unwrap();
+    let deleted = service.get_client(&client.client_id).await;
+    assert!(matches!(deleted, Err(Error::ClientNotFound)));
+}
+
+#[tokio::test]
+async fn authorization_code_clients_need_a_redirect_uri() {
+    let pool = get_test_db_pool().await;
+    let service = ClientService::new(pool.clone());
+
+    let result = service
+        .register_client(client_settings("spa", vec![], GrantType::defaults()), true)
+        .await;
+
+    assert!(matches!(result, Err(Error::InvalidClientMetadata(_))));
+}
+
+#[tokio::test]
+async fn client_credentials_clients_must_be_confidential() {
+    let pool = get_test_db_pool().await;
+    let service = ClientService::new(pool.clone());
+    let grant_types = vec![GrantType::ClientCredentials];
+
+    let public = service
+        .register_client(client_settings("batch", vec![], grant_types.clone()), true)
+        .await;
+    assert!(matches!(public, Err(Error::InvalidClientMetadata(_))));
+
+    let (_, secret) = service
+        .register_client(client_settings("batch", vec![], grant_types), false)
+        .await
+        .unwrap();
+    assert!(secret.is_some());
+}
+
+#[tokio::test]
+async fn admin_can_replace_client_permissions() {
+    let pool = get_test_db_pool().await;
+    let service = ClientService::new(pool.clone());
+    let (client, _) = service
+        .register_client(
+            client_settings("batch", vec![], vec![GrantType::ClientCredentials]),
+            false,
+        )
+        .await
+        .unwrap();
+
+    let permission = ClientPermission {
+        resource_server: "reports".to_string(),
+        resource: "invoices".to_string(),
+        actions: vec!["read".to_string()],
+    };
+    service
+        .replace_permissions(&client.client_id, vec![permission.clone()])
+        .await
+        .unwrap();
+    assert_eq!(
+        service.list_permissions(&client.client_id).await.unwrap(),
+        vec![permission]
+    );
+
+    service
+        .replace_permissions(&client.client_id, vec![])
+        .await
+        .unwrap();
+    assert!(
+        service
+            .list_permissions(&client.client_id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let unknown = service.replace_permissions("unknown", vec![]).await;
+    assert!(matches!(unknown, Err(Error::ClientNotFound)));
+}
+
+#[tokio::test]
+async fn client_access_token_lifetime_is_capped() {
+    let pool = get_test_db_pool().await;
+    let service = ClientService::new(pool.clone());
+
+    let mut settings = client_settings("long-lived", vec![], vec![]);
+    settings.access_token_lifetime = Some(60 * 60 * 24);
+    let registered = service.register_client(settings, false).await;
+    assert!(matches!(registered, Err(Error::InvalidClientMetadata(_))));
+
+    let mut settings = client_settings("short-lived", vec![], vec![]);
+    settings.access_token_lifetime = Some(60 * 5);
+    assert!(service.register_client(settings, false).await.is_ok());
+}
@@ -0,0 +1,130 @@
+/* Device code repository integration test */
+
+use chrono::{Duration, Utc};
+
+use crate::{client_settings, get_test_db_pool};
+
+use gandalf::adapters::repositories::{
+    DeviceCodeRepository, PgDeviceCodeRepository, PgUserRepository, UserRepository,
+};
+use gandalf::domain::models::{Acr, Authentication, DeviceCode, GrantType, User};
+use gandalf::domain::services::ClientService;
+
+#[tokio::test]
+async fn device_code_is_consumed_once_approved() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let device_repo = PgDeviceCodeRepository::new(pool.clone());
+
+    let (client, _) = ClientService::new(pool.clone())
+        .register_client(
+            client_settings("cli", vec![], vec![GrantType::DeviceCode]),
+            true,
+        )
+        .await
+        .unwrap();
+    let user = User::new("device@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+
+    let now = Utc::now();
+    let request = DeviceCode {
+        device_code_hash: "d".repeat(64),
+        user_code: "WDJBMJHT".to_string(),
+        client_id: client.client_id.clone(),
+        scope: "openid".to_string(),
+        polling_interval: 5,
+        last_polled_at: None,
+        user_id: None,
+        approved_at: None,
+        denied_at: None,
+        acr: None,
+        auth_time: None,
+        expires_at: now + Duration::seconds(600),
+        consumed_at: None,
+        session_id: None,
+        created_at: now,
+    };
+    assert!(device_repo.create_device_code(&request).await.unwrap());
+
+    // A pending user code can't be handed out twice
+    let duplicate = DeviceCode {
+        device_code_hash: "e".repeat(64),
+        ..request.clone()
+    };
+    assert!(!device_repo.create_device_code(&duplicate).await.unwrap());
+
+    let (_, previous_poll) = device_repo
+        .record_poll(&request.device_code_hash)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(previous_poll.is_none());
+    let (_, previous_poll) = device_repo
+        .record_poll(&request.device_code_hash)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(previous_poll.is_some());
+
+    // Nothing to consume before the user approves
+    let pending = device_repo
+        .consume_device_code(&request.device_code_hash)
+        .await
+        .unwrap();
+    assert!(pending.is_none());
+
+    assert!(
+        device_repo
+            .find_pending("WDJBMJHT")
+            .await
+            .unwrap()
+            .is_some()
+    );
+    // The approving session signed in earlier
+    let authentication = Authentication {
+        acr: Acr::Password,
+        auth_time: now - Duration::minutes(10),
+    };
+    assert!(
+        device_repo
+            .decide("WDJBMJHT", user.id, true, authentication)
+            .await
+            .unwrap()
+    );
+    // A decision is final
+    assert!(
+        !device_repo
+            .decide(
+                "WDJBMJHT",
+                user.id,
+                false,
+                Authentication::now(Acr::Password)
+            )
+            .await
+            .unwrap()
+    );
+    assert!(
+        device_repo
+            .find_pending("WDJBMJHT")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let consumed = device_repo
+        .consume_device_code(&request.device_code_hash)
+        .await
+        .unwrap();
+    let consumed = consumed.unwrap();
+    assert_eq!(consumed.user_id, Some(user.id));
+    assert_eq!(
+        consumed.auth_time.map(|auth_time| auth_time.timestamp()),
+        Some(authentication.auth_time.timestamp())
+    );
+
+    let replayed = device_repo
+        .consume_device_code(&request.device_code_hash)
+        .await
+        .unwrap();
+    assert!(replayed.is_none());
+}
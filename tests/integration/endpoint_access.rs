@@ -0,0 +1,150 @@
+/* Endpoint access integration test */
+
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::Body;
+use axum::http::{Request, StatusCode, header::AUTHORIZATION};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::fixtures::{auth_user, device_info};
+use crate::{client_settings, get_test_db_pool};
+
+use gandalf::adapters::dtos::{ClientGrant, SessionTokens};
+use gandalf::adapters::repositories::{PgUserRepository, UserRepository};
+use gandalf::app_modules::AppState;
+use gandalf::app_modules::api::v1::routes::v1_routes;
+use gandalf::config::database::PgPool;
+use gandalf::domain::models::{AccessRange, Acr, Authentication, Client, GrantType, User};
+
+// Signs the user in, to the client of `grant` or first-party
+async fn sign_in(
+    pool: &Arc<PgPool>,
+    state: &AppState,
+    user_id: Uuid,
+    grant: Option<ClientGrant<'_>>,
+) -> SessionTokens {
+    let user = PgUserRepository::new(pool.clone())
+        .find_auth_user_by_id(&user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    state
+        .auth_service
+        .make_session(
+            user,
+            "127.0.0.1".parse().unwrap(),
+            device_info(),
+            None,
+            grant,
+            Authentication::now(Acr::Password),
+        )
+        .await
+        .unwrap()
+}
+
+fn grant<'a>(client: &'a Client, scope: &'a str) -> Option<ClientGrant<'a>> {
+    Some(ClientGrant { client, scope })
+}
+
+async fn get(state: &AppState, path: &str, tokens: &SessionTokens) -> StatusCode {
+    let app = Router::new().nest("/api/v1", v1_routes().with_state(state.clone()));
+    let request = Request::get(format!("/api/v1{path}"))
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.access_token))
+        .body(Body::empty())
+        .unwrap();
+    app.oneshot(request).await.unwrap().status()
+}
+
+async fn third_party_client(state: &AppState) -> Client {
+    let (client, _) = state
+        .client_service
+        .register_client(
+            client_settings(
+                "third-party",
+                vec!["https://app.example.com/callback".to_string()],
+                vec![GrantType::AuthorizationCode],
+            ),
+            false,
+        )
+        .await
+        .unwrap();
+    client
+}
+
+#[tokio::test]
+async fn tokens_of_admins_signed_in_to_a_client_are_not_admin_tokens() {
+    let pool = get_test_db_pool().await;
+    let state = AppState::new(pool.clone()).await;
+
+    let admin = User {
+        access_range: AccessRange::Global,
+        password_hash: Some("unused".to_string()),
+        ..User::new("admin@mail.com".to_string())
+    };
+    PgUserRepository::new(pool.clone())
+        .save(&admin)
+        .await
+        .unwrap();
+    let third_party = third_party_client(&state).await;
+    let first_party = sign_in(&pool, &state, admin.id, None).await;
+    let signed_in_to_client =
+        sign_in(&pool, &state, admin.id, grant(&third_party, "openid email")).await;
+
+    assert_eq!(
+        get(&state, "/admin/clients", &first_party).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get(&state, "/admin/clients", &signed_in_to_client).await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn tokens_issued_to_a_client_are_rejected_by_first_party_endpoints() {
+    let pool = get_test_db_pool().await;
+    let state = AppState::new(pool.clone()).await;
+    let user_id = auth_user(pool.clone(), "user@mail.com").await.id;
+    let third_party = third_party_client(&state).await;
+    let first_party = sign_in(&pool, &state, user_id, None).await;
+    let signed_in_to_client =
+        sign_in(&pool, &state, user_id, grant(&third_party, "openid email")).await;
+
+    assert_eq!(
+        get(&state, "/me/sessions", &first_party).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get(&state, "/me/sessions", &signed_in_to_client).await,
+        StatusCode::FORBIDDEN
+    );
+    // Its `openid` scope opens the OpenID Connect endpoints
+    assert_eq!(
+        get(&state, "/userinfo", &signed_in_to_client).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn userinfo_needs_the_openid_scope() {
+    let pool = get_test_db_pool().await;
+    let state = AppState::new(pool.clone()).await;
+    let user_id = auth_user(pool.clone(), "userinfo@mail.com").await.id;
+    let third_party = third_party_client(&state).await;
+
+    let openid = sign_in(&pool, &state, user_id, grant(&third_party, "openid")).await;
+    let email_only = sign_in(&pool, &state, user_id, grant(&third_party, "email")).await;
+    let first_party = sign_in(&pool, &state, user_id, None).await;
+
+    assert_eq!(get(&state, "/userinfo", &openid).await, StatusCode::OK);
+    assert_eq!(
+        get(&state, "/userinfo", &email_only).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        get(&state, "/userinfo", &first_party).await,
+        StatusCode::FORBIDDEN
+    );
+}
@@ -0,0 +1,143 @@
+/* Group repository integration test */
+
+use uuid::Uuid;
+
+use crate::get_test_db_pool;
+
+use gandalf::adapters::dtos::MemberAddition;
+use gandalf::adapters::repositories::{
+    GroupRepository, PgGroupRepository, PgUserRepository, UserRepository,
+};
+use gandalf::domain::models::{Group, Principal, User};
+
+async fn new_group(repo: &PgGroupRepository, name: &str) -> Group {
+    let group = Group::new(format!("{name}-{}", Uuid::new_v4()), None);
+    assert!(repo.create_group(&group).await.unwrap());
+    group
+}
+
+#[tokio::test]
+async fn group_names_are_unique() {
+    let pool = get_test_db_pool().await;
+    let repo = PgGroupRepository::new(pool);
+
+    let group = new_group(&repo, "unique").await;
+    let duplicate = Group::new(group.name.clone(), Some("Again".to_string()));
+
+    assert!(!repo.create_group(&duplicate).await.unwrap());
+}
+
+#[tokio::test]
+async fn nested_members_belong_to_every_enclosing_group() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let repo = PgGroupRepository::new(pool);
+
+    let user = User::new("nested-member@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+    let company = new_group(&repo, "company").await;
+    let engineering = new_group(&repo, "engineering").await;
+    let backend = new_group(&repo, "backend").await;
+
+    let member = Principal::User(user.id);
+    assert_eq!(
+        repo.add_member(backend.id, member).await.unwrap(),
+        MemberAddition::Added
+    );
+    assert_eq!(
+        repo.add_member(backend.id, member).await.unwrap(),
+        MemberAddition::AlreadyMember
+    );
+    repo.add_member(engineering.id, Principal::Group(backend.id))
+        .await
+        .unwrap();
+    repo.add_member(company.id, Principal::Group(engineering.id))
+        .await
+        .unwrap();
+
+    let mut groups: Vec<_> = repo
+        .list_effective_groups(member)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|group| group.id)
+        .collect();
+    groups.sort();
+    let mut expected = vec![company.id, engineering.id, backend.id];
+    expected.sort();
+    assert_eq!(groups, expected);
+
+    let members = repo.list_members(engineering.id).await.unwrap();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].member, Principal::Group(backend.id));
+
+    assert!(repo.remove_member(backend.id, member).await.unwrap());
+    assert!(!repo.remove_member(backend.id, member).await.unwrap());
+    assert!(repo.list_effective_groups(member).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn groups_can_not_contain_themselves() {
+    let pool = get_test_db_pool().await;
+    let repo = PgGroupRepository::new(pool);
+
+    let outer = new_group(&repo, "outer").await;
+    let middle = new_group(&repo, "middle").await;
+    let inner = new_group(&repo, "inner").await;
+    repo.add_member(outer.id, Principal::Group(middle.id))
+        .await
+        .unwrap();
+    repo.add_member(middle.id, Principal::Group(inner.id))
+        .await
+        .unwrap();
+
+    for (group, member) in [
+        (outer.id, outer.id),
+        (inner.id, outer.id),
+        (inner.id, middle.id),
+    ] {
+        assert_eq!(
+            repo.add_member(group, Principal::Group(member))
+                .await
+                .unwrap(),
+            MemberAddition::Cycle
+        );
+    }
+    assert!(repo.list_members(inner.id).await.unwrap().is_empty());
+
+    // Diamonds are no cycles
+    assert_eq!(
+        repo.add_member(outer.id, Principal::Group(inner.id))
+            .await
+            .unwrap(),
+        MemberAddition::Added
+    );
+}
+
+#[tokio::test]
+async fn deleting_a_group_removes_it_from_its_parents() {
+    let pool = get_test_db_pool().await;
+    let repo = PgGroupRepository::new(pool);
+
+    let parent = new_group(&repo, "parent").await;
+    let child = new_group(&repo, "child").await;
+    let grandchild = new_group(&repo, "grandchild").await;
+    repo.add_member(parent.id, Principal::Group(child.id))
+        .await
+        .unwrap();
+    repo.add_member(child.id, Principal::Group(grandchild.id))
+        .await
+        .unwrap();
+
+    assert!(repo.delete_group(child.id).await.unwrap());
+    assert!(!repo.delete_group(child.id).await.unwrap());
+
+    assert!(repo.find_group(child.id).await.unwrap().is_none());
+    assert!(repo.list_members(parent.id).await.unwrap().is_empty());
+    assert!(
+        repo.list_effective_groups(Principal::Group(grandchild.id))
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
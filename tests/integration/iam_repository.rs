@@ -0,0 +1,225 @@
+/* IAM repository integration tests */
+
+use crate::get_test_db_pool;
+
+use gandalf::adapters::repositories::{
+    IamRepository, PgIamRepository, PgUserRepository, UserRepository,
+};
+use gandalf::domain::models::{
+    Decision, Effect, Grant, Permission, Principal, Resource, ResourceType, Role, RoleAssignment,
+    User,
+};
+use gandalf::domain::services::AuthorizationService;
+use gandalf::domain::services::errors::Error;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[tokio::test]
+async fn permissions_merge_the_actions_of_every_role_held() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let iam_repo = PgIamRepository::new(pool.clone());
+
+    let user = User::new("iam@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+
+    let channel = ResourceType::new(
+        "iam-chat".to_string(),
+        "channel".to_string(),
+        strings(&["read", "write", "delete"]),
+    );
+    iam_repo.create_resource_type(&channel).await.unwrap();
+    let general = Resource::new(&channel, "general".to_string());
+    let random = Resource::new(&channel, "random".to_string());
+    iam_repo.create_resource(&general).await.unwrap();
+    iam_repo.create_resource(&random).await.unwrap();
+    let reader = Role::new(&channel, "reader".to_string(), strings(&["read"]));
+    let writer = Role::new(&channel, "writer".to_string(), strings(&["read", "write"]));
+    iam_repo.create_role(&reader).await.unwrap();
+    iam_repo.create_role(&writer).await.unwrap();
+
+    let principal = Principal::User(user.id);
+    for (role, resource) in [(&reader, &general), (&writer, &general), (&reader, &random)] {
+        let assignment = RoleAssignment::new(principal, role, resource);
+        assert!(iam_repo.assign_role(&assignment).await.unwrap());
+    }
+    assert!(
+        !iam_repo
+            .assign_role(&RoleAssignment::new(principal, &reader, &random))
+            .await
+            .unwrap()
+    );
+
+    let found = iam_repo
+        .find_resource_type("iam-chat", "channel")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.actions, strings(&["delete", "read", "write"]));
+
+    let permissions = iam_repo.list_permissions(principal).await.unwrap();
+    assert_eq!(
+        permissions,
+        vec![
+            Permission {
+                resource_server: "iam-chat".to_string(),
+                resource: "channel/general".to_string(),
+                actions: strings(&["read", "write"]),
+            },
+            Permission {
+                resource_server: "iam-chat".to_string(),
+                resource: "channel/random".to_string(),
+                actions: strings(&["read"]),
+            },
+        ]
+    );
+
+    assert!(
+        iam_repo
+            .unassign_role(principal, reader.id, random.id)
+            .await
+            .unwrap()
+    );
+    assert_eq!(iam_repo.list_permissions(principal).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn roles_only_take_actions_of_their_resource_type() {
+    let pool = get_test_db_pool().await;
+    let iam_repo = PgIamRepository::new(pool.clone());
+
+    let folder = ResourceType::new(
+        "iam-files".to_string(),
+        "folder".to_string(),
+        strings(&["read"]),
+    );
+    iam_repo.create_resource_type(&folder).await.unwrap();
+
+    let admin = Role::new(&folder, "admin".to_string(), strings(&["read", "share"]));
+    assert!(iam_repo.create_role(&admin).await.is_err());
+}
+
+#[tokio::test]
+async fn authorization_checks_report_the_granting_role() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let iam_repo = PgIamRepository::new(pool.clone());
+    let authorization = AuthorizationService::new(pool.clone());
+
+    let user = User::new("authz@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+    let channel = ResourceType::new(
+        "authz-chat".to_string(),
+        "channel".to_string(),
+        strings(&["read", "write"]),
+    );
+    iam_repo.create_resource_type(&channel).await.unwrap();
+    let general = Resource::new(&channel, "general".to_string());
+    iam_repo.create_resource(&general).await.unwrap();
+    let reader = Role::new(&channel, "reader".to_string(), strings(&["read"]));
+    iam_repo.create_role(&reader).await.unwrap();
+    let principal = Principal::User(user.id);
+    iam_repo
+        .assign_role(&RoleAssignment::new(principal, &reader, &general))
+        .await
+        .unwrap();
+
+    let allowed = authorization
+        .check(principal, "authz-chat", "channel/general", "read")
+        .await
+        .unwrap();
+    assert_eq!(
+        allowed,
+        Decision::allow(Grant {
+            role_id: reader.id,
+            role: "reader".to_string(),
+            resource: "channel/general".to_string(),
+            effect: Effect::Allow,
+        })
+    );
+
+    let denied = authorization
+        .check(principal, "authz-chat", "channel/general", "write")
+        .await
+        .unwrap();
+    assert_eq!(denied, Decision::deny());
+
+    let invalid = authorization
+        .check(principal, "authz-chat", "general", "read")
+        .await;
+    assert!(matches!(invalid, Err(Error::InvalidResource(_))));
+}
+
+#[tokio::test]
+async fn batch_checks_and_resource_listing_cover_many_resources() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let iam_repo = PgIamRepository::new(pool.clone());
+    let authorization = AuthorizationService::new(pool.clone());
+
+    let user = User::new("batch@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+    let document = ResourceType::new(
+        "batch-docs".to_string(),
+        "document".to_string(),
+        strings(&["read", "edit"]),
+    );
+    iam_repo.create_resource_type(&document).await.unwrap();
+    let editor = Role::new(&document, "editor".to_string(), strings(&["read", "edit"]));
+    let reader = Role::new(&document, "reader".to_string(), strings(&["read"]));
+    iam_repo.create_role(&editor).await.unwrap();
+    iam_repo.create_role(&reader).await.unwrap();
+
+    // Every other document is editable
+    let principal = Principal::User(user.id);
+    let mut editable = Vec::new();
+    for index in 0..5 {
+        let resource = Resource::new(&document, format!("doc-{index}"));
+        iam_repo.create_resource(&resource).await.unwrap();
+        let role = if index % 2 == 0 { &editor } else { &reader };
+        iam_repo
+            .assign_role(&RoleAssignment::new(principal, role, &resource))
+            .await
+            .unwrap();
+        if index % 2 == 0 {
+            editable.push(resource.id);
+        }
+    }
+
+    let keys: Vec<String> = (0..6)
+        .map(|index| format!("document/doc-{index}"))
+        .collect();
+    let checks: Vec<(&str, &str)> = keys.iter().map(|key| (key.as_str(), "edit")).collect();
+    let decisions = authorization
+        .check_many(principal, "batch-docs", &checks)
+        .await
+        .unwrap();
+    let allowed: Vec<bool> = decisions.iter().map(|decision| decision.allowed).collect();
+    assert_eq!(allowed, vec![true, false, true, false, true, false]);
+
+    editable.sort();
+    let first = authorization
+        .list_resources(principal, "batch-docs", "document", "edit", None, Some(2))
+        .await
+        .unwrap();
+    let first_ids: Vec<_> = first.resources.iter().map(|resource| resource.id).collect();
+    assert_eq!(first_ids, editable[..2]);
+    assert_eq!(first.next_cursor, Some(editable[1]));
+
+    let last = authorization
+        .list_resources(
+            principal,
+            "batch-docs",
+            "document",
+            "edit",
+            first.next_cursor,
+            Some(2),
+        )
+        .await
+        .unwrap();
+    let last_ids: Vec<_> = last.resources.iter().map(|resource| resource.id).collect();
+    assert_eq!(last_ids, editable[2..]);
+    assert_eq!(last.next_cursor, None);
+}
@@ -1,3 +1,19 @@
 /* Integration tests module */
 
+mod access_token_verification;
+mod account_management;
+mod authorization_code_flow;
+mod authorization_code_repository;
+mod authorization_evaluation;
+mod client_authentication;
+mod device_code_repository;
+mod endpoint_access;
+mod group_repository;
+mod iam_repository;
+mod revoked_token_repository;
+mod session_limits;
+mod session_repository;
+mod signing_key_repository;
+mod token_exchange;
+mod token_revocation;
 mod user_registration;
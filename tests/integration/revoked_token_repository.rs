@@ -0,0 +1,48 @@
+/* Revoked token repository integration tests */
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::get_test_db_pool;
+
+use gandalf::adapters::repositories::{PgRevokedTokenRepository, RevokedTokenRepository};
+use gandalf::domain::models::RevokedToken;
+
+#[tokio::test]
+async fn revoked_tokens_keep_the_later_expiry_until_purged() {
+    let pool = get_test_db_pool().await;
+    let repo = PgRevokedTokenRepository::new(pool.clone());
+    let now = Utc::now();
+    let session_id = Uuid::new_v4();
+    let jti = Uuid::new_v4().to_string();
+
+    repo.revoke(&[
+        RevokedToken::sid(session_id, now + Duration::minutes(15)),
+        RevokedToken::jti(&jti, now - Duration::minutes(1)),
+    ])
+    .await
+    .unwrap();
+    repo.revoke(&[RevokedToken::sid(session_id, now + Duration::minutes(5))])
+        .await
+        .unwrap();
+
+    let revoked = repo.list_revoked().await.unwrap();
+    let session = revoked
+        .iter()
+        .find(|revoked| revoked.value == session_id.to_string())
+        .unwrap();
+    assert_eq!(
+        session.expires_at.timestamp(),
+        (now + Duration::minutes(15)).timestamp()
+    );
+    assert!(!revoked.iter().any(|revoked| revoked.value == jti));
+
+    // Only expired entries are purged
+    assert!(repo.purge_expired().await.unwrap() >= 1);
+    let revoked = repo.list_revoked().await.unwrap();
+    assert!(
+        revoked
+            .iter()
+            .any(|revoked| revoked.value == session_id.to_string())
+    );
+}
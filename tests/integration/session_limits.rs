@@ -0,0 +1,108 @@
+/* Session limit integration test */
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::fixtures::{auth_service, auth_user, device_info, test_config};
+use crate::get_test_db_pool;
+
+use gandalf::adapters::dtos::SessionTokens;
+use gandalf::adapters::repositories::{PgSessionRepository, PgUserRepository, SessionRepository};
+use gandalf::app_modules::auth::Error;
+use gandalf::config::app_config::SessionLimitPolicy;
+use gandalf::config::database::PgPool;
+use gandalf::domain::models::{Acr, Authentication};
+use gandalf::domain::services::{AuthService, UserService};
+
+// A service allowing two sessions per user, under `policy`
+async fn limited_auth(policy: SessionLimitPolicy) -> (Arc<PgPool>, AuthService) {
+    let pool = get_test_db_pool().await;
+    let config = test_config(|config| {
+        config.max_sessions_per_user = 2;
+        config.session_limit_policy = policy;
+    });
+    let auth = auth_service(pool.clone(), config);
+    (pool, auth)
+}
+
+async fn sign_in(
+    pool: &Arc<PgPool>,
+    auth: &AuthService,
+    user_id: Uuid,
+) -> Result<SessionTokens, Error> {
+    let user = PgUserRepository::new(pool.clone())
+        .find_auth_user_by_id(&user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    auth.make_session(
+        user,
+        "127.0.0.1".parse().unwrap(),
+        device_info(),
+        None,
+        None,
+        Authentication::now(Acr::Password),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn sign_in_past_the_limit_is_rejected() {
+    let (pool, auth) = limited_auth(SessionLimitPolicy::Reject).await;
+    let user_id = auth_user(pool.clone(), "reject@mail.com").await.id;
+
+    for _ in 0..2 {
+        sign_in(&pool, &auth, user_id).await.unwrap();
+    }
+    let rejected = sign_in(&pool, &auth, user_id).await;
+
+    assert!(matches!(rejected, Err(Error::SessionLimitReached)));
+    assert_eq!(auth.list_sessions(user_id).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn sign_in_past_the_limit_evicts_the_least_recently_active_session() {
+    let (pool, auth) = limited_auth(SessionLimitPolicy::EvictOldest).await;
+    let sessions = PgSessionRepository::new(pool.clone());
+    let user_id = auth_user(pool.clone(), "evict@mail.com").await.id;
+
+    let first = sign_in(&pool, &auth, user_id).await.unwrap().session_id;
+    let second = sign_in(&pool, &auth, user_id).await.unwrap().session_id;
+    // The first session is used again, the second is now the least recently active
+    sessions.update_last_active(first).await.unwrap();
+
+    let third = sign_in(&pool, &auth, user_id).await.unwrap().session_id;
+
+    let evicted = sessions.get_session_by_id(second).await.unwrap().unwrap();
+    assert!(evicted.is_revoked);
+    assert_eq!(evicted.revoked_reason.as_deref(), Some("evicted"));
+    let mut active: Vec<Uuid> = auth
+        .list_sessions(user_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|session| session.id)
+        .collect();
+    active.sort();
+    let mut expected = vec![first, third];
+    expected.sort();
+    assert_eq!(active, expected);
+}
+
+#[tokio::test]
+async fn user_session_limit_overrides_the_default() {
+    let (pool, auth) = limited_auth(SessionLimitPolicy::Reject).await;
+    let user_id = auth_user(pool.clone(), "override@mail.com").await.id;
+    UserService::new(pool.clone())
+        .set_session_limit(&user_id, Some(3))
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        sign_in(&pool, &auth, user_id).await.unwrap();
+    }
+    let rejected = sign_in(&pool, &auth, user_id).await;
+
+    assert!(matches!(rejected, Err(Error::SessionLimitReached)));
+}
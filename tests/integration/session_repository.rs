@@ -0,0 +1,203 @@
+/* Session repository integration test */
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::get_test_db_pool;
+
+use gandalf::adapters::repositories::{
+    PgSessionRepository, PgUserRepository, SessionRepository, UserRepository,
+};
+use gandalf::domain::models::{Acr, Session, User};
+
+fn new_session(user_id: Uuid) -> Session {
+    let now = Utc::now();
+    Session {
+        id: Uuid::new_v4(),
+        user_id,
+        refresh_token_hash: "initial".to_string(),
+        refresh_token_generation: 0,
+        device_identifier: None,
+        device_name: None,
+        device_type: None,
+        ip_address: "127.0.0.1".parse().unwrap(),
+        user_agent: None,
+        expires_at: now + Duration::hours(1),
+        created_at: now,
+        last_active_at: now,
+        is_revoked: false,
+        revoked_reason: None,
+        revoked_at: None,
+        client_id: None,
+        acr: Acr::Password,
+        auth_time: now,
+        scope: None,
+    }
+}
+
+#[tokio::test]
+async fn rotate_refresh_token_bumps_generation_once() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let session_repo = PgSessionRepository::new(pool.clone());
+
+    let user = User::new("rotate@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+    let session = new_session(user.id);
+    session_repo.create_session(&session).await.unwrap();
+
+    let rotated = session_repo
+        .rotate_refresh_token(session.id, 0, "second")
+        .await
+        .unwrap();
+    assert!(rotated);
+
+    // The same generation can not be rotated twice
+    let rotated_again = session_repo
+        .rotate_refresh_token(session.id, 0, "third")
+        .await
+        .unwrap();
+    assert!(!rotated_again);
+
+    let stored = session_repo
+        .get_session_by_id(session.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.refresh_token_generation, 1);
+    assert_eq!(stored.refresh_token_hash, "second");
+}
+
+#[tokio::test]
+async fn revoke_user_sessions_can_keep_current_session() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let session_repo = PgSessionRepository::new(pool.clone());
+
+    let user = User::new("logout@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+    let current = new_session(user.id);
+    let other = new_session(user.id);
+    session_repo.create_session(&current).await.unwrap();
+    session_repo.create_session(&other).await.unwrap();
+
+    let revoked = session_repo
+        .revoke_user_sessions(user.id, Some(current.id), Some("logout_all".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(revoked, vec![other.id]);
+
+    let current = session_repo
+        .get_session_by_id(current.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let other = session_repo
+        .get_session_by_id(other.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!current.is_revoked);
+    assert!(other.is_revoked);
+    assert_eq!(other.revoked_reason.as_deref(), Some("logout_all"));
+}
+
+#[tokio::test]
+async fn revoke_user_session_respects_ownership() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let session_repo = PgSessionRepository::new(pool.clone());
+
+    let owner = User::new("owner@mail.com".to_string());
+    let intruder = User::new("intruder@mail.com".to_string());
+    user_repo.save(&owner).await.unwrap();
+    user_repo.save(&intruder).await.unwrap();
+    let session = new_session(owner.id);
+    session_repo.create_session(&session).await.unwrap();
+
+    let revoked = session_repo
+        .revoke_user_session(intruder.id, session.id, None)
+        .await
+        .unwrap();
+    assert!(!revoked);
+    assert!(
+        session_repo
+            .list_active_sessions(intruder.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let revoked = session_repo
+        .revoke_user_session(owner.id, session.id, None)
+        .await
+        .unwrap();
+    assert!(revoked);
+    assert!(
+        session_repo
+            .list_active_sessions(owner.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn revoke_session_keeps_the_first_reason() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let session_repo = PgSessionRepository::new(pool.clone());
+
+    let user = User::new("revoked@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+    let session = new_session(user.id);
+    session_repo.create_session(&session).await.unwrap();
+
+    session_repo
+        .revoke_session(session.id, Some("refresh_token_reuse".to_string()))
+        .await
+        .unwrap();
+    session_repo
+        .revoke_session(session.id, Some("token_revoked".to_string()))
+        .await
+        .unwrap();
+
+    let stored = session_repo
+        .get_session_by_id(session.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(stored.is_revoked);
+    assert_eq!(
+        stored.revoked_reason.as_deref(),
+        Some("refresh_token_reuse")
+    );
+}
+
+#[tokio::test]
+async fn update_last_active_records_use() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let session_repo = PgSessionRepository::new(pool.clone());
+
+    let user = User::new("active@mail.com".to_string());
+    user_repo.save(&user).await.unwrap();
+    let session = new_session(user.id);
+    session_repo.create_session(&session).await.unwrap();
+    let created = session_repo
+        .get_session_by_id(session.id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    session_repo.update_last_active(session.id).await.unwrap();
+
+    let used = session_repo
+        .get_session_by_id(session.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(used.last_active_at > created.last_active_at);
+    assert_eq!(used.created_at, created.created_at);
+}
@@ -0,0 +1,80 @@
+/* Signing key repository integration test */
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::get_test_db_pool;
+
+use gandalf::adapters::repositories::{PgSigningKeyRepository, SigningKeyRepository};
+use gandalf::domain::models::{KeyState, SigningKey};
+
+fn new_key() -> SigningKey {
+    SigningKey {
+        kid: Uuid::new_v4().simple().to_string(),
+        algorithm: "ES256".to_string(),
+        key_state: KeyState::Next,
+        encrypted_private_key: Some(vec![1, 2, 3]),
+        created_at: Utc::now(),
+        activated_at: None,
+        retire_after: None,
+        retired_at: None,
+    }
+}
+
+fn state_of(keys: &[SigningKey], kid: &str) -> KeyState {
+    keys.iter().find(|key| key.kid == kid).unwrap().key_state
+}
+
+// Keys share one table, so the whole lifecycle is exercised in a single test
+#[tokio::test]
+async fn signing_keys_rotate_through_their_lifecycle() {
+    let pool = get_test_db_pool().await;
+    let repo = PgSigningKeyRepository::new(pool.clone());
+    let (first, second, third) = (new_key(), new_key(), new_key());
+    let earlier = Utc::now() - Duration::seconds(1);
+
+    // Bootstrap only rotates while there is no active key
+    let bootstrap = Some(DateTime::<Utc>::MIN_UTC);
+    assert!(
+        repo.is_rotation_due(DateTime::<Utc>::MIN_UTC)
+            .await
+            .unwrap()
+    );
+    assert!(repo.rotate_keys(&first, earlier, bootstrap).await.unwrap());
+    assert!(repo.rotate_keys(&second, earlier, bootstrap).await.unwrap());
+    assert!(!repo.rotate_keys(&third, earlier, bootstrap).await.unwrap());
+    assert!(
+        !repo
+            .is_rotation_due(DateTime::<Utc>::MIN_UTC)
+            .await
+            .unwrap()
+    );
+    assert!(
+        repo.is_rotation_due(Utc::now() + Duration::seconds(1))
+            .await
+            .unwrap()
+    );
+
+    let keys = repo.list_usable_keys().await.unwrap();
+    assert_eq!(state_of(&keys, &first.kid), KeyState::Active);
+    assert_eq!(state_of(&keys, &second.kid), KeyState::Next);
+
+    assert!(repo.rotate_keys(&third, earlier, None).await.unwrap());
+
+    let keys = repo.list_usable_keys().await.unwrap();
+    assert_eq!(state_of(&keys, &first.kid), KeyState::Retiring);
+    assert_eq!(state_of(&keys, &second.kid), KeyState::Active);
+    assert_eq!(state_of(&keys, &third.kid), KeyState::Next);
+
+    // The retiring key has outlived its tokens
+    assert_eq!(repo.retire_expired_keys().await.unwrap(), 1);
+
+    let keys = repo.list_keys().await.unwrap();
+    let retired = keys.iter().find(|key| key.kid == first.kid).unwrap();
+    assert_eq!(retired.key_state, KeyState::Retired);
+    assert!(retired.encrypted_private_key.is_none());
+    assert!(retired.retired_at.is_some());
+
+    let usable = repo.list_usable_keys().await.unwrap();
+    assert!(usable.iter().all(|key| key.kid != first.kid));
+}
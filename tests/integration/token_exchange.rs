@@ -0,0 +1,294 @@
+/* Token exchange integration test */
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::fixtures::{auth_service, auth_user, device_info, test_config};
+use crate::{client_settings, get_test_db_pool};
+
+use gandalf::config::database::PgPool;
+
+use gandalf::adapters::dtos::{ClientGrant, ClientSettings, TokenExchangeRequest};
+use gandalf::adapters::repositories::{IamRepository, PgIamRepository, PgUserRepository};
+use gandalf::app_modules::auth::Error;
+use gandalf::domain::models::{
+    Acr, Actor, Authentication, Client, GrantType, JwtClaims, Principal, Resource, ResourceAccess,
+    ResourceType, Role, RoleAssignment,
+};
+use gandalf::domain::services::{AuthService, ClientService, OAuthService};
+
+// A user signed in to `app`, which exchanges their token
+struct Exchange {
+    pool: Arc<PgPool>,
+    auth: Arc<AuthService>,
+    oauth: OAuthService,
+    clients: ClientService,
+    app: Client,
+    user_id: Uuid,
+    subject_token: String,
+    subject: JwtClaims,
+}
+
+impl Exchange {
+    async fn new(email: &str, configure: impl FnOnce(&mut ClientSettings)) -> Self {
+        let pool = get_test_db_pool().await;
+        let auth = Arc::new(auth_service(pool.clone(), test_config(|_| {})));
+        let clients = ClientService::new(pool.clone());
+
+        let mut settings = client_settings(
+            "app",
+            vec!["https://app.example.com/callback".to_string()],
+            vec![GrantType::AuthorizationCode, GrantType::TokenExchange],
+        );
+        configure(&mut settings);
+        let (app, _) = clients.register_client(settings, false).await.unwrap();
+
+        let user_id = auth_user(pool.clone(), email).await.id;
+        let subject_token = sign_in(&pool, &auth, user_id, &app).await;
+        let subject = auth.verify_access_token(&subject_token).await.unwrap();
+
+        Self {
+            oauth: OAuthService::new(pool.clone(), Arc::clone(&auth)),
+            pool,
+            auth,
+            clients,
+            app,
+            user_id,
+            subject_token,
+            subject,
+        }
+    }
+
+    async fn client(&self, name: &str, grant_types: Vec<GrantType>) -> Client {
+        self.clients
+            .register_client(client_settings(name, vec![], grant_types), false)
+            .await
+            .unwrap()
+            .0
+    }
+
+    async fn exchange(
+        &self,
+        client: &Client,
+        subject_token: &str,
+        actor_token: Option<&str>,
+        audience: Option<&str>,
+        scope: Option<&str>,
+    ) -> Result<(String, i64, String), Error> {
+        let request = TokenExchangeRequest {
+            subject_token: subject_token.to_string(),
+            actor_token: actor_token.map(str::to_string),
+            audience: audience.map(str::to_string),
+            scope: scope.map(str::to_string),
+        };
+        self.oauth.exchange_token(client, request).await
+    }
+}
+
+// Returns the access token of a new session of the user with `client`
+async fn sign_in(pool: &Arc<PgPool>, auth: &AuthService, user_id: Uuid, client: &Client) -> String {
+    let user = PgUserRepository::new(pool.clone())
+        .find_auth_user_by_id(&user_id)
+        .await
+        .unwrap()
+        .unwrap();
+    auth.make_session(
+        user,
+        "127.0.0.1".parse().unwrap(),
+        device_info(),
+        None,
+        Some(ClientGrant {
+            client,
+            scope: "openid email",
+        }),
+        Authentication::now(Acr::Password),
+    )
+    .await
+    .unwrap()
+    .access_token
+}
+
+#[tokio::test]
+async fn exchanged_scope_is_at_most_the_subjects_and_the_clients() {
+    let exchange = Exchange::new("scope@mail.com", |settings| {
+        settings.scopes.push("profile".to_string());
+    })
+    .await;
+    assert_eq!(exchange.subject.scope, "openid email");
+
+    let app = &exchange.app;
+    let token = &exchange.subject_token;
+    let wider = exchange
+        .exchange(app, token, None, None, Some("openid profile"))
+        .await;
+    assert!(matches!(wider, Err(Error::InvalidScope)));
+    let (_, _, scope) = exchange
+        .exchange(app, token, None, None, Some("openid"))
+        .await
+        .unwrap();
+    assert_eq!(scope, "openid");
+
+    // A client not registered for the subject's scope can't ask for it,
+    // and gets none of it by default
+    let settings = ClientSettings {
+        scopes: vec![],
+        ..client_settings("other", vec![], vec![GrantType::TokenExchange])
+    };
+    let (other, _) = exchange
+        .clients
+        .register_client(settings, false)
+        .await
+        .unwrap();
+    let unregistered = exchange
+        .exchange(&other, token, None, None, Some("openid"))
+        .await;
+    assert!(matches!(unregistered, Err(Error::InvalidScope)));
+    let (_, _, scope) = exchange
+        .exchange(&other, token, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(scope, "");
+}
+
+#[tokio::test]
+async fn exchanged_token_for_an_audience_only_carries_its_permissions() {
+    let exchange = Exchange::new("audience@mail.com", |_| {}).await;
+    let api = exchange
+        .client("api", vec![GrantType::ClientCredentials])
+        .await;
+
+    // The user holds roles on the api and on another resource server
+    let iam = PgIamRepository::new(exchange.pool.clone());
+    for server in [api.client_id.clone(), "elsewhere".to_string()] {
+        let project = ResourceType::new(server, "project".to_string(), vec!["read".into()]);
+        iam.create_resource_type(&project).await.unwrap();
+        let web = Resource::new(&project, "web".to_string());
+        iam.create_resource(&web).await.unwrap();
+        let reader = Role::new(&project, "reader".to_string(), vec!["read".into()]);
+        iam.create_role(&reader).await.unwrap();
+        let assignment = RoleAssignment::new(Principal::User(exchange.user_id), &reader, &web);
+        iam.assign_role(&assignment).await.unwrap();
+    }
+
+    // Only audiences the client is registered for may be asked for
+    let token = &exchange.subject_token;
+    let unregistered = exchange
+        .exchange(&exchange.app, token, None, Some(&api.client_id), None)
+        .await;
+    assert!(matches!(unregistered, Err(Error::InvalidTarget)));
+
+    let mut settings = client_settings(
+        "app",
+        exchange.app.redirect_uris.clone(),
+        exchange.app.grant_types.clone(),
+    );
+    settings.audiences = vec![api.client_id.clone()];
+    let app = exchange
+        .clients
+        .update_client(&exchange.app.client_id, settings, true)
+        .await
+        .unwrap();
+
+    // The subject token was issued before the roles, a new one holds them
+    let subject_token = sign_in(&exchange.pool, &exchange.auth, exchange.user_id, &app).await;
+    let (exchanged, _, _) = exchange
+        .exchange(&app, &subject_token, None, Some(&api.client_id), None)
+        .await
+        .unwrap();
+    let claims = exchange
+        .auth
+        .verify_exchangeable_token(&exchanged, &api.client_id)
+        .await
+        .unwrap();
+
+    let expected: ResourceAccess = [(
+        api.client_id.clone(),
+        [("project/web".to_string(), vec!["read".to_string()])].into(),
+    )]
+    .into();
+    assert_eq!(claims.aud, api.client_id);
+    assert_eq!(claims.resource_access, expected);
+}
+
+#[tokio::test]
+async fn exchanged_token_never_outlives_the_subject_token() {
+    let exchange = Exchange::new("lifetime@mail.com", |_| {}).await;
+
+    let mut settings = client_settings("long-lived", vec![], vec![GrantType::TokenExchange]);
+    settings.access_token_lifetime = Some(60 * 60);
+    let (long_lived, _) = exchange
+        .clients
+        .register_client(settings, false)
+        .await
+        .unwrap();
+
+    let (exchanged, expires_in, _) = exchange
+        .exchange(&long_lived, &exchange.subject_token, None, None, None)
+        .await
+        .unwrap();
+    // Still addressed to the subject token's audience
+    let claims = exchange
+        .auth
+        .verify_exchangeable_token(&exchanged, &exchange.app.client_id)
+        .await
+        .unwrap();
+
+    assert_eq!(claims.exp, exchange.subject.exp);
+    assert!(expires_in < 60 * 60);
+}
+
+#[tokio::test]
+async fn actors_are_chained_most_recent_first() {
+    let exchange = Exchange::new("actors@mail.com", |_| {}).await;
+    let app = &exchange.app;
+
+    let mut actor_tokens = vec![];
+    for name in ["first-actor", "second-actor"] {
+        let actor = exchange
+            .client(name, vec![GrantType::ClientCredentials])
+            .await;
+        let token = exchange
+            .auth
+            .issue_client_token(&actor, "", ResourceAccess::new())
+            .unwrap();
+        actor_tokens.push((actor.client_id, token));
+    }
+    let [(first, first_token), (second, second_token)] = &actor_tokens[..] else {
+        unreachable!();
+    };
+
+    let (delegated, _, _) = exchange
+        .exchange(app, &exchange.subject_token, Some(first_token), None, None)
+        .await
+        .unwrap();
+    let (redelegated, _, _) = exchange
+        .exchange(app, &delegated, Some(second_token), None, None)
+        .await
+        .unwrap();
+    let claims = exchange
+        .auth
+        .verify_access_token(&redelegated)
+        .await
+        .unwrap();
+    let chain = Actor {
+        sub: second.clone(),
+        act: Some(Box::new(Actor {
+            sub: first.clone(),
+            act: None,
+        })),
+    };
+    assert_eq!(claims.act.as_ref(), Some(&chain));
+
+    // Exchanging without an actor keeps the chain
+    let (impersonated, _, _) = exchange
+        .exchange(app, &redelegated, None, None, None)
+        .await
+        .unwrap();
+    let claims = exchange
+        .auth
+        .verify_access_token(&impersonated)
+        .await
+        .unwrap();
+    assert_eq!(claims.act, Some(chain));
+}
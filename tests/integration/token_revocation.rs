@@ -0,0 +1,95 @@
+/* Token revocation integration test */
+
+use crate::fixtures::{auth_service, auth_user, device_info, test_config};
+use crate::{client_settings, get_test_db_pool};
+
+use gandalf::adapters::dtos::ClientGrant;
+use gandalf::domain::models::{Acr, Authentication, GrantType, TokenType};
+use gandalf::domain::services::ClientService;
+
+#[tokio::test]
+async fn tokens_are_only_revoked_by_their_client() {
+    let pool = get_test_db_pool().await;
+    let service = auth_service(pool.clone(), test_config(|_| {}));
+    let clients = ClientService::new(pool.clone());
+
+    let redirect_uris = vec!["https://app.example.com/callback".to_string()];
+    let (client, _) = clients
+        .register_client(
+            client_settings("app", redirect_uris.clone(), GrantType::defaults()),
+            false,
+        )
+        .await
+        .unwrap();
+    let (other, _) = clients
+        .register_client(
+            client_settings("other", redirect_uris, GrantType::defaults()),
+            false,
+        )
+        .await
+        .unwrap();
+
+    let user = auth_user(pool.clone(), "revoked@mail.com").await;
+    let tokens = service
+        .make_session(
+            user,
+            "127.0.0.1".parse().unwrap(),
+            device_info(),
+            None,
+            Some(ClientGrant {
+                client: &client,
+                scope: "openid",
+            }),
+            Authentication::now(Acr::Password),
+        )
+        .await
+        .unwrap();
+
+    // Another client's request is ignored, whatever the token type
+    for (token, hint) in [
+        (&tokens.refresh_token, Some(TokenType::Refresh)),
+        (&tokens.access_token, None),
+    ] {
+        service
+            .revoke_token(token, hint, &other.client_id)
+            .await
+            .unwrap();
+    }
+    assert!(
+        service
+            .verify_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+
+    // Neither does a refresh token that was already rotated out
+    let (_, refresh_token) = service
+        .refresh_session(&tokens.refresh_token, Some(&client))
+        .await
+        .unwrap();
+    service
+        .revoke_token(
+            &tokens.refresh_token,
+            Some(TokenType::Refresh),
+            &client.client_id,
+        )
+        .await
+        .unwrap();
+    assert!(
+        service
+            .verify_access_token(&tokens.access_token)
+            .await
+            .is_ok()
+    );
+
+    service
+        .revoke_token(&refresh_token, Some(TokenType::Refresh), &client.client_id)
+        .await
+        .unwrap();
+    assert!(
+        service
+            .verify_access_token(&tokens.access_token)
+            .await
+            .is_err()
+    );
+}
@@ -1,10 +1,13 @@
 /* User service integration test */
 
+use crate::fixtures::auth_user;
 use crate::get_test_db_pool;
 
-// use gandalf::domain::services::UserService;
 use gandalf::adapters::repositories::{PgUserRepository, UserRepository};
 use gandalf::domain::models::User;
+use gandalf::domain::services::UserService;
+use gandalf::domain::services::errors::Error;
+use uuid::Uuid;
 
 #[tokio::test]
 async fn check_email_exists() {
@@ -32,3 +35,24 @@ async fn create_user_succeeds() {
     let email_exists: bool = user_repo.email_exists(email).await.unwrap();
     assert!(email_exists);
 }
+
+#[tokio::test]
+async fn session_limit_can_be_overridden_per_user() {
+    let pool = get_test_db_pool().await;
+    let user_repo = PgUserRepository::new(pool.clone());
+    let service = UserService::new(pool.clone());
+
+    let user_id = auth_user(pool.clone(), "limited@mail.com").await.id;
+    let found = user_repo.find_auth_user_by_id(&user_id).await.unwrap();
+    assert_eq!(found.unwrap().max_sessions, None);
+
+    service.set_session_limit(&user_id, Some(2)).await.unwrap();
+    let found = user_repo.find_auth_user_by_id(&user_id).await.unwrap();
+    assert_eq!(found.unwrap().max_sessions, Some(2));
+
+    let negative = service.set_session_limit(&user_id, Some(-1)).await;
+    assert!(matches!(negative, Err(Error::InvalidSessionLimit)));
+
+    let unknown = service.set_session_limit(&Uuid::new_v4(), None).await;
+    assert!(matches!(unknown, Err(Error::UserNotFound)));
+}
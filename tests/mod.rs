@@ -4,4 +4,4 @@ mod _test_utils;
 mod integration;
 mod unit;
 
-pub use _test_utils::{database::get_test_db_pool, mocks};
+pub use _test_utils::{database::get_test_db_pool, fixtures, fixtures::client_settings, mocks};